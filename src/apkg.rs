@@ -0,0 +1,455 @@
+use std::{
+    env, fs,
+    io::{Error, Result},
+    process,
+};
+
+use rusqlite::{params, Connection};
+
+use crate::{clock, export::Card, json::Json};
+
+const DECK_ID: u64 = 1_597_000_000_001;
+const MODEL_ID: u64 = 1_597_000_000_002;
+
+// legacy schema 11, which every Anki version since 2.1 still imports
+const SCHEMA: &str = "
+    CREATE TABLE col (
+        id INTEGER PRIMARY KEY, crt INTEGER NOT NULL, mod INTEGER NOT NULL,
+        scm INTEGER NOT NULL, ver INTEGER NOT NULL, dty INTEGER NOT NULL,
+        usn INTEGER NOT NULL, ls INTEGER NOT NULL, conf TEXT NOT NULL,
+        models TEXT NOT NULL, decks TEXT NOT NULL, dconf TEXT NOT NULL, tags TEXT NOT NULL
+    );
+    CREATE TABLE notes (
+        id INTEGER PRIMARY KEY, guid TEXT NOT NULL, mid INTEGER NOT NULL,
+        mod INTEGER NOT NULL, usn INTEGER NOT NULL, tags TEXT NOT NULL, flds TEXT NOT NULL,
+        sfld INTEGER NOT NULL, csum INTEGER NOT NULL, flags INTEGER NOT NULL, data TEXT NOT NULL
+    );
+    CREATE TABLE cards (
+        id INTEGER PRIMARY KEY, nid INTEGER NOT NULL, did INTEGER NOT NULL,
+        ord INTEGER NOT NULL, mod INTEGER NOT NULL, usn INTEGER NOT NULL,
+        type INTEGER NOT NULL, queue INTEGER NOT NULL, due INTEGER NOT NULL,
+        ivl INTEGER NOT NULL, factor INTEGER NOT NULL, reps INTEGER NOT NULL,
+        lapses INTEGER NOT NULL, left INTEGER NOT NULL, odue INTEGER NOT NULL,
+        odid INTEGER NOT NULL, flags INTEGER NOT NULL, data TEXT NOT NULL
+    );
+    CREATE TABLE revlog (
+        id INTEGER PRIMARY KEY, cid INTEGER NOT NULL, usn INTEGER NOT NULL,
+        ease INTEGER NOT NULL, ivl INTEGER NOT NULL, lastIvl INTEGER NOT NULL,
+        factor INTEGER NOT NULL, time INTEGER NOT NULL, type INTEGER NOT NULL
+    );
+    CREATE TABLE graves (usn INTEGER NOT NULL, oid INTEGER NOT NULL, type INTEGER NOT NULL);
+    CREATE INDEX ix_notes_usn ON notes (usn);
+    CREATE INDEX ix_cards_usn ON cards (usn);
+    CREATE INDEX ix_revlog_usn ON revlog (usn);
+    CREATE INDEX ix_cards_nid ON cards (nid);
+    CREATE INDEX ix_cards_sched ON cards (did, queue, due);
+    CREATE INDEX ix_revlog_cid ON revlog (cid);
+    CREATE INDEX ix_notes_csum ON notes (csum);";
+
+pub fn package(cards: &[Card]) -> Result<Vec<u8>> {
+    let path = env::temp_dir().join(format!("chords-{}.anki2", process::id()));
+    let _ = fs::remove_file(&path);
+
+    let collection = Connection::open(&path)
+        .and_then(|connection| fill(&connection, cards))
+        .map_err(|error| Error::other(format!("anki collection: {error}")));
+    let collection = collection.and_then(|()| fs::read(&path));
+    let _ = fs::remove_file(&path);
+
+    Ok(zip(&[("collection.anki2", &collection?), ("media", b"{}")]))
+}
+
+fn fill(connection: &Connection, cards: &[Card]) -> rusqlite::Result<()> {
+    let now = clock::now();
+    let seconds = now / 1000;
+
+    connection.execute_batch(SCHEMA)?;
+    connection.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?3, 11, 0, 0, 0, ?4, ?5, ?6, ?7, '{}')",
+        params![
+            seconds,
+            now,
+            now,
+            collection_config().to_string(),
+            models(seconds).to_string(),
+            decks(seconds).to_string(),
+            deck_config().to_string(),
+        ],
+    )?;
+
+    for (card, position) in cards.iter().zip(0..) {
+        let id = now + position;
+        let tags = match card.tags.is_empty() {
+            true => String::new(),
+            false => format!(" {} ", card.tags.join(" ")),
+        };
+        let digest = sha1(card.word.as_bytes());
+
+        connection.execute(
+            "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')",
+            params![
+                id,
+                hex(&sha1(format!("{}\x1f{}", card.word, card.chord).as_bytes())[..8]),
+                MODEL_ID,
+                seconds,
+                tags,
+                [&card.word, &card.chord, &card.diagram]
+                    .map(String::as_str)
+                    .join("\x1f"),
+                card.word,
+                u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]),
+            ],
+        )?;
+        connection.execute(
+            "INSERT INTO cards VALUES (?1, ?1, ?2, 0, ?3, -1, 0, 0, ?4, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+            params![id, DECK_ID, seconds, position + 1],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn collection_config() -> Json {
+    Json::Object(vec![
+        ("nextPos", 1_u32.into()),
+        ("estTimes", true.into()),
+        ("activeDecks", Json::array([DECK_ID as f64])),
+        ("sortType", "noteFld".into()),
+        ("timeLim", 0_u32.into()),
+        ("sortBackwards", false.into()),
+        ("addToCur", true.into()),
+        ("curDeck", (DECK_ID as f64).into()),
+        ("newSpread", 0_u32.into()),
+        ("dueCounts", true.into()),
+        ("curModel", MODEL_ID.to_string().into()),
+        ("collapseTime", 1200_u32.into()),
+    ])
+}
+
+fn models(seconds: u64) -> Json {
+    let field = |name: &str, ord: u32| {
+        Json::Object(vec![
+            ("name", name.into()),
+            ("ord", ord.into()),
+            ("sticky", false.into()),
+            ("rtl", false.into()),
+            ("font", "Arial".into()),
+            ("size", 20_u32.into()),
+            ("media", Json::Array(Vec::new())),
+        ])
+    };
+    let template = Json::Object(vec![
+        ("name", "Chord".into()),
+        ("ord", 0_u32.into()),
+        ("qfmt", "{{Word}}".into()),
+        (
+            "afmt",
+            "{{FrontSide}}<hr id=answer>{{Chord}}<br>{{Diagram}}".into(),
+        ),
+        ("did", Json::Null),
+        ("bqfmt", "".into()),
+        ("bafmt", "".into()),
+    ]);
+
+    Json::Object(vec![(
+        // the key has to match MODEL_ID
+        "1597000000002",
+        Json::Object(vec![
+            ("id", (MODEL_ID as f64).into()),
+            ("name", "Chords".into()),
+            ("type", 0_u32.into()),
+            ("mod", (seconds as f64).into()),
+            ("usn", (-1.0).into()),
+            ("sortf", 0_u32.into()),
+            ("did", (DECK_ID as f64).into()),
+            ("tmpls", Json::Array(vec![template])),
+            (
+                "flds",
+                Json::Array(vec![
+                    field("Word", 0),
+                    field("Chord", 1),
+                    field("Diagram", 2),
+                ]),
+            ),
+            (
+                "css",
+                ".card { font-family: sans-serif; font-size: 24px; text-align: center; }".into(),
+            ),
+            ("latexPre", "".into()),
+            ("latexPost", "".into()),
+            ("tags", Json::Array(Vec::new())),
+            ("vers", Json::Array(Vec::new())),
+            (
+                "req",
+                Json::array([Json::array([
+                    Json::from(0_u32),
+                    "any".into(),
+                    Json::array([0_u32]),
+                ])]),
+            ),
+        ]),
+    )])
+}
+
+fn decks(seconds: u64) -> Json {
+    let deck = |id: u64, name: &str| {
+        Json::Object(vec![
+            ("id", (id as f64).into()),
+            ("name", name.into()),
+            ("desc", "".into()),
+            ("mod", (seconds as f64).into()),
+            ("usn", (-1.0).into()),
+            ("collapsed", false.into()),
+            ("newToday", Json::array([0_u32, 0])),
+            ("revToday", Json::array([0_u32, 0])),
+            ("lrnToday", Json::array([0_u32, 0])),
+            ("timeToday", Json::array([0_u32, 0])),
+            ("dyn", 0_u32.into()),
+            ("conf", 1_u32.into()),
+            ("extendNew", 10_u32.into()),
+            ("extendRev", 50_u32.into()),
+        ])
+    };
+
+    // the key has to match DECK_ID
+    Json::Object(vec![
+        ("1", deck(1, "Default")),
+        ("1597000000001", deck(DECK_ID, "Chords")),
+    ])
+}
+
+fn deck_config() -> Json {
+    Json::Object(vec![(
+        "1",
+        Json::Object(vec![
+            ("id", 1_u32.into()),
+            ("name", "Default".into()),
+            ("mod", 0_u32.into()),
+            ("usn", 0_u32.into()),
+            ("maxTaken", 60_u32.into()),
+            ("autoplay", true.into()),
+            ("timer", 0_u32.into()),
+            ("replayq", true.into()),
+            ("dyn", false.into()),
+            (
+                "new",
+                Json::Object(vec![
+                    ("delays", Json::array([1_u32, 10])),
+                    ("ints", Json::array([1_u32, 4, 7])),
+                    ("initialFactor", 2500_u32.into()),
+                    ("order", 1_u32.into()),
+                    ("perDay", 20_u32.into()),
+                    ("bury", true.into()),
+                ]),
+            ),
+            (
+                "rev",
+                Json::Object(vec![
+                    ("perDay", 200_u32.into()),
+                    ("ease4", 1.3.into()),
+                    ("fuzz", 0.05.into()),
+                    ("ivlFct", 1_u32.into()),
+                    ("maxIvl", 36500_u32.into()),
+                    ("bury", true.into()),
+                    ("hardFactor", 1.2.into()),
+                ]),
+            ),
+            (
+                "lapse",
+                Json::Object(vec![
+                    ("delays", Json::array([10_u32])),
+                    ("mult", 0_u32.into()),
+                    ("minInt", 1_u32.into()),
+                    ("leechFails", 8_u32.into()),
+                    ("leechAction", 0_u32.into()),
+                ]),
+            ),
+        ]),
+    )])
+}
+
+// an uncompressed zip archive, which Anki reads like a deflated one
+fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    const VERSION: u16 = 20;
+    const DATE: u16 = 0x21;
+
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, contents) in files {
+        let offset = archive.len() as u32;
+        let mut header = Vec::new();
+        header.extend(VERSION.to_le_bytes());
+        header.extend([0; 6]);
+        header.extend(DATE.to_le_bytes());
+        header.extend(crc32(contents).to_le_bytes());
+        header.extend((contents.len() as u32).to_le_bytes());
+        header.extend((contents.len() as u32).to_le_bytes());
+        header.extend((name.len() as u16).to_le_bytes());
+        header.extend([0; 2]);
+
+        archive.extend(0x0403_4b50_u32.to_le_bytes());
+        archive.extend(&header);
+        archive.extend(name.as_bytes());
+        archive.extend(*contents);
+
+        directory.extend(0x0201_4b50_u32.to_le_bytes());
+        directory.extend(VERSION.to_le_bytes());
+        directory.extend(&header);
+        directory.extend([0; 10]);
+        directory.extend(offset.to_le_bytes());
+        directory.extend(name.as_bytes());
+    }
+
+    let (offset, size) = (archive.len() as u32, directory.len() as u32);
+    let count = files.len() as u16;
+    archive.append(&mut directory);
+    archive.extend(0x0605_4b50_u32.to_le_bytes());
+    archive.extend([0; 4]);
+    archive.extend(count.to_le_bytes());
+    archive.extend(count.to_le_bytes());
+    archive.extend(size.to_le_bytes());
+    archive.extend(offset.to_le_bytes());
+    archive.extend([0; 2]);
+
+    archive
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, byte| {
+        (0..8).fold(crc ^ u32::from(*byte), |crc, _| {
+            (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+fn sha1(bytes: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [
+        0x6745_2301,
+        0xefcd_ab89,
+        0x98ba_dcfe,
+        0x1032_5476,
+        0xc3d2_e1f0,
+    ];
+
+    let mut message = bytes.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend((bytes.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0_u32; 80];
+        for (index, word) in block.chunks(4).enumerate() {
+            words[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for index in 16..80 {
+            words[index] =
+                (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16])
+                    .rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (index, word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, temp);
+        }
+
+        for (value, added) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(added);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (chunk, value) in digest.chunks_mut(4).zip(state) {
+        chunk.copy_from_slice(&value.to_be_bytes());
+    }
+
+    digest
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(
+            hex(&sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex(&sha1(&[b'a'; 100])),
+            "7f9000257a4918d7072655ea468540cdcbd42e0c"
+        );
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn package_notes_and_cards() {
+        let cards = [Card {
+            word: "the".to_owned(),
+            chord: "H+T".to_owned(),
+            diagram: "<svg></svg>".to_owned(),
+            tags: vec!["common".to_owned()],
+        }];
+
+        let archive = package(&cards).unwrap();
+        assert!(archive.starts_with(b"PK\x03\x04"));
+
+        // the central directory holds a 46 byte record per file
+        let end = archive.len() - 22;
+        let field = |at: usize| u32::from_le_bytes(archive[at..at + 4].try_into().unwrap());
+        assert_eq!(field(end), 0x0605_4b50);
+        assert_eq!(
+            field(end + 12) as usize,
+            2 * 46 + "collection.anki2media".len()
+        );
+        assert_eq!(field(field(end + 16) as usize), 0x0201_4b50);
+
+        // the collection is stored first, right after its 30 byte header and name
+        let size = u32::from_le_bytes(archive[18..22].try_into().unwrap()) as usize;
+        let start = 30 + "collection.anki2".len();
+        let collection = &archive[start..start + size];
+        assert_eq!(crc32(collection).to_le_bytes(), archive[14..18]);
+
+        let path = env::temp_dir().join(format!("chords-test-{}.anki2", process::id()));
+        fs::write(&path, collection).unwrap();
+        let connection = Connection::open(&path).unwrap();
+        let (fields, tags, sort, checksum): (String, String, String, u32) = connection
+            .query_row("SELECT flds, tags, sfld, csum FROM notes", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap();
+        let cards: u32 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM cards WHERE did = ?1",
+                [DECK_ID],
+                |row| row.get(0),
+            )
+            .unwrap();
+        drop(connection);
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(fields, "the\x1fH+T\x1f<svg></svg>");
+        assert_eq!(tags, " common ");
+        assert_eq!(sort, "the");
+        assert_eq!(checksum, 0xbbcc_df2e);
+        assert_eq!(cards, 1);
+    }
+}
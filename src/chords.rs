@@ -1,36 +1,41 @@
 use std::{
     char::ParseCharError,
     collections::{btree_map::IntoIter, BTreeMap, BTreeSet},
-    fs::{read_to_string, File},
-    io::{Result as IoResult, Write},
+    io::{Error, ErrorKind, Result as IoResult},
+    mem,
     path::Path,
     str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
+use crate::{
+    config::Config,
+    layout::Geometry,
+    normalize::Normalization,
+    storage::{read_to_string, write},
+};
+
+const FORMAT_VERSION: u32 = 2;
+const VERSION_PREFIX: &str = "# chords format ";
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Chord(String);
 
 impl Chord {
     pub fn insert(&mut self, key: char) -> bool {
         let key = key.to_ascii_uppercase();
-        if !key.is_ascii_uppercase() || self.0.contains(key) {
+        if !(key.is_ascii_uppercase() || key.is_ascii_digit()) {
             return false;
         }
-
-        if self.0.is_empty() {
-            self.0 = String::from(key)
-        } else {
-            match self.0.find(|char| char != '+' && key < char) {
-                Some(position) => {
-                    self.0.insert_str(position, &format!("{key}+"));
-                }
-                None => {
-                    self.0.push('+');
-                    self.0.push(key);
-                }
-            }
+        let key = key.to_string();
+        if self.keys().any(|other| other == key) {
+            return false;
         }
 
+        let mut keys: BTreeSet<_> = self.keys().map(str::to_owned).collect();
+        keys.insert(key);
+        self.0 = keys.into_iter().collect::<Vec<_>>().join("+");
+
         true
     }
 
@@ -38,11 +43,23 @@ impl Chord {
         self.0.as_str()
     }
 
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.split('+').filter(|key| !key.is_empty())
+    }
+
+    pub fn distance(&self, other: &Chord) -> usize {
+        let shared = self
+            .keys()
+            .filter(|key| other.keys().any(|other| other == *key));
+
+        self.keys().count() + other.keys().count() - 2 * shared.count()
+    }
+
     pub fn clear(&mut self) {
         self.0.clear();
     }
 
-    pub fn is_empty(&mut self) -> bool {
+    pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
 }
@@ -51,64 +68,439 @@ impl FromStr for Chord {
     type Err = ParseCharError;
 
     fn from_str(string: &str) -> Result<Self, Self::Err> {
-        let chords: Result<BTreeSet<_>, _> = string
+        let keys: Result<BTreeSet<_>, _> = string
             .split('+')
-            .map(|string| char::from_str(string.trim()).map(|char| char.to_ascii_uppercase()))
+            .map(str::trim)
+            .map(|key| match is_token(key) {
+                true => Ok(key.to_owned()),
+                false => char::from_str(key).map(|char| char.to_ascii_uppercase().to_string()),
+            })
             .collect();
 
-        let chars: Vec<_> = chords?.iter().map(ToString::to_string).collect();
+        Ok(Self(keys?.into_iter().collect::<Vec<_>>().join("+")))
+    }
+}
+
+fn is_token(key: &str) -> bool {
+    key.len() > 1
+        && key.starts_with(|char: char| char.is_ascii_uppercase())
+        && key
+            .chars()
+            .all(|char| char.is_ascii_uppercase() || char.is_ascii_digit() || char == '_')
+}
+
+#[derive(Clone)]
+pub struct Notation {
+    key_order: Vec<String>,
+    separator: String,
+    tokens: BTreeSet<String>,
+}
+
+impl Notation {
+    pub fn from_config(config: &Config) -> Self {
+        let custom = config.custom_keys.iter().map(|(token, _)| token.as_str());
+        let geometry = config.geometry.iter().flat_map(Geometry::names);
+
+        Self {
+            key_order: config.key_order.clone(),
+            separator: config.chord_separator.clone(),
+            tokens: custom
+                .chain(geometry)
+                .filter(|token| is_token(token))
+                .map(str::to_owned)
+                .collect(),
+        }
+    }
 
-        Ok(Self(chars.join("+")))
+    pub fn check(&self, chord: &Chord) -> IoResult<()> {
+        match chord
+            .keys()
+            .find(|key| is_token(key) && !self.tokens.contains(*key))
+        {
+            Some(key) => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "unknown key {key} in {}, declare it as key.{key} in the config",
+                    chord.as_str()
+                ),
+            )),
+            None => Ok(()),
+        }
     }
+
+    pub fn format(&self, chord: &Chord) -> String {
+        self.ordered(chord).join(&self.separator)
+    }
+
+    pub fn format_canonical(&self, chord: &Chord) -> String {
+        self.ordered(chord).join("+")
+    }
+
+    pub fn parse(&self, string: &str) -> IoResult<Chord> {
+        let invalid = |_| Error::new(ErrorKind::InvalidInput, format!("invalid chord {string}"));
+        if self.separator == "+" || string.contains('+') {
+            let chord = string.parse().map_err(invalid)?;
+            return self.check(&chord).map(|()| chord);
+        }
+
+        let keys: Vec<_> = match self.separator.trim() {
+            _ if self.separator.is_empty() => string
+                .chars()
+                .filter(|char| !char.is_whitespace())
+                .map(String::from)
+                .collect(),
+            "" => string.split_whitespace().map(str::to_owned).collect(),
+            separator => string.split(separator).map(str::to_owned).collect(),
+        };
+
+        let chord = keys.join("+").parse().map_err(invalid)?;
+        self.check(&chord).map(|()| chord)
+    }
+
+    fn ordered<'a>(&self, chord: &'a Chord) -> Vec<&'a str> {
+        let mut keys: Vec<_> = chord.keys().collect();
+        keys.sort_by_key(|key| {
+            self.key_order
+                .iter()
+                .position(|ordered| ordered == key)
+                .unwrap_or(usize::MAX)
+        });
+
+        keys
+    }
+}
+
+impl Default for Notation {
+    fn default() -> Self {
+        Self {
+            key_order: Vec::new(),
+            separator: String::from("+"),
+            tokens: BTreeSet::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Suppressed {
+    pub duplicate: bool,
+    pub near_miss: bool,
+}
+
+impl Suppressed {
+    const NAMES: [&'static str; 2] = ["duplicate", "near-miss"];
+
+    pub fn flag(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "duplicate" => Some(&mut self.duplicate),
+            "near-miss" => Some(&mut self.near_miss),
+            _ => None,
+        }
+    }
+
+    fn names(&self) -> Vec<&'static str> {
+        [self.duplicate, self.near_miss]
+            .into_iter()
+            .zip(Self::NAMES)
+            .filter_map(|(set, name)| set.then_some(name))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Metadata {
+    pub created: Option<u64>,
+    pub modified: Option<u64>,
+    pub suppressed: Suppressed,
+    pub tags: BTreeSet<String>,
+    pub note: Option<String>,
+}
+
+impl Metadata {
+    fn parse(fields: &str) -> IoResult<Self> {
+        let mut metadata = Self::default();
+
+        // the note takes the rest of the line, spaces included
+        let (fields, note) = match fields.trim_start().strip_prefix("note=") {
+            Some(note) => ("", Some(note)),
+            None => fields
+                .split_once(" note=")
+                .map_or((fields, None), |(fields, note)| (fields, Some(note))),
+        };
+        metadata.note = note.and_then(parse_note);
+
+        for field in fields.split_whitespace() {
+            let (name, value) = field.split_once('=').unwrap_or((field, ""));
+            let invalid = || {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid value '{value}' for field '{name}' in chords file"),
+                )
+            };
+
+            match name {
+                "created" => metadata.created = Some(value.parse().map_err(|_| invalid())?),
+                "modified" => metadata.modified = Some(value.parse().map_err(|_| invalid())?),
+                "suppress" => {
+                    for flag in value.split(',') {
+                        *metadata.suppressed.flag(flag).ok_or_else(invalid)? = true;
+                    }
+                }
+                "tags" => {
+                    for tag in value.split(',') {
+                        metadata.tags.insert(parse_tag(tag).ok_or_else(invalid)?);
+                    }
+                }
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("unknown field '{name}' in chords file"),
+                    ))
+                }
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    fn format(&self) -> String {
+        let mut fields: Vec<_> = [("created", self.created), ("modified", self.modified)]
+            .into_iter()
+            .filter_map(|(name, value)| Some(format!("{name}={}", value?)))
+            .collect();
+
+        let suppressed = self.suppressed.names();
+        if !suppressed.is_empty() {
+            fields.push(format!("suppress={}", suppressed.join(",")));
+        }
+        if !self.tags.is_empty() {
+            let tags: Vec<_> = self.tags.iter().map(String::as_str).collect();
+            fields.push(format!("tags={}", tags.join(",")));
+        }
+        if let Some(note) = &self.note {
+            fields.push(format!("note={note}"));
+        }
+
+        fields.join(" ")
+    }
+}
+
+pub fn parse_note(note: &str) -> Option<String> {
+    let note = note.trim();
+
+    (!note.is_empty() && !note.contains('|')).then(|| note.to_owned())
+}
+
+pub fn parse_tag(tag: &str) -> Option<String> {
+    let valid = !tag.is_empty()
+        && tag
+            .chars()
+            .all(|char| !char.is_whitespace() && !matches!(char, ',' | '=' | '|'));
+
+    valid.then(|| tag.to_lowercase())
 }
 
-pub struct Chords(BTreeMap<Chord, String>);
+#[derive(Clone, Default)]
+pub struct Chords {
+    entries: BTreeMap<Chord, String>,
+    metadata: BTreeMap<String, Metadata>,
+}
 
 impl Chords {
     pub fn read_from_file(path: impl AsRef<Path>) -> IoResult<Self> {
-        let lines = read_to_string(path)?;
+        Self::read_from_file_with_progress(path, &AtomicUsize::new(0))
+    }
 
-        let chords = lines
-            .split('\n')
-            .filter_map(|line| {
-                let mut split = line.split(':');
+    pub fn read_from_file_with_progress(
+        path: impl AsRef<Path>,
+        progress: &AtomicUsize,
+    ) -> IoResult<Self> {
+        Self::parse(&read_to_string(path)?, progress)
+    }
 
-                let chord: Chord = split.next()?.parse().ok()?;
-                let word = split.next()?.trim().to_owned();
+    fn parse(contents: &str, progress: &AtomicUsize) -> IoResult<Self> {
+        let mut lines = contents.split('\n').peekable();
+
+        let version = match lines
+            .peek()
+            .and_then(|line| line.strip_prefix(VERSION_PREFIX))
+        {
+            Some(version) => {
+                lines.next();
+                version.trim().parse().map_err(|_| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("invalid chords file version '{}'", version.trim()),
+                    )
+                })?
+            }
+            None => 1,
+        };
+        if version > FORMAT_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "chords file has format version {version}, only up to {FORMAT_VERSION} is supported"
+                ),
+            ));
+        }
 
-                Some((chord, word))
-            })
-            .collect();
+        let mut chords = Self::default();
+        for line in lines {
+            progress.fetch_add(1, Ordering::Relaxed);
+
+            let Some((chord, rest)) = line.split_once(':') else {
+                continue;
+            };
+            let Ok(chord) = chord.parse::<Chord>() else {
+                continue;
+            };
+            let (word, metadata) = match version {
+                1 => (rest, Metadata::default()),
+                _ => match rest.rsplit_once(" |") {
+                    Some((word, fields)) => (word, Metadata::parse(fields)?),
+                    None => (rest, Metadata::default()),
+                },
+            };
+            let word = word.trim().to_owned();
+
+            if metadata != Metadata::default() {
+                chords.metadata.insert(word.clone(), metadata);
+            }
+            chords.entries.insert(chord, word);
+        }
 
-        Ok(Self(chords))
+        Ok(chords)
     }
 
-    pub fn write_to_file(&self, path: impl AsRef<Path>) -> IoResult<()> {
-        let lines: Vec<_> = self
-            .0
-            .iter()
-            .map(|(chord, word)| format!("{chord}: {word}\n", chord = chord.as_str()))
-            .collect();
+    pub fn parse_str(contents: &str) -> IoResult<Self> {
+        Self::parse(contents, &AtomicUsize::new(0))
+    }
 
-        File::create(path)?.write_all(lines.concat().as_bytes())
+    pub fn write_to_file(&self, path: impl AsRef<Path>, notation: &Notation) -> IoResult<()> {
+        write(path, self.format(notation))
+    }
+
+    pub fn format(&self, notation: &Notation) -> String {
+        let mut lines = vec![format!("{VERSION_PREFIX}{FORMAT_VERSION}\n")];
+        lines.extend(self.entries.iter().map(|(chord, word)| {
+            let chord = notation.format_canonical(chord);
+            match self.metadata.get(word) {
+                Some(metadata) => format!("{chord}: {word} | {}\n", metadata.format()),
+                // the delimiter keeps a bar in the word from being read as metadata
+                None if word.contains(" |") => format!("{chord}: {word} |\n"),
+                None => format!("{chord}: {word}\n"),
+            }
+        }));
+
+        lines.concat()
     }
 
     pub fn iter(&self) -> IntoIter<Chord, String> {
-        self.0.clone().into_iter()
+        self.entries.clone().into_iter()
+    }
+
+    pub fn get(&self, chord: &Chord) -> Option<&String> {
+        self.entries.get(chord)
+    }
+
+    pub fn contains(&self, chord: &Chord) -> bool {
+        self.entries.contains_key(chord)
     }
 
     pub fn remove(&mut self, chord: &Chord) -> Option<String> {
-        self.0.remove(chord)
+        self.entries.remove(chord)
     }
 
     pub fn insert(&mut self, chord: Chord, word: String) -> Option<String> {
-        self.0.insert(chord, word)
+        self.entries.insert(chord, word)
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&Chord, &str) -> bool) {
+        self.entries.retain(|chord, word| keep(chord, word));
+
+        let words: BTreeSet<_> = self.entries.values().collect();
+        self.metadata.retain(|word, _| words.contains(word));
+    }
+
+    pub fn metadata(&self, word: &str) -> Metadata {
+        self.metadata.get(word).cloned().unwrap_or_default()
+    }
+
+    pub fn set_metadata(&mut self, word: &str, metadata: Metadata) {
+        if metadata == Metadata::default() {
+            self.metadata.remove(word);
+        } else {
+            self.metadata.insert(word.to_owned(), metadata);
+        }
+    }
+
+    pub fn normalize(&mut self, normalization: Normalization) {
+        for word in self.entries.values_mut() {
+            *word = normalization.apply(word).into_owned();
+        }
+        self.metadata = mem::take(&mut self.metadata)
+            .into_iter()
+            .map(|(word, metadata)| (normalization.apply(&word).into_owned(), metadata))
+            .collect();
+    }
+
+    pub fn inherit_metadata(&mut self, other: &Chords) {
+        let words: BTreeSet<_> = self.entries.values().collect();
+
+        for (word, metadata) in &other.metadata {
+            if !words.contains(word) {
+                continue;
+            }
+
+            let current = self.metadata.entry(word.clone()).or_default();
+            if *current == Metadata::default() || metadata.modified > current.modified {
+                current.clone_from(metadata);
+            }
+        }
+    }
+}
+
+impl FromIterator<(Chord, String)> for Chords {
+    fn from_iter<T: IntoIterator<Item = (Chord, String)>>(iter: T) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+            metadata: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Chords {
+    pub fn from_pairs(entries: &[(&str, &str)]) -> Self {
+        entries
+            .iter()
+            .map(|(chord, word)| (chord.parse().unwrap(), (*word).to_owned()))
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::layout::{Finger, Hand, Key};
+
+    fn declaring(tokens: &[&str]) -> Config {
+        let thumb = Key {
+            hand: Hand::Right,
+            finger: Finger::Thumb,
+            row: 3,
+            column: 0,
+            position: None,
+        };
+
+        Config {
+            custom_keys: tokens
+                .iter()
+                .map(|token| ((*token).to_owned(), thumb))
+                .collect(),
+            ..Config::default()
+        }
+    }
 
     #[test]
     fn parse_chords() {
@@ -119,6 +511,10 @@ mod tests {
         assert_eq!(chord1, chord2);
         assert_eq!(chord1.as_str(), "A+B+C");
         assert!(invalid.is_err());
+
+        let tokens: Chord = "e + DUP+T1".parse().unwrap();
+        assert_eq!(tokens.keys().collect::<Vec<_>>(), ["DUP", "E", "T1"]);
+        assert!("Dup+e".parse::<Chord>().is_err());
     }
 
     #[test]
@@ -151,4 +547,132 @@ mod tests {
         assert!(insert_empty.insert('d'));
         assert_eq!(insert_empty.as_str(), "D");
     }
+
+    #[test]
+    fn chord_distance() {
+        let chord: Chord = "A+B+C".parse().unwrap();
+        let extra: Chord = "A+B+C+D".parse().unwrap();
+        let substituted: Chord = "A+B+D".parse().unwrap();
+
+        assert_eq!(chord.distance(&chord), 0);
+        assert_eq!(chord.distance(&extra), 1);
+        assert_eq!(extra.distance(&chord), 1);
+        assert_eq!(chord.distance(&substituted), 2);
+    }
+
+    #[test]
+    fn format_with_notation() {
+        let chord: Chord = "A+H+S+T".parse().unwrap();
+        assert_eq!(Notation::default().format(&chord), "A+H+S+T");
+
+        let notation = |key_order: &[&str]| {
+            Notation::from_config(&Config {
+                key_order: key_order.iter().map(|key| (*key).to_owned()).collect(),
+                ..Config::default()
+            })
+        };
+        let steno = notation(&["S", "T", "K", "P", "W", "H", "R", "A"]);
+        assert_eq!(steno.format(&chord), "S+T+H+A");
+
+        let partial = notation(&["T"]);
+        assert_eq!(partial.format(&chord), "T+A+H+S");
+    }
+
+    #[test]
+    fn parse_with_separator() {
+        let chord: Chord = "A+H+S+T".parse().unwrap();
+        let notation = |separator: &str| {
+            Notation::from_config(&Config {
+                chord_separator: separator.to_owned(),
+                ..declaring(&["DUP"])
+            })
+        };
+
+        for (separator, written) in [("", "AHST"), (" ", "A H S T"), ("-", "A-H-S-T")] {
+            let notation = notation(separator);
+            assert_eq!(notation.format(&chord), written);
+            assert_eq!(notation.parse(written).unwrap(), chord);
+            assert_eq!(notation.parse("A+H+S+T").unwrap(), chord);
+            assert_eq!(notation.format_canonical(&chord), "A+H+S+T");
+        }
+        assert_eq!(notation(" ").parse("DUP E").unwrap().as_str(), "DUP+E");
+    }
+
+    #[test]
+    fn undeclared_tokens() {
+        let notation = Notation::from_config(&declaring(&["DUP"]));
+        assert!(notation.parse("DUP+E").is_ok());
+        assert!(notation.parse("TH+E").is_err());
+        assert!(Notation::default().parse("DUP+E").is_err());
+
+        let error = notation.check(&"TH+E".parse().unwrap()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown key TH in E+TH, declare it as key.TH in the config"
+        );
+    }
+
+    #[test]
+    fn migrate_chords_file() {
+        let parse = |contents| Chords::parse(contents, &AtomicUsize::new(0));
+
+        let legacy = parse("A+B: a | b\nC+D: c\n").unwrap();
+        assert_eq!(legacy.get(&"A+B".parse().unwrap()).unwrap(), "a | b");
+
+        let current = parse("# chords format 2\nA+B: a | b | created=1 modified=2\n").unwrap();
+        assert_eq!(current.get(&"A+B".parse().unwrap()).unwrap(), "a | b");
+        assert_eq!(current.metadata("a | b").created, Some(1));
+
+        let suppressed = parse("# chords format 2\nA+B: a | suppress=near-miss\n").unwrap();
+        assert!(suppressed.metadata("a").suppressed.near_miss);
+        assert!(!suppressed.metadata("a").suppressed.duplicate);
+
+        let tagged = parse("# chords format 2\nA+B: a | tags=Verb,common\n").unwrap();
+        let tags: Vec<_> = tagged.metadata("a").tags.into_iter().collect();
+        assert_eq!(tags, ["common", "verb"]);
+        assert_eq!(
+            tagged.format(&Notation::default()),
+            "# chords format 2\nA+B: a | tags=common,verb\n"
+        );
+
+        let noted =
+            "# chords format 2\nA+B: a | created=1 note=past tense of  see\nC+D: c | note=x\n";
+        let noted = parse(noted).unwrap();
+        assert_eq!(noted.metadata("a").created, Some(1));
+        assert_eq!(
+            noted.metadata("a").note.as_deref(),
+            Some("past tense of  see")
+        );
+        assert_eq!(
+            noted.format(&Notation::default()),
+            "# chords format 2\nA+B: a | created=1 note=past tense of  see\nC+D: c | note=x\n"
+        );
+
+        assert!(parse("# chords format 3\nA+B: a\n").is_err());
+        assert!(parse("# chords format 2\nA+B: a | colour=x\n").is_err());
+        assert!(parse("# chords format 2\nA+B: a | tags=x,,y\n").is_err());
+    }
+
+    #[test]
+    fn round_trip_bar_in_word() {
+        let mut chords = Chords::default();
+        chords.insert("A+B".parse().unwrap(), "a | b".to_owned());
+        chords.insert("C+D".parse().unwrap(), "c |".to_owned());
+        chords.insert("E+F".parse().unwrap(), "e | f".to_owned());
+        chords.set_metadata(
+            "e | f",
+            Metadata {
+                created: Some(1),
+                ..Metadata::default()
+            },
+        );
+
+        let parsed = Chords::parse_str(&chords.format(&Notation::default())).unwrap();
+
+        assert_eq!(parsed.get(&"A+B".parse().unwrap()).unwrap(), "a | b");
+        assert_eq!(parsed.get(&"C+D".parse().unwrap()).unwrap(), "c |");
+        assert_eq!(parsed.get(&"E+F".parse().unwrap()).unwrap(), "e | f");
+        assert_eq!(parsed.metadata("e | f").created, Some(1));
+        assert_eq!(parsed.metadata("a | b"), Metadata::default());
+    }
 }
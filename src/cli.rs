@@ -0,0 +1,680 @@
+use std::{
+    collections::BTreeMap,
+    io::{Error, ErrorKind, Result},
+    str::FromStr,
+};
+
+use crate::{
+    chords::parse_tag,
+    completions::Shell,
+    export::{Format, Subset},
+    import::{Columns, Order},
+    practice::Table,
+};
+
+const DEFAULT_SUGGESTIONS: usize = 5;
+const DEFAULT_UNCOVERED: usize = 20;
+const DEFAULT_RESULTS: usize = 20;
+const DEFAULT_PORT: u16 = 7654;
+const DEFAULT_MIN_COUNT: usize = 3;
+#[cfg(unix)]
+const DEFAULT_SOCKET: &str = "chords.sock";
+
+pub struct Usage {
+    pub name: &'static str,
+    pub options: &'static [&'static str],
+    pub flags: &'static [&'static str],
+    pub files: bool,
+}
+
+pub const USAGES: &[Usage] = &[
+    Usage {
+        name: "validate",
+        options: &[],
+        flags: &["json"],
+        files: false,
+    },
+    Usage {
+        name: "search",
+        options: &["limit"],
+        flags: &["json"],
+        files: false,
+    },
+    Usage {
+        name: "export",
+        options: &[
+            "format", "output", "max-rank", "tag", "min-keys", "max-keys",
+        ],
+        flags: &["list"],
+        files: false,
+    },
+    Usage {
+        name: "export-practice",
+        options: &["by"],
+        flags: &[],
+        files: false,
+    },
+    Usage {
+        name: "misfires",
+        options: &[],
+        flags: &[],
+        files: true,
+    },
+    Usage {
+        name: "stats",
+        options: &["corpus"],
+        flags: &["json"],
+        files: false,
+    },
+    Usage {
+        name: "import-words",
+        options: &["word-col", "count-col", "rank-col", "chord-col"],
+        flags: &["replace", "header"],
+        files: true,
+    },
+    Usage {
+        name: "reweight",
+        options: &["weight", "min-count"],
+        flags: &[],
+        files: true,
+    },
+    Usage {
+        name: "import",
+        options: &[],
+        flags: &[],
+        files: true,
+    },
+    Usage {
+        name: "suggest",
+        options: &["count"],
+        flags: &[],
+        files: false,
+    },
+    Usage {
+        name: "filter",
+        options: &["max-rank"],
+        flags: &["chorded", "unchorded"],
+        files: false,
+    },
+    Usage {
+        name: "simulate",
+        options: &["chord-time", "char-time"],
+        flags: &["json"],
+        files: true,
+    },
+    Usage {
+        name: "coverage",
+        options: &["top"],
+        flags: &["json"],
+        files: true,
+    },
+    Usage {
+        name: "substitute",
+        options: &[],
+        flags: &["apply"],
+        files: false,
+    },
+    Usage {
+        name: "migrate-layout",
+        options: &["from", "to", "mapping"],
+        flags: &["apply"],
+        files: false,
+    },
+    Usage {
+        name: "suppress",
+        options: &["warning"],
+        flags: &["clear"],
+        files: false,
+    },
+    Usage {
+        name: "orphans",
+        options: &[],
+        flags: &["delete", "restore"],
+        files: false,
+    },
+    Usage {
+        name: "merge",
+        options: &["base", "ours", "theirs", "output"],
+        flags: &[],
+        files: false,
+    },
+    Usage {
+        name: "serve",
+        options: &["port"],
+        flags: &[],
+        files: false,
+    },
+    #[cfg(unix)]
+    Usage {
+        name: "daemon",
+        options: &["socket"],
+        flags: &[],
+        files: false,
+    },
+    Usage {
+        name: "completions",
+        options: &[],
+        flags: &[],
+        files: false,
+    },
+];
+
+pub enum Command {
+    Tui,
+    Validate {
+        json: bool,
+    },
+    Search {
+        query: String,
+        limit: usize,
+        json: bool,
+    },
+    Export {
+        format: Format,
+        output: Option<String>,
+        subset: Subset,
+    },
+    ListFormats,
+    ExportPractice {
+        table: Table,
+    },
+    Misfires {
+        log: String,
+    },
+    Stats {
+        corpus: Option<String>,
+        json: bool,
+    },
+    ImportWords {
+        list: String,
+        columns: Option<Columns>,
+        replace: bool,
+    },
+    Reweight {
+        corpus: String,
+        weight: Option<f64>,
+        min_count: usize,
+    },
+    Import {
+        file: String,
+    },
+    Suggest {
+        word: String,
+        count: usize,
+    },
+    Filter {
+        chorded: Option<bool>,
+        max_rank: Option<usize>,
+    },
+    Coverage {
+        corpus: String,
+        top: usize,
+        json: bool,
+    },
+    Simulate {
+        corpus: String,
+        chord_time: Option<f64>,
+        char_time: Option<f64>,
+        json: bool,
+    },
+    Completions {
+        shell: Shell,
+    },
+    Serve {
+        port: u16,
+    },
+    #[cfg(unix)]
+    Daemon {
+        socket: String,
+    },
+    Substitute {
+        from: char,
+        to: char,
+        apply: bool,
+    },
+    MigrateLayout {
+        source: MappingSource,
+        apply: bool,
+    },
+    Suppress {
+        word: String,
+        warning: String,
+        clear: bool,
+    },
+    Orphans {
+        resolution: Option<Resolution>,
+    },
+    Merge {
+        base: String,
+        ours: String,
+        theirs: String,
+        output: Option<String>,
+    },
+}
+
+pub enum Resolution {
+    Delete,
+    Restore,
+}
+
+pub enum MappingSource {
+    Layouts { from: String, to: String },
+    File(String),
+}
+
+impl Command {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let Some(command) = args.next() else {
+            return Ok(Command::Tui);
+        };
+
+        match command.as_str() {
+            "validate" => {
+                let mut options = Options::parse(args, &["json"])?;
+                let json = options.flag("json");
+                options.finish()?;
+
+                Ok(Command::Validate { json })
+            }
+            "search" => {
+                let mut options = Options::parse(args, &["json"])?;
+                let query = options.argument("query")?;
+                let limit = options.optional("limit")?.unwrap_or(DEFAULT_RESULTS);
+                let json = options.flag("json");
+                options.finish()?;
+
+                Ok(Command::Search { query, limit, json })
+            }
+            "export" => {
+                let mut options = Options::parse(args, &["list"])?;
+                if options.flag("list") {
+                    options.finish()?;
+                    return Ok(Command::ListFormats);
+                }
+                let format = options.required("format")?.parse()?;
+                let output = options.optional("output")?;
+                let max_rank = options.optional("max-rank")?;
+                let tag = options
+                    .optional::<String>("tag")?
+                    .map(|tag| {
+                        parse_tag(&tag).ok_or_else(|| invalid_input(format!("Invalid tag {tag}")))
+                    })
+                    .transpose()?;
+                let min_keys = options.optional("min-keys")?.unwrap_or(1);
+                let max_keys = options.optional("max-keys")?.unwrap_or(usize::MAX);
+                options.finish()?;
+
+                Ok(Command::Export {
+                    format,
+                    output,
+                    subset: Subset {
+                        max_rank,
+                        tag,
+                        keys: min_keys..=max_keys,
+                    },
+                })
+            }
+            "export-practice" => {
+                let mut options = Options::parse(args, &[])?;
+                let table = options.optional::<Table>("by")?.unwrap_or(Table::Entries);
+                options.finish()?;
+
+                Ok(Command::ExportPractice { table })
+            }
+            "misfires" => {
+                let mut options = Options::parse(args, &[])?;
+                let log = options.argument("log")?;
+                options.finish()?;
+
+                Ok(Command::Misfires { log })
+            }
+            "stats" => {
+                let mut options = Options::parse(args, &["json"])?;
+                let corpus = options.optional("corpus")?;
+                let json = options.flag("json");
+                options.finish()?;
+
+                Ok(Command::Stats { corpus, json })
+            }
+            "import-words" => {
+                let mut options = Options::parse(args, &["replace", "header"])?;
+                let list = options.argument("list")?;
+                let word = options.optional("word-col")?;
+                let order = match (
+                    options.optional("count-col")?,
+                    options.optional("rank-col")?,
+                ) {
+                    (Some(_), Some(_)) => {
+                        return Err(invalid_input(
+                            "Options --count-col and --rank-col exclude each other".to_owned(),
+                        ))
+                    }
+                    (Some(column), None) => Some(Order::Count(column)),
+                    (None, Some(column)) => Some(Order::Rank(column)),
+                    (None, None) => None,
+                };
+                let chord = options.optional("chord-col")?;
+                let replace = options.flag("replace");
+                let header = options.flag("header");
+                options.finish()?;
+
+                let columns = (word.is_some() || order.is_some() || chord.is_some() || header)
+                    .then(|| Columns {
+                        word: word.unwrap_or_default(),
+                        order,
+                        chord,
+                        header,
+                    });
+
+                Ok(Command::ImportWords {
+                    list,
+                    columns,
+                    replace,
+                })
+            }
+            "reweight" => {
+                let mut options = Options::parse(args, &[])?;
+                let corpus = options.argument("corpus")?;
+                let weight = options.optional("weight")?;
+                let min_count = options.optional("min-count")?.unwrap_or(DEFAULT_MIN_COUNT);
+                options.finish()?;
+                if weight.is_some_and(|weight| !(0.0..=1.0).contains(&weight)) {
+                    return Err(invalid_input(
+                        "Option --weight must be between 0 and 1".to_owned(),
+                    ));
+                }
+
+                Ok(Command::Reweight {
+                    corpus,
+                    weight,
+                    min_count,
+                })
+            }
+            "import" => {
+                let mut options = Options::parse(args, &[])?;
+                let file = options.argument("file")?;
+                options.finish()?;
+
+                Ok(Command::Import { file })
+            }
+            "suggest" => {
+                let mut options = Options::parse(args, &[])?;
+                let word = options.argument("word")?;
+                let count = options.optional("count")?.unwrap_or(DEFAULT_SUGGESTIONS);
+                options.finish()?;
+
+                Ok(Command::Suggest { word, count })
+            }
+            "filter" => {
+                let mut options = Options::parse(args, &["chorded", "unchorded"])?;
+                let chorded = match (options.flag("chorded"), options.flag("unchorded")) {
+                    (true, true) => {
+                        return Err(invalid_input(String::from(
+                            "Expected at most one of --chorded and --unchorded",
+                        )))
+                    }
+                    (true, false) => Some(true),
+                    (false, true) => Some(false),
+                    (false, false) => None,
+                };
+                let max_rank = options.optional("max-rank")?;
+                options.finish()?;
+
+                Ok(Command::Filter { chorded, max_rank })
+            }
+            "coverage" => {
+                let mut options = Options::parse(args, &["json"])?;
+                let corpus = options.argument("corpus")?;
+                let top = options.optional("top")?.unwrap_or(DEFAULT_UNCOVERED);
+                let json = options.flag("json");
+                options.finish()?;
+
+                Ok(Command::Coverage { corpus, top, json })
+            }
+            "simulate" => {
+                let mut options = Options::parse(args, &["json"])?;
+                let corpus = options.argument("corpus")?;
+                let chord_time = options.optional("chord-time")?;
+                let char_time = options.optional("char-time")?;
+                let json = options.flag("json");
+                options.finish()?;
+
+                Ok(Command::Simulate {
+                    corpus,
+                    chord_time,
+                    char_time,
+                    json,
+                })
+            }
+            "serve" => {
+                let mut options = Options::parse(args, &[])?;
+                let port = options.optional("port")?.unwrap_or(DEFAULT_PORT);
+                options.finish()?;
+
+                Ok(Command::Serve { port })
+            }
+            #[cfg(unix)]
+            "daemon" => {
+                let mut options = Options::parse(args, &[])?;
+                let socket = options
+                    .optional("socket")?
+                    .unwrap_or_else(|| DEFAULT_SOCKET.to_owned());
+                options.finish()?;
+
+                Ok(Command::Daemon { socket })
+            }
+            #[cfg(not(unix))]
+            "daemon" => Err(invalid_input(String::from(
+                "The daemon listens on a Unix socket, which this platform does not support",
+            ))),
+            "completions" => {
+                let mut options = Options::parse(args, &[])?;
+                let shell = options.argument("shell")?.parse()?;
+                options.finish()?;
+
+                Ok(Command::Completions { shell })
+            }
+            "substitute" => {
+                let mut options = Options::parse(args, &["apply"])?;
+                let from = key(&options.argument("from")?)?;
+                let to = key(&options.argument("to")?)?;
+                let apply = options.flag("apply");
+                options.finish()?;
+
+                Ok(Command::Substitute { from, to, apply })
+            }
+            "migrate-layout" => {
+                let mut options = Options::parse(args, &["apply"])?;
+                let from = options.optional("from")?;
+                let to = options.optional("to")?;
+                let mapping = options.optional("mapping")?;
+                let apply = options.flag("apply");
+                options.finish()?;
+
+                let source = match (from, to, mapping) {
+                    (Some(from), Some(to), None) => MappingSource::Layouts { from, to },
+                    (None, None, Some(path)) => MappingSource::File(path),
+                    _ => {
+                        return Err(invalid_input(String::from(
+                            "Expected either --from and --to or --mapping",
+                        )))
+                    }
+                };
+
+                Ok(Command::MigrateLayout { source, apply })
+            }
+            "orphans" => {
+                let mut options = Options::parse(args, &["delete", "restore"])?;
+                let resolution = match (options.flag("delete"), options.flag("restore")) {
+                    (true, true) => {
+                        return Err(invalid_input(
+                            "Flags --delete and --restore exclude each other".to_owned(),
+                        ))
+                    }
+                    (true, false) => Some(Resolution::Delete),
+                    (false, true) => Some(Resolution::Restore),
+                    (false, false) => None,
+                };
+                options.finish()?;
+
+                Ok(Command::Orphans { resolution })
+            }
+            "suppress" => {
+                let mut options = Options::parse(args, &["clear"])?;
+                let word = options.argument("word")?;
+                let warning = options.required("warning")?;
+                let clear = options.flag("clear");
+                options.finish()?;
+
+                Ok(Command::Suppress {
+                    word,
+                    warning,
+                    clear,
+                })
+            }
+            "merge" => {
+                let mut options = Options::parse(args, &[])?;
+                let base = options.required("base")?;
+                let ours = options.required("ours")?;
+                let theirs = options.required("theirs")?;
+                let output = options.optional("output")?;
+                options.finish()?;
+
+                Ok(Command::Merge {
+                    base,
+                    ours,
+                    theirs,
+                    output,
+                })
+            }
+            command => Err(invalid_input(format!("Unknown command {command}"))),
+        }
+    }
+}
+
+struct Options {
+    values: BTreeMap<String, String>,
+    flags: Vec<String>,
+    positional: Vec<String>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>, flags: &[&str]) -> Result<Self> {
+        let mut options = Self {
+            values: BTreeMap::new(),
+            flags: Vec::new(),
+            positional: Vec::new(),
+        };
+
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                options.positional.push(arg);
+                continue;
+            };
+
+            if let Some((name, value)) = name.split_once('=') {
+                options.values.insert(name.to_owned(), value.to_owned());
+            } else if flags.contains(&name) {
+                options.flags.push(name.to_owned());
+            } else {
+                let value = args
+                    .next()
+                    .ok_or_else(|| invalid_input(format!("Missing value for option --{name}")))?;
+                options.values.insert(name.to_owned(), value);
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn required(&mut self, name: &str) -> Result<String> {
+        self.values
+            .remove(name)
+            .ok_or_else(|| invalid_input(format!("Missing option --{name}")))
+    }
+
+    fn argument(&mut self, name: &str) -> Result<String> {
+        if self.positional.is_empty() {
+            return Err(invalid_input(format!("Missing argument <{name}>")));
+        }
+
+        Ok(self.positional.remove(0))
+    }
+
+    fn optional<T: FromStr>(&mut self, name: &str) -> Result<Option<T>> {
+        self.values
+            .remove(name)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    invalid_input(format!("Invalid value {value} for option --{name}"))
+                })
+            })
+            .transpose()
+    }
+
+    fn flag(&mut self, name: &str) -> bool {
+        let set = self.flags.iter().any(|flag| flag == name);
+        self.flags.retain(|flag| flag != name);
+
+        set
+    }
+
+    fn finish(self) -> Result<()> {
+        if let Some(name) = self.values.keys().chain(&self.flags).next() {
+            return Err(invalid_input(format!("Unknown option --{name}")));
+        }
+        if let Some(arg) = self.positional.first() {
+            return Err(invalid_input(format!("Unexpected argument {arg}")));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    let flag = format!("--{name}");
+    let len = args.len();
+    args.retain(|arg| *arg != flag);
+
+    args.len() != len
+}
+
+fn key(argument: &str) -> Result<char> {
+    argument
+        .parse()
+        .map_err(|_| invalid_input(format!("Invalid key {argument}, expected a single key")))
+}
+
+pub fn invalid_input(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usages_match_commands() {
+        for usage in USAGES {
+            let args = [usage.name.to_owned(), String::from("--unknown-option")];
+            let Err(error) = Command::parse(args.into_iter()) else {
+                panic!("{} accepted an unknown option", usage.name);
+            };
+
+            assert!(!error.to_string().starts_with("Unknown command"));
+        }
+    }
+
+    #[test]
+    fn take_global_flag() {
+        let mut args = vec![
+            String::from("validate"),
+            String::from("--quiet"),
+            String::from("--json"),
+        ];
+
+        assert!(take_flag(&mut args, "quiet"));
+        assert!(!take_flag(&mut args, "quiet"));
+        assert_eq!(args, ["validate", "--json"]);
+    }
+}
@@ -0,0 +1,168 @@
+use std::{io::Error, str::FromStr};
+
+use crate::cli::{invalid_input, Usage, USAGES};
+
+const FORMATS: &str = "chords export --list 2>/dev/null | cut -d' ' -f1";
+
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            shell => Err(invalid_input(format!(
+                "Unknown shell {shell}, expected bash, zsh or fish"
+            ))),
+        }
+    }
+}
+
+pub fn completions(shell: &Shell) -> String {
+    match shell {
+        Shell::Bash => bash(),
+        Shell::Zsh => zsh(),
+        Shell::Fish => fish(),
+    }
+}
+
+fn names() -> String {
+    let names: Vec<_> = USAGES.iter().map(|usage| usage.name).collect();
+    names.join(" ")
+}
+
+fn options(usage: &Usage) -> String {
+    let options: Vec<_> = usage
+        .options
+        .iter()
+        .chain(usage.flags)
+        .map(|option| format!("--{option}"))
+        .collect();
+    options.join(" ")
+}
+
+fn bash() -> String {
+    let cases: String = USAGES
+        .iter()
+        .map(|usage| {
+            let fallback = if usage.files {
+                r#"COMPREPLY=($(compgen -f -- "$cur"))"#
+            } else {
+                "COMPREPLY=()"
+            };
+            format!(
+                "        {name})\n            if [[ $cur == -* ]]; then\n                COMPREPLY=($(compgen -W \"{options}\" -- \"$cur\"))\n            else\n                {fallback}\n            fi\n            ;;\n",
+                name = usage.name,
+                options = options(usage),
+            )
+        })
+        .collect();
+
+    format!(
+        r#"_chords() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+
+    if [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "{names}" -- "$cur"))
+        return
+    fi
+    if [[ $prev == --format ]]; then
+        COMPREPLY=($(compgen -W "$({FORMATS})" -- "$cur"))
+        return
+    fi
+    if [[ ${{COMP_WORDS[1]}} == completions ]]; then
+        COMPREPLY=($(compgen -W "bash zsh fish" -- "$cur"))
+        return
+    fi
+
+    case "${{COMP_WORDS[1]}}" in
+{cases}    esac
+}}
+
+complete -F _chords chords
+"#,
+        names = names(),
+    )
+}
+
+fn zsh() -> String {
+    let cases: String = USAGES
+        .iter()
+        .map(|usage| {
+            let fallback = if usage.files { "_files" } else { ":" };
+            format!(
+                "        {name})\n            if [[ $PREFIX == -* ]]; then\n                compadd -- {options}\n            else\n                {fallback}\n            fi\n            ;;\n",
+                name = usage.name,
+                options = options(usage),
+            )
+        })
+        .collect();
+
+    format!(
+        r#"_chords() {{
+    if (( CURRENT == 2 )); then
+        compadd -- {names}
+        return
+    fi
+    if [[ $words[CURRENT-1] == --format ]]; then
+        compadd -- ${{(f)"$({FORMATS})"}}
+        return
+    fi
+    if [[ $words[2] == completions ]]; then
+        compadd -- bash zsh fish
+        return
+    fi
+
+    case $words[2] in
+{cases}    esac
+}}
+
+compdef _chords chords
+"#,
+        names = names(),
+    )
+}
+
+fn fish() -> String {
+    let mut lines = vec![
+        String::from("complete -c chords -f"),
+        format!(
+            "complete -c chords -n __fish_use_subcommand -a '{}'",
+            names()
+        ),
+        String::from(
+            "complete -c chords -n '__fish_seen_subcommand_from completions' -a 'bash zsh fish'",
+        ),
+    ];
+
+    for usage in USAGES {
+        let condition = format!("'__fish_seen_subcommand_from {}'", usage.name);
+        if usage.files {
+            lines.push(format!("complete -c chords -n {condition} -F"));
+        }
+        for option in usage.options {
+            let values = match *option {
+                "format" => String::from(
+                    "-x -a \"(chords export --list 2>/dev/null | string split -f1 ' ')\"",
+                ),
+                _ => String::from("-r -F"),
+            };
+            lines.push(format!(
+                "complete -c chords -n {condition} -l {option} {values}"
+            ));
+        }
+        for flag in usage.flags {
+            lines.push(format!("complete -c chords -n {condition} -l {flag}"));
+        }
+    }
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
@@ -0,0 +1,267 @@
+use std::{
+    fs::{read_to_string, write},
+    io::{Error, ErrorKind, Result},
+    path::Path,
+    result::Result as StdResult,
+    time::Duration,
+};
+
+use crate::{
+    chords::Chord,
+    cost::Cost,
+    layout::{Finger, Geometry, Hand, Key},
+    normalize::Normalization,
+    simulate::Model,
+    steno,
+    tui::{Column, PANE_RATIOS},
+    words::Cutoff,
+};
+
+#[derive(Clone)]
+pub struct Config {
+    pub fingers: Vec<(Hand, Finger, Vec<String>)>,
+    pub allow_adjacent_same_finger: bool,
+    pub max_keys_per_hand: Option<usize>,
+    pub max_keys: Option<usize>,
+    pub key_order: Vec<String>,
+    pub chord_separator: String,
+    pub near_miss_distance: usize,
+    pub pane_ratio: u16,
+    pub columns: Vec<Column>,
+    pub auto_commit: bool,
+    pub fold_case: bool,
+    pub normalization: Normalization,
+    pub personal_weight: f64,
+    pub cutoff: Option<Cutoff>,
+    pub suffix_keys: Vec<(String, char)>,
+    pub geometry: Option<Geometry>,
+    pub custom_keys: Vec<(String, Key)>,
+    pub steno_keys: Vec<(String, String)>,
+    pub impulse_keys: Vec<String>,
+    pub max_impulse_keys: Option<usize>,
+    pub suggest_command: Option<String>,
+    pub suggest_timeout: Duration,
+    pub database: Option<String>,
+    pub cost: Option<Cost>,
+    pub model: Model,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fingers: Vec::new(),
+            allow_adjacent_same_finger: false,
+            max_keys_per_hand: None,
+            max_keys: None,
+            key_order: Vec::new(),
+            chord_separator: String::from("+"),
+            near_miss_distance: 1,
+            pane_ratio: 60,
+            columns: vec![Column::Rank, Column::Word, Column::Chord],
+            auto_commit: false,
+            fold_case: false,
+            normalization: Normalization::None,
+            personal_weight: 0.7,
+            cutoff: None,
+            suffix_keys: Vec::new(),
+            geometry: None,
+            custom_keys: Vec::new(),
+            steno_keys: Vec::new(),
+            impulse_keys: Vec::new(),
+            max_impulse_keys: None,
+            suggest_command: None,
+            suggest_timeout: Duration::from_secs(10),
+            database: None,
+            cost: None,
+            model: Model::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let lines = match read_to_string(path) {
+            Ok(lines) => lines,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+
+        let mut config = Self::default();
+
+        for (number, line) in lines.split('\n').enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            config
+                .parse_line(line)
+                .map_err(|reason| invalid_line(number + 1, line, reason))?;
+        }
+
+        Ok(config)
+    }
+
+    pub fn update_file(path: impl AsRef<Path>, name: &str, value: &str) -> Result<()> {
+        let lines = match read_to_string(&path) {
+            Ok(lines) => lines,
+            Err(error) if error.kind() == ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error),
+        };
+
+        let setting = format!("{name} = {value}");
+        let mut found = false;
+        let mut lines: Vec<_> = lines
+            .lines()
+            .map(|line| match line.split_once('=') {
+                Some((key, _)) if key.trim() == name => {
+                    found = true;
+                    setting.clone()
+                }
+                _ => line.to_owned(),
+            })
+            .collect();
+
+        if !found {
+            lines.push(setting);
+        }
+
+        write(path, lines.join("\n") + "\n")
+    }
+
+    fn parse_line(&mut self, line: &str) -> StdResult<(), Option<String>> {
+        let (name, value) = line.split_once('=').ok_or(None)?;
+        let (name, value) = (name.trim(), value.trim());
+
+        // the cost parser explains what is wrong with the expression
+        if name == "cost" {
+            self.cost = Some(value.parse().map_err(Some)?);
+            return Ok(());
+        }
+
+        self.parse_setting(name, value).ok_or(None)
+    }
+
+    fn parse_setting(&mut self, name: &str, value: &str) -> Option<()> {
+        match name.split('.').collect::<Vec<_>>().as_slice() {
+            ["allow_adjacent_same_finger"] => {
+                self.allow_adjacent_same_finger = value.parse().ok()?;
+            }
+            ["max_keys_per_hand"] => self.max_keys_per_hand = Some(value.parse().ok()?),
+            ["max_keys"] => self.max_keys = Some(value.parse().ok()?),
+            ["key_order"] => self.key_order = parse_keys(value),
+            ["chord_separator"] => {
+                self.chord_separator = match value {
+                    "none" => String::new(),
+                    "space" => String::from(" "),
+                    _ if value.chars().any(char::is_alphanumeric) => return None,
+                    _ => value.to_owned(),
+                };
+            }
+            ["impulse_keys"] => self.impulse_keys = parse_keys(value),
+            ["max_impulse_keys"] => self.max_impulse_keys = Some(value.parse().ok()?),
+            ["near_miss_distance"] => self.near_miss_distance = value.parse().ok()?,
+            ["pane_ratio"] => {
+                self.pane_ratio = value
+                    .parse()
+                    .ok()
+                    .filter(|ratio| PANE_RATIOS.contains(ratio))?;
+            }
+            ["columns"] => {
+                self.columns = value
+                    .split_whitespace()
+                    .map(|column| column.parse().ok())
+                    .collect::<Option<_>>()?;
+            }
+            ["chord_time"] => self.model.chord_time = value.parse().ok()?,
+            ["char_time"] => self.model.char_time = value.parse().ok()?,
+            ["typing_wpm"] => {
+                let wpm: f64 = value.parse().ok().filter(|wpm| *wpm > 0.0)?;
+                self.model.char_time = 60_000.0 / (wpm * 5.0);
+            }
+            ["suggest_command"] => self.suggest_command = Some(value.to_owned()),
+            ["suggest_timeout"] => {
+                self.suggest_timeout = Duration::from_millis(value.parse().ok()?)
+            }
+            ["database"] => self.database = Some(value.to_owned()),
+            ["auto_commit"] => self.auto_commit = value.parse().ok()?,
+            ["fold_case"] => self.fold_case = value.parse().ok()?,
+            ["normalization"] => self.normalization = value.parse().ok()?,
+            ["personal_weight"] => {
+                self.personal_weight = value
+                    .parse()
+                    .ok()
+                    .filter(|weight| (0.0..=1.0).contains(weight))?;
+            }
+            ["cutoff"] => self.cutoff = Some(value.parse().ok()?),
+            ["suffix", suffix] => {
+                let key = value.parse::<char>().ok()?.to_ascii_uppercase();
+                self.suffix_keys.push(((*suffix).to_owned(), key));
+            }
+            ["key", token] => {
+                let key = self.parse_custom_key(value)?;
+                let token = token.parse::<Chord>().ok()?;
+                let [token] = token.keys().collect::<Vec<_>>()[..] else {
+                    return None;
+                };
+                self.custom_keys.push((token.to_owned(), key));
+            }
+            ["steno", key] if steno::is_key(key) => {
+                let token = value.parse::<Chord>().ok()?;
+                let [token] = token.keys().collect::<Vec<_>>()[..] else {
+                    return None;
+                };
+                self.steno_keys.push(((*key).to_owned(), token.to_owned()));
+            }
+            ["finger", hand, finger] => {
+                self.fingers
+                    .push((hand.parse().ok()?, finger.parse().ok()?, parse_keys(value)));
+            }
+            _ => return None,
+        }
+
+        Some(())
+    }
+
+    fn parse_custom_key(&self, value: &str) -> Option<Key> {
+        const CUSTOM_ROW: u8 = 4;
+
+        let fields: Vec<_> = value.split_whitespace().collect();
+        let (hand, finger, row, column) = match fields[..] {
+            [hand, finger] => {
+                let column = self
+                    .custom_keys
+                    .iter()
+                    .filter(|(_, key)| key.row == CUSTOM_ROW)
+                    .count();
+                (hand, finger, CUSTOM_ROW, u8::try_from(column).ok()?)
+            }
+            [hand, finger, row, column] => (hand, finger, row.parse().ok()?, column.parse().ok()?),
+            _ => return None,
+        };
+
+        Some(Key {
+            hand: hand.parse().ok()?,
+            finger: finger.parse().ok()?,
+            row,
+            column,
+            position: None,
+        })
+    }
+}
+
+fn parse_keys(value: &str) -> Vec<String> {
+    value
+        .split_whitespace()
+        .map(str::to_ascii_uppercase)
+        .collect()
+}
+
+fn invalid_line(number: usize, line: &str, reason: Option<String>) -> Error {
+    let message = match reason {
+        Some(reason) => format!("Invalid config line {number} ({reason}): {line}"),
+        None => format!("Invalid config line {number}: {line}"),
+    };
+
+    Error::new(ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,254 @@
+use std::{iter::Peekable, str::Chars, str::FromStr};
+
+#[derive(Default)]
+pub struct Features {
+    pub keys: u32,
+    pub home: u32,
+    pub top: u32,
+    pub reach: u32,
+    pub thumb: u32,
+    pub index: u32,
+    pub middle: u32,
+    pub ring: u32,
+    pub pinky: u32,
+    pub left: u32,
+    pub right: u32,
+    pub unknown: u32,
+}
+
+impl Features {
+    fn get(&self, name: &str) -> Option<u32> {
+        Some(match name {
+            "keys" => self.keys,
+            "home" => self.home,
+            "top" => self.top,
+            "reach" => self.reach,
+            "thumb" => self.thumb,
+            "index" => self.index,
+            "middle" => self.middle,
+            "ring" => self.ring,
+            "pinky" => self.pinky,
+            "left" => self.left,
+            "right" => self.right,
+            "unknown" => self.unknown,
+            _ => return None,
+        })
+    }
+}
+
+/// Expression such as `top + 2 * reach + max(keys - 2, 0)`.
+#[derive(Clone)]
+pub struct Cost(Expression);
+
+impl Cost {
+    pub fn evaluate(&self, features: &Features) -> u32 {
+        let cost = self.0.evaluate(features);
+
+        if cost.is_finite() {
+            cost.round().clamp(0.0, f64::from(u32::MAX)) as u32
+        } else {
+            u32::MAX
+        }
+    }
+}
+
+impl FromStr for Cost {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: string.chars().peekable(),
+        };
+        let expression = parser.expression()?;
+
+        match parser.peek() {
+            None => Ok(Self(expression)),
+            Some(char) => Err(format!("unexpected '{char}'")),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Clone, Copy)]
+enum Function {
+    Min,
+    Max,
+    Abs,
+}
+
+#[derive(Clone)]
+enum Expression {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+impl Expression {
+    fn evaluate(&self, features: &Features) -> f64 {
+        match self {
+            Expression::Number(number) => *number,
+            Expression::Variable(name) => f64::from(features.get(name).unwrap_or_default()),
+            Expression::Negate(expression) => -expression.evaluate(features),
+            Expression::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(features), right.evaluate(features));
+                match operator {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide => left / right,
+                }
+            }
+            Expression::Call(function, arguments) => {
+                let mut values = arguments.iter().map(|argument| argument.evaluate(features));
+                match function {
+                    Function::Min => values.fold(f64::INFINITY, f64::min),
+                    Function::Max => values.fold(f64::NEG_INFINITY, f64::max),
+                    Function::Abs => values.next().unwrap_or_default().abs(),
+                }
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|char| char.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.peek() {
+            Some(char) if char == expected => {
+                self.chars.next();
+                Ok(())
+            }
+            Some(char) => Err(format!("expected '{expected}' but found '{char}'")),
+            None => Err(format!("expected '{expected}'")),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, String> {
+        let mut expression = self.term()?;
+
+        while let Some(operator) = match self.peek() {
+            Some('+') => Some(Operator::Add),
+            Some('-') => Some(Operator::Subtract),
+            _ => None,
+        } {
+            self.chars.next();
+            expression = Expression::Binary(operator, expression.into(), self.term()?.into());
+        }
+
+        Ok(expression)
+    }
+
+    fn term(&mut self) -> Result<Expression, String> {
+        let mut expression = self.factor()?;
+
+        while let Some(operator) = match self.peek() {
+            Some('*') => Some(Operator::Multiply),
+            Some('/') => Some(Operator::Divide),
+            _ => None,
+        } {
+            self.chars.next();
+            expression = Expression::Binary(operator, expression.into(), self.factor()?.into());
+        }
+
+        Ok(expression)
+    }
+
+    fn factor(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some('-') => {
+                self.chars.next();
+                Ok(Expression::Negate(self.factor()?.into()))
+            }
+            Some('(') => {
+                self.chars.next();
+                let expression = self.expression()?;
+                self.expect(')')?;
+                Ok(expression)
+            }
+            Some(char) if char.is_ascii_digit() || char == '.' => {
+                let number = self.take_while(|char| char.is_ascii_digit() || char == '.');
+                number
+                    .parse()
+                    .map(Expression::Number)
+                    .map_err(|_| format!("invalid number {number}"))
+            }
+            Some(char) if char.is_ascii_alphabetic() => {
+                let name = self.take_while(|char| char.is_ascii_alphanumeric() || char == '_');
+                let function = match name.as_str() {
+                    "min" => Function::Min,
+                    "max" => Function::Max,
+                    "abs" => Function::Abs,
+                    _ if Features::default().get(&name).is_some() => {
+                        return Ok(Expression::Variable(name))
+                    }
+                    _ => return Err(format!("unknown name {name}")),
+                };
+                Ok(Expression::Call(function, self.arguments()?))
+            }
+            Some(char) => Err(format!("unexpected '{char}'")),
+            None => Err(String::from("unexpected end of expression")),
+        }
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Expression>, String> {
+        self.expect('(')?;
+        let mut arguments = vec![self.expression()?];
+        while self.peek() == Some(',') {
+            self.chars.next();
+            arguments.push(self.expression()?);
+        }
+        self.expect(')')?;
+
+        Ok(arguments)
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(char) = self.chars.next_if(|char| predicate(*char)) {
+            taken.push(char);
+        }
+
+        taken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluate_expressions() {
+        let features = Features {
+            keys: 3,
+            pinky: 1,
+            reach: 2,
+            ..Features::default()
+        };
+        let cost = |expression: &str| expression.parse::<Cost>().unwrap().evaluate(&features);
+
+        assert_eq!(cost("2 * pinky + reach"), 4);
+        assert_eq!(cost("max(keys - 2, 0) * (1 + 1)"), 2);
+        assert_eq!(cost("-keys"), 0);
+        assert_eq!(cost("keys / 2"), 2);
+        assert!("keys +".parse::<Cost>().is_err());
+        assert!("speed".parse::<Cost>().is_err());
+        assert!("min(keys".parse::<Cost>().is_err());
+    }
+}
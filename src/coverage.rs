@@ -0,0 +1,172 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+use crate::{
+    chords::{Chord, Chords},
+    json::Json,
+    word_set::WordSet,
+};
+
+pub struct Coverage {
+    pub tokens: usize,
+    pub covered: usize,
+    pub keystrokes: usize,
+    pub saved: usize,
+    pub uncovered: Vec<(String, usize)>,
+}
+
+impl Coverage {
+    pub fn share(&self) -> f64 {
+        ratio(self.covered, self.tokens)
+    }
+
+    pub fn saved_share(&self) -> f64 {
+        ratio(self.saved, self.keystrokes)
+    }
+
+    pub fn to_json(&self) -> Json {
+        let uncovered = self.uncovered.iter().map(|(word, count)| {
+            Json::Object(vec![
+                ("word", word.as_str().into()),
+                ("count", (*count).into()),
+            ])
+        });
+
+        Json::Object(vec![
+            ("tokens", self.tokens.into()),
+            ("covered", self.covered.into()),
+            ("keystrokes", self.keystrokes.into()),
+            ("saved", self.saved.into()),
+            ("uncovered", Json::array(uncovered)),
+        ])
+    }
+}
+
+impl Display for Coverage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(f, "Tokens: {}", self.tokens)?;
+        writeln!(
+            f,
+            "Chordable: {} ({:.1}%)",
+            self.covered,
+            self.share() * 100.0
+        )?;
+        write!(
+            f,
+            "Keystrokes saved: {} of {} ({:.1}%)",
+            self.saved,
+            self.keystrokes,
+            self.saved_share() * 100.0
+        )?;
+
+        if !self.uncovered.is_empty() {
+            write!(f, "\nTop uncovered words:")?;
+        }
+        for (word, count) in &self.uncovered {
+            write!(f, "\n{count:>8}  {word}")?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn coverage(text: &str, chords: &Chords, stop_words: &WordSet, top: usize) -> Coverage {
+    let by_word = shortest_chords(chords);
+
+    let mut coverage = Coverage {
+        tokens: 0,
+        covered: 0,
+        keystrokes: 0,
+        saved: 0,
+        uncovered: Vec::new(),
+    };
+    let mut uncovered: BTreeMap<String, usize> = BTreeMap::new();
+
+    for token in tokens(text) {
+        let lowercase = token.to_lowercase();
+        if stop_words.contains(token) || stop_words.contains(&lowercase) {
+            continue;
+        }
+
+        let length = token.chars().count();
+        coverage.tokens += 1;
+        coverage.keystrokes += length;
+
+        match by_word.get(token).or_else(|| by_word.get(&lowercase)) {
+            Some(chord) => {
+                coverage.covered += 1;
+                coverage.saved += length.saturating_sub(chord.keys().count());
+            }
+            None => *uncovered.entry(lowercase).or_default() += 1,
+        }
+    }
+
+    let mut uncovered: Vec<_> = uncovered.into_iter().collect();
+    uncovered.sort_by_key(|(_, count)| Reverse(*count));
+    uncovered.truncate(top);
+    coverage.uncovered = uncovered;
+
+    coverage
+}
+
+pub fn shortest_chords(chords: &Chords) -> BTreeMap<String, Chord> {
+    let mut by_word: BTreeMap<String, Chord> = BTreeMap::new();
+    for (chord, word) in chords.iter() {
+        let shorter = by_word
+            .get(&word)
+            .is_none_or(|other| chord.keys().count() < other.keys().count());
+        if shorter {
+            by_word.insert(word, chord);
+        }
+    }
+
+    by_word
+}
+
+pub fn tokens(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|char: char| !(char.is_alphanumeric() || char == '\''))
+        .map(|token| token.trim_matches('\''))
+        .filter(|token| !token.is_empty())
+}
+
+pub fn ratio(part: usize, total: usize) -> f64 {
+    match total {
+        0 => 0.0,
+        total => part as f64 / total as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_covered_tokens() {
+        let chords = Chords::from_pairs(&[("H+T", "the"), ("E+H+T", "the"), ("A+N", "and")]);
+        let stop_words: WordSet = ["a".to_owned()].into_iter().collect();
+
+        let coverage = coverage(
+            "The cat and the dog, a cat's toy.",
+            &chords,
+            &stop_words,
+            10,
+        );
+
+        assert_eq!(coverage.tokens, 7);
+        assert_eq!(coverage.keystrokes, 23);
+        assert_eq!(coverage.covered, 3);
+        assert_eq!(coverage.saved, 3);
+        assert_eq!(
+            coverage.uncovered,
+            [
+                ("cat".to_owned(), 1),
+                ("cat's".to_owned(), 1),
+                ("dog".to_owned(), 1),
+                ("toy".to_owned(), 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    fs::{metadata, remove_file},
+    io::{BufRead, BufReader, Error, ErrorKind, Result, Write},
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::Path,
+    thread,
+};
+
+use crate::library::Library;
+
+const SUGGESTIONS: usize = 5;
+
+/// Answers `word <word>`, `chord <chord>` and `suggest <word>` lines by `ok ...` or `none`.
+pub fn run(library: &Library, path: &Path) -> Result<()> {
+    // a stale socket from a previous run would make binding fail
+    match metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => remove_file(path)?,
+        Ok(_) => {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ))
+        }
+        Err(_) => {}
+    }
+    let listener = UnixListener::bind(path)?;
+    println!("Listening on {}", path.display());
+
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            scope.spawn(|| {
+                if let Err(error) = handle(stream, library) {
+                    eprintln!("connection failed: {error}");
+                }
+            });
+        }
+
+        Ok(())
+    })
+}
+
+fn handle(mut stream: UnixStream, library: &Library) -> Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+
+    for line in reader.lines() {
+        let reply = respond(&line?, library);
+        writeln!(stream, "{reply}")?;
+    }
+
+    Ok(())
+}
+
+fn respond(request: &str, library: &Library) -> String {
+    let (command, argument) = request
+        .trim()
+        .split_once(' ')
+        .map_or((request.trim(), ""), |(command, argument)| {
+            (command, argument.trim())
+        });
+    let notation = library.notation();
+    let reply = |items: Vec<String>| match items.is_empty() {
+        true => String::from("none"),
+        false => format!("ok {}", items.join(" ")),
+    };
+
+    match command {
+        _ if argument.is_empty() => String::from("error missing argument"),
+        "word" => reply(
+            library
+                .chords_of(argument)
+                .iter()
+                .map(|chord| notation.format_canonical(chord))
+                .collect(),
+        ),
+        "chord" => match notation.parse(argument) {
+            Ok(chord) => reply(library.word(&chord).cloned().into_iter().collect()),
+            Err(_) => format!("error invalid chord {argument}"),
+        },
+        "suggest" => match library.suggest(argument, SUGGESTIONS) {
+            Ok(suggestions) => reply(
+                suggestions
+                    .iter()
+                    .map(|suggestion| notation.format_canonical(&suggestion.chord))
+                    .collect(),
+            ),
+            Err(error) => format!("error {error}"),
+        },
+        command => format!("error unknown command {command}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chords::Chords, config::Config, library::Paths, word_set::WordSet, words::Words};
+
+    #[test]
+    fn line_protocol() {
+        let chords = Chords::from_pairs(&[("H+T", "the")]);
+        let library = Library::new(
+            chords,
+            Words::default(),
+            WordSet::default(),
+            &Config::default(),
+            Paths {
+                chords: "",
+                journal: "",
+            },
+        );
+
+        assert_eq!(respond("word the", &library), "ok H+T");
+        assert_eq!(respond("chord T+H", &library), "ok the");
+        assert_eq!(respond("chord E", &library), "none");
+        assert_eq!(respond("word", &library), "error missing argument");
+        assert_eq!(respond("fly away", &library), "error unknown command fly");
+    }
+}
@@ -0,0 +1,1140 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{Error, Result},
+    ops::RangeInclusive,
+    str::FromStr,
+};
+
+use crate::{
+    chords::{Chord, Chords, Notation},
+    cli::invalid_input,
+    layout::Layout,
+    steno::Steno,
+    words::Words,
+};
+
+pub struct Context<'a> {
+    pub chords: &'a Chords,
+    pub words: &'a Words,
+    pub notation: &'a Notation,
+    pub layout: &'a Layout,
+    pub steno: &'a Steno,
+}
+
+pub trait Exporter: Sync {
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    fn export(&self, context: &Context) -> Export;
+}
+
+pub struct Export {
+    pub text: String,
+    pub skipped: Vec<String>,
+}
+
+impl From<String> for Export {
+    fn from(text: String) -> Self {
+        Self {
+            text,
+            skipped: Vec::new(),
+        }
+    }
+}
+
+struct Builtin {
+    name: &'static str,
+    description: &'static str,
+    export: fn(&Context) -> Export,
+}
+
+impl Exporter for Builtin {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn export(&self, context: &Context) -> Export {
+        (self.export)(context)
+    }
+}
+
+pub static EXPORTERS: &[&dyn Exporter] = &[
+    &Builtin {
+        name: "markdown",
+        description: "Markdown tables grouped by first key",
+        export: |context| markdown(group_by_first_key(context.chords, context.notation)).into(),
+    },
+    &Builtin {
+        name: "markdown-tags",
+        description: "Markdown tables grouped by tag",
+        export: |context| markdown(group_by_tag(context.chords, context.notation)).into(),
+    },
+    &Builtin {
+        name: "html",
+        description: "Printable cheat sheet with key diagrams",
+        export: |context| html(context.chords, context.notation, context.layout).into(),
+    },
+    &Builtin {
+        name: "anki",
+        description: "Anki flashcards as CSV, or a deck with --output <file>.apkg",
+        export: |context| {
+            anki(
+                context.chords,
+                context.words,
+                context.notation,
+                context.layout,
+            )
+            .into()
+        },
+    },
+    &Builtin {
+        name: "qmk",
+        description: "QMK combos sending each word",
+        export: |context| qmk(context.chords, context.notation),
+    },
+    &Builtin {
+        name: "zmk",
+        description: "ZMK combos and macros, keys numbered row by row",
+        export: |context| zmk(context.chords, context.notation, context.layout),
+    },
+    &Builtin {
+        name: "karabiner",
+        description: "Karabiner-Elements complex modifications",
+        export: |context| karabiner(context.chords, context.notation),
+    },
+    &Builtin {
+        name: "kanata",
+        description: "Kanata chords configuration",
+        export: |context| kanata(context.chords, context.notation),
+    },
+    &Builtin {
+        name: "kmonad",
+        description: "KMonad tap-macro aliases",
+        export: |context| kmonad(context.chords, context.notation),
+    },
+    &Builtin {
+        name: "keyd",
+        description: "keyd chord bindings",
+        export: |context| keyd(context.chords, context.notation),
+    },
+    &Builtin {
+        name: "espanso",
+        description: "Espanso text expansion matches",
+        export: |context| espanso(context.chords, context.notation).into(),
+    },
+    &Builtin {
+        name: "autohotkey",
+        description: "AutoHotkey hotstrings",
+        export: |context| autohotkey(context.chords, context.notation, false).into(),
+    },
+    &Builtin {
+        name: "autohotkey-hotkeys",
+        description: "AutoHotkey simultaneous key hotkeys",
+        export: |context| autohotkey(context.chords, context.notation, true).into(),
+    },
+    &Builtin {
+        name: "plover",
+        description: "Plover JSON dictionary of steno strokes",
+        export: |context| plover(context.chords, context.notation, context.steno),
+    },
+    &Builtin {
+        name: "javelin",
+        description: "Javelin steno firmware dictionary source",
+        // the Javelin dictionary builder reads Plover's JSON dictionary format
+        export: |context| plover(context.chords, context.notation, context.steno),
+    },
+];
+
+pub struct Subset {
+    pub max_rank: Option<usize>,
+    pub tag: Option<String>,
+    pub keys: RangeInclusive<usize>,
+}
+
+impl Subset {
+    pub fn apply(&self, chords: &mut Chords, words: &Words) {
+        let tagged: Option<BTreeSet<_>> = self.tag.as_ref().map(|tag| {
+            chords
+                .iter()
+                .map(|(_, word)| word)
+                .filter(|word| chords.metadata(word).tags.contains(tag))
+                .collect()
+        });
+
+        chords.retain(|chord, word| {
+            self.keys.contains(&chord.keys().count())
+                && tagged.as_ref().is_none_or(|tagged| tagged.contains(word))
+                && self.max_rank.is_none_or(|max_rank| {
+                    words.rank(word).is_some_and(|rank| rank.get() <= max_rank)
+                })
+        });
+    }
+}
+
+pub struct Format(&'static dyn Exporter);
+
+impl Format {
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    pub fn export(&self, context: &Context) -> Export {
+        self.0.export(context)
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self> {
+        EXPORTERS
+            .iter()
+            .find(|exporter| exporter.name() == string)
+            .map(|exporter| Format(*exporter))
+            .ok_or_else(|| {
+                let names: Vec<_> = EXPORTERS.iter().map(|exporter| exporter.name()).collect();
+                invalid_input(format!(
+                    "Unknown export format {string}, expected one of {}",
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+type Group = Vec<(String, Chord, String)>;
+
+fn group_by_first_key(chords: &Chords, notation: &Notation) -> BTreeMap<String, Group> {
+    let mut groups: BTreeMap<String, Group> = BTreeMap::new();
+
+    for (chord, word) in chords.iter() {
+        let text = notation.format(&chord);
+        let first_key = notation
+            .format_canonical(&chord)
+            .split('+')
+            .next()
+            .unwrap_or_default()
+            .to_owned();
+
+        groups
+            .entry(first_key)
+            .or_default()
+            .push((text, chord, word));
+    }
+
+    for entries in groups.values_mut() {
+        entries.sort();
+    }
+
+    groups
+}
+
+fn group_by_tag(chords: &Chords, notation: &Notation) -> BTreeMap<String, Group> {
+    let mut groups: BTreeMap<String, Group> = BTreeMap::new();
+
+    for (chord, word) in chords.iter() {
+        let mut tags: Vec<_> = chords.metadata(&word).tags.into_iter().collect();
+        if tags.is_empty() {
+            tags.push(String::from("Untagged"));
+        }
+
+        for tag in tags {
+            groups.entry(tag).or_default().push((
+                notation.format(&chord),
+                chord.clone(),
+                word.clone(),
+            ));
+        }
+    }
+
+    for entries in groups.values_mut() {
+        entries.sort();
+    }
+
+    groups
+}
+
+fn markdown(groups: BTreeMap<String, Group>) -> String {
+    let mut markdown = String::from("# Chords\n");
+
+    for (heading, entries) in groups {
+        markdown.push_str(&format!(
+            "\n## {heading}\n\n| Chord | Word |\n| --- | --- |\n"
+        ));
+        for (chord, _, word) in entries {
+            let word = word.replace('|', "\\|");
+            markdown.push_str(&format!("| {chord} | {word} |\n"));
+        }
+    }
+
+    markdown
+}
+
+fn html(chords: &Chords, notation: &Notation, layout: &Layout) -> String {
+    const STYLE: &str = "body { font-family: sans-serif; }
+section { break-inside: avoid; }
+table { border-collapse: collapse; }
+td { padding: 2px 8px; border-bottom: 1px solid #ccc; }
+@media print { body { font-size: 10pt; } }";
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Chords</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>Chords</h1>\n"
+    );
+
+    for (first_key, entries) in group_by_first_key(chords, notation) {
+        html.push_str(&format!(
+            "<section>\n<h2>{first_key}</h2>\n{diagram}<table>\n",
+            first_key = escape_html(&first_key),
+            diagram = diagram(layout, &[first_key.as_str()]),
+        ));
+        for (text, chord, word) in entries {
+            let keys: Vec<_> = chord.keys().collect();
+
+            html.push_str(&format!(
+                "<tr><td>{diagram}</td><td>{text}</td><td>{word}</td></tr>\n",
+                diagram = diagram(layout, &keys),
+                text = escape_html(&text),
+                word = escape_html(&word),
+            ));
+        }
+        html.push_str("</table>\n</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+
+    html
+}
+
+pub struct Card {
+    pub word: String,
+    pub chord: String,
+    pub diagram: String,
+    pub tags: Vec<String>,
+}
+
+pub fn anki_cards(
+    chords: &Chords,
+    words: &Words,
+    notation: &Notation,
+    layout: &Layout,
+) -> Vec<Card> {
+    let mut entries: Vec<_> = chords.iter().collect();
+    entries.sort_by_key(|(_, word)| words.rank(word).map_or(usize::MAX, |rank| rank.get()));
+
+    entries
+        .into_iter()
+        .map(|(chord, word)| {
+            let keys: Vec<_> = chord.keys().collect();
+            Card {
+                chord: notation.format(&chord),
+                diagram: diagram(layout, &keys),
+                tags: chords.metadata(&word).tags.into_iter().collect(),
+                word,
+            }
+        })
+        .collect()
+}
+
+fn anki(chords: &Chords, words: &Words, notation: &Notation, layout: &Layout) -> String {
+    let mut csv = String::from(
+        "#separator:Comma\n#html:true\n#columns:Word,Chord,Diagram,Tags\n#tags column:4\n",
+    );
+    for card in anki_cards(chords, words, notation, layout) {
+        csv.push_str(&format!(
+            "{word},{chord},{diagram},{tags}\n",
+            word = escape_csv(&card.word),
+            chord = escape_csv(&card.chord),
+            diagram = escape_csv(&card.diagram),
+            tags = escape_csv(&card.tags.join(" ")),
+        ));
+    }
+
+    csv
+}
+
+fn qmk(chords: &Chords, notation: &Notation) -> Export {
+    let mut names = Vec::new();
+    let mut arrays = String::new();
+    let mut cases = String::new();
+    let mut skipped = Vec::new();
+    for (chord, word) in chords.iter() {
+        let keys: Option<Vec<_>> = chord.keys().map(qmk_key).collect();
+        let Some(keys) = keys.filter(|keys| keys.len() > 1 && word.is_ascii()) else {
+            skipped.push(format!("{}: {word}", notation.format(&chord)));
+            continue;
+        };
+
+        let name = format!("combo_{}", names.len());
+        arrays.push_str(&format!(
+            "const uint16_t PROGMEM {name}[] = {{{keys}, COMBO_END}}; // {word}\n",
+            keys = keys.join(", "),
+        ));
+        cases.push_str(&format!(
+            "        case {index}:\n            SEND_STRING({word});\n            break;\n",
+            index = names.len(),
+            word = escape_json(&word),
+        ));
+        names.push(name);
+    }
+
+    let combos: Vec<_> = names
+        .iter()
+        .map(|name| format!("    COMBO_ACTION({name}),\n"))
+        .collect();
+    let text = format!(
+        "{arrays}
+combo_t key_combos[] = {{
+{combos}}};
+
+void process_combo_event(uint16_t combo_index, bool pressed) {{
+    if (!pressed) {{
+        return;
+    }}
+    switch (combo_index) {{
+{cases}    }}
+}}
+",
+        combos = combos.concat(),
+    );
+
+    Export { text, skipped }
+}
+
+fn qmk_key(key: &str) -> Option<String> {
+    let name = match key {
+        _ if key.len() == 1 && key.chars().all(|char| char.is_ascii_alphanumeric()) => key,
+        "[" => "LBRC",
+        "]" => "RBRC",
+        ";" => "SCLN",
+        "'" => "QUOT",
+        "," => "COMM",
+        "." => "DOT",
+        "/" => "SLSH",
+        "-" => "MINS",
+        "=" => "EQL",
+        _ => return None,
+    };
+
+    Some(format!("KC_{name}"))
+}
+
+fn zmk(chords: &Chords, notation: &Notation, layout: &Layout) -> Export {
+    const TIMEOUT: u16 = 50;
+
+    let mut keys: Vec<_> = layout.keys().collect();
+    keys.sort_by_key(|(_, key)| (key.row, key.column));
+    let positions: BTreeMap<_, _> = keys
+        .into_iter()
+        .enumerate()
+        .map(|(position, (name, _))| (name, position))
+        .collect();
+
+    let mut macros = String::new();
+    let mut combos = String::new();
+    let mut count = 0;
+    let mut skipped = Vec::new();
+    for (chord, word) in chords.iter() {
+        let key_positions: Option<Vec<_>> = chord
+            .keys()
+            .map(|key| positions.get(key).map(usize::to_string))
+            .collect();
+        let bindings: Option<Vec<_>> = word.chars().map(zmk_key).collect();
+        let (Some(key_positions), Some(bindings)) = (key_positions, bindings) else {
+            skipped.push(format!("{}: {word}", notation.format(&chord)));
+            continue;
+        };
+        if key_positions.len() < 2 {
+            skipped.push(format!("{}: {word}", notation.format(&chord)));
+            continue;
+        }
+
+        let index = count;
+        count += 1;
+        let bindings: Vec<_> = bindings.iter().map(|key| format!("&kp {key}")).collect();
+        macros.push_str(&format!(
+            "        word_{index}: word_{index} {{
+            compatible = \"zmk,behavior-macro\";
+            #binding-cells = <0>;
+            bindings = <{bindings}>;
+        }};
+",
+            bindings = bindings.join(" "),
+        ));
+        combos.push_str(&format!(
+            "        // {chord} {word}
+        combo_{index} {{
+            timeout-ms = <{TIMEOUT}>;
+            key-positions = <{key_positions}>;
+            bindings = <&word_{index}>;
+        }};
+",
+            chord = notation.format(&chord),
+            key_positions = key_positions.join(" "),
+        ));
+    }
+
+    let text = format!(
+        "/ {{
+    macros {{
+{macros}    }};
+
+    combos {{
+        compatible = \"zmk,combos\";
+{combos}    }};
+}};
+"
+    );
+
+    Export { text, skipped }
+}
+
+fn zmk_key(char: char) -> Option<String> {
+    let key = match char {
+        'a'..='z' => char.to_ascii_uppercase().to_string(),
+        'A'..='Z' => format!("LS({char})"),
+        '0'..='9' => format!("N{char}"),
+        ' ' => "SPACE".to_owned(),
+        '-' => "MINUS".to_owned(),
+        '\'' => "SQT".to_owned(),
+        '.' => "DOT".to_owned(),
+        ',' => "COMMA".to_owned(),
+        _ => return None,
+    };
+
+    Some(key)
+}
+
+fn karabiner(chords: &Chords, notation: &Notation) -> Export {
+    let mut manipulators = Vec::new();
+    let mut skipped = Vec::new();
+    for (chord, word) in chords.iter() {
+        let to: Option<Vec<_>> = word.chars().map(karabiner_key).collect();
+        let Some(to) = to.filter(|_| chord.keys().count() > 1) else {
+            skipped.push(format!("{}: {word}", notation.format(&chord)));
+            continue;
+        };
+
+        let from: Vec<_> = chord
+            .keys()
+            .map(|key| format!("{{ \"key_code\": \"{}\" }}", key.to_lowercase()))
+            .collect();
+        let to: Vec<_> = to
+            .into_iter()
+            .map(|(key_code, shift)| {
+                let modifiers = if shift {
+                    ", \"modifiers\": [\"left_shift\"]"
+                } else {
+                    ""
+                };
+                format!("{{ \"key_code\": \"{key_code}\"{modifiers} }}")
+            })
+            .collect();
+
+        manipulators.push(format!(
+            "        {{
+          \"type\": \"basic\",
+          \"description\": {description},
+          \"from\": {{
+            \"simultaneous\": [{from}],
+            \"simultaneous_options\": {{ \"key_down_order\": \"insensitive\", \"key_up_order\": \"insensitive\" }}
+          }},
+          \"to\": [{to}]
+        }}",
+            description = escape_json(&format!("{} -> {word}", notation.format(&chord))),
+            from = from.join(", "),
+            to = to.join(", "),
+        ));
+    }
+
+    let text = format!(
+        "{{
+  \"title\": \"Chords\",
+  \"rules\": [
+    {{
+      \"description\": \"Chords\",
+      \"manipulators\": [
+{manipulators}
+      ]
+    }}
+  ]
+}}
+",
+        manipulators = manipulators.join(",\n")
+    );
+
+    Export { text, skipped }
+}
+
+fn karabiner_key(char: char) -> Option<(String, bool)> {
+    let key_code = match char {
+        'a'..='z' | '0'..='9' => char.to_string(),
+        'A'..='Z' => return Some((char.to_ascii_lowercase().to_string(), true)),
+        ' ' => "spacebar".to_owned(),
+        '-' => "hyphen".to_owned(),
+        '\'' => "quote".to_owned(),
+        '.' => "period".to_owned(),
+        ',' => "comma".to_owned(),
+        _ => return None,
+    };
+
+    Some((key_code, false))
+}
+
+fn kanata(chords: &Chords, notation: &Notation) -> Export {
+    const TIMEOUT: u16 = 50;
+
+    let mut config = String::from("(defchordsv2\n");
+    let mut skipped = Vec::new();
+    for (chord, word) in chords.iter() {
+        let output: Option<Vec<_>> = word.chars().map(kanata_key).collect();
+        let Some(output) = output.filter(|_| chord.keys().count() > 1) else {
+            skipped.push(format!("{}: {word}", notation.format(&chord)));
+            continue;
+        };
+        let keys: Vec<_> = chord.keys().map(str::to_lowercase).collect();
+
+        config.push_str(&format!(
+            "  ({keys}) (macro {output}) {TIMEOUT} all-released () ;; {chord} {word}\n",
+            keys = keys.join(" "),
+            output = output.join(" "),
+            chord = notation.format(&chord),
+        ));
+    }
+    config.push_str(")\n");
+
+    Export {
+        text: config,
+        skipped,
+    }
+}
+
+fn kmonad(chords: &Chords, notation: &Notation) -> Export {
+    let mut config = String::from("(defalias\n");
+    let mut skipped = Vec::new();
+    for (chord, word) in chords.iter() {
+        let output: Option<Vec<_>> = word.chars().map(kanata_key).collect();
+        let Some(output) = output.filter(|_| chord.keys().count() > 1) else {
+            skipped.push(format!("{}: {word}", notation.format(&chord)));
+            continue;
+        };
+        let keys: Vec<_> = chord.keys().map(str::to_lowercase).collect();
+
+        config.push_str(&format!(
+            "  {keys} #({output}) ;; {chord} {word}\n",
+            keys = keys.join("-"),
+            output = output.join(" "),
+            chord = notation.format(&chord),
+        ));
+    }
+    config.push_str(")\n");
+
+    Export {
+        text: config,
+        skipped,
+    }
+}
+
+fn kanata_key(char: char) -> Option<String> {
+    let key = match char {
+        'a'..='z' | '0'..='9' | '\'' | '.' | ',' => char.to_string(),
+        'A'..='Z' => format!("S-{}", char.to_ascii_lowercase()),
+        ' ' => "spc".to_owned(),
+        '-' => "min".to_owned(),
+        _ => return None,
+    };
+
+    Some(key)
+}
+
+fn keyd(chords: &Chords, notation: &Notation) -> Export {
+    let mut config = String::from("[main]\n\n");
+    let mut skipped = Vec::new();
+    for (chord, word) in chords.iter() {
+        if chord.keys().count() < 2 {
+            skipped.push(format!("{}: {word}", notation.format(&chord)));
+            continue;
+        }
+        let keys: Vec<_> = chord.keys().map(str::to_lowercase).collect();
+
+        config.push_str(&format!(
+            "{keys} = macro({output})\n",
+            keys = keys.join("+"),
+            output = escape_keyd(&word),
+        ));
+    }
+
+    Export {
+        text: config,
+        skipped,
+    }
+}
+
+fn escape_keyd(string: &str) -> String {
+    let keys: Vec<_> = string
+        .chars()
+        .map(|char| match char {
+            ' ' => "space".to_owned(),
+            '\\' | '(' | ')' => format!("\\{char}"),
+            char => char.to_string(),
+        })
+        .collect();
+
+    keys.join(" ")
+}
+
+fn espanso(chords: &Chords, notation: &Notation) -> String {
+    let mut matches = String::from("matches:\n");
+    for (chord, word) in chords.iter() {
+        matches.push_str(&format!(
+            "  - trigger: {trigger}\n    replace: {word}\n    word: true\n",
+            trigger = escape_json(&abbreviation(&chord, notation)),
+            word = escape_json(&word),
+        ));
+    }
+
+    matches
+}
+
+fn autohotkey(chords: &Chords, notation: &Notation, hotkeys: bool) -> String {
+    let mut script = String::from("#Requires AutoHotkey v2.0\n\n");
+    for (chord, word) in chords.iter() {
+        script.push_str(&format!(
+            ":T:{trigger}::{word}\n",
+            trigger = abbreviation(&chord, notation),
+            word = word.replace('`', "``").replace(';', "`;"),
+        ));
+    }
+
+    if hotkeys {
+        script.push('\n');
+        for (chord, word) in chords.iter() {
+            let keys: Vec<_> = chord.keys().map(str::to_lowercase).collect();
+            let [first, second] = keys.as_slice() else {
+                continue;
+            };
+
+            script.push_str(&format!(
+                "~{first} & {second}:: {{\n    Send \"{{BS}}\"\n    SendText \"{word}\"\n}}\n",
+                word = word.replace('`', "``").replace('"', "`\""),
+            ));
+        }
+    }
+
+    script
+}
+
+fn abbreviation(chord: &Chord, notation: &Notation) -> String {
+    const PREFIX: char = ';';
+
+    let keys: String = notation
+        .format_canonical(chord)
+        .split('+')
+        .map(str::to_lowercase)
+        .collect();
+
+    format!("{PREFIX}{keys}")
+}
+
+fn diagram(layout: &Layout, pressed: &[&str]) -> String {
+    const SIZE: f64 = 12.0;
+
+    let keys: Vec<_> = layout.keys().collect();
+    let (width, height) = keys.iter().fold((0.0, 0.0), |(width, height), (_, key)| {
+        let (x, y) = key.origin();
+        (
+            f64::max(width, (x + 1.0) * SIZE),
+            f64::max(height, (y + 1.0) * SIZE),
+        )
+    });
+
+    let mut svg = format!("<svg width=\"{width}\" height=\"{height}\">");
+    for (name, key) in keys {
+        let (background, foreground) = if pressed.contains(&name) {
+            ("#333", "#fff")
+        } else {
+            ("#fff", "#333")
+        };
+        let (x, y) = key.origin();
+        let (x, y) = (x * SIZE, y * SIZE);
+
+        svg.push_str(&format!(
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{SIZE}\" height=\"{SIZE}\" fill=\"{background}\" stroke=\"#333\"/><text x=\"{cx}\" y=\"{cy}\" fill=\"{foreground}\" font-size=\"8\" text-anchor=\"middle\" dominant-baseline=\"central\">{name}</text>",
+            cx = x + SIZE / 2.0,
+            cy = y + SIZE / 2.0,
+            name = escape_html(name),
+        ));
+    }
+    svg.push_str("</svg>");
+
+    svg
+}
+
+pub fn escape_csv(string: &str) -> String {
+    format!("\"{}\"", string.replace('"', "\"\""))
+}
+
+fn plover(chords: &Chords, notation: &Notation, steno: &Steno) -> Export {
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    for (chord, word) in chords.iter() {
+        let Some(stroke) = steno.stroke(&chord) else {
+            skipped.push(format!("{}: {word}", notation.format(&chord)));
+            continue;
+        };
+        // braces start Plover commands, literal ones are escaped
+        let translation = word
+            .replace('\\', "\\\\")
+            .replace('{', "\\{")
+            .replace('}', "\\}");
+        entries.push(format!(
+            "{}: {}",
+            escape_json(&stroke),
+            escape_json(&translation)
+        ));
+    }
+
+    Export {
+        text: format!("{{\n{}\n}}\n", entries.join(",\n")),
+        skipped,
+    }
+}
+
+pub fn escape_json(string: &str) -> String {
+    let mut escaped = String::from('"');
+    for char in string.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            char if char.is_control() => escaped.push_str(&format!("\\u{:04x}", char as u32)),
+            char => escaped.push(char),
+        }
+    }
+    escaped.push('"');
+
+    escaped
+}
+
+fn escape_html(string: &str) -> String {
+    string
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn keyd_escaping() {
+        assert_eq!(escape_keyd("the"), "t h e");
+        assert_eq!(escape_keyd("a (b)\\"), "a space \\( b \\) \\\\");
+    }
+
+    #[test]
+    fn markdown_groups() {
+        let mut chords = Chords::from_pairs(&[("T+H", "the"), ("A+N", "and"), ("T+O", "a|b")]);
+        let mut metadata = chords.metadata("the");
+        metadata
+            .tags
+            .extend(["common".to_owned(), "article".to_owned()]);
+        chords.set_metadata("the", metadata);
+        let notation = Notation::default();
+
+        assert_eq!(
+            markdown(group_by_first_key(&chords, &notation)),
+            "# Chords
+
+## A
+
+| Chord | Word |
+| --- | --- |
+| A+N | and |
+
+## H
+
+| Chord | Word |
+| --- | --- |
+| H+T | the |
+
+## O
+
+| Chord | Word |
+| --- | --- |
+| O+T | a\\|b |
+"
+        );
+
+        let tagged = markdown(group_by_tag(&chords, &notation));
+        let headings: Vec<_> = tagged
+            .lines()
+            .filter(|line| line.starts_with("##"))
+            .collect();
+        assert_eq!(headings, ["## Untagged", "## article", "## common"]);
+        assert_eq!(tagged.matches("| H+T | the |").count(), 2);
+    }
+
+    #[test]
+    fn html_cheat_sheet() {
+        let chords = Chords::from_pairs(&[("T+H", "the"), ("A+N", "<b> & </b>")]);
+        let layout = Layout::from_config(&Config::default());
+
+        let html = html(&chords, &Notation::default(), &layout);
+
+        assert_eq!(html.matches("<section>").count(), 2);
+        assert!(html.contains("<h2>A</h2>"));
+        assert!(html.contains("<td>A+N</td><td>&lt;b&gt; &amp; &lt;/b&gt;</td>"));
+        // one key per group heading and every key of the two chords
+        assert_eq!(html.matches("fill=\"#333\" stroke").count(), 6);
+        assert!(html.ends_with("</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn anki_cards_by_rank() {
+        let mut chords = Chords::from_pairs(&[("T+H", "the"), ("A+N", "and")]);
+        let mut metadata = chords.metadata("and");
+        metadata.tags.insert("common".to_owned());
+        chords.set_metadata("and", metadata);
+        let mut words = Words::default();
+        words.import("and\nthe\n");
+
+        let csv = anki(&chords, &words, &Notation::default(), &Layout::qwerty());
+        let rows: Vec<_> = csv
+            .lines()
+            .skip(4)
+            .map(|row| {
+                let fields: Vec<_> = row.split(',').collect();
+                [fields[0], fields[1], fields[fields.len() - 1]]
+            })
+            .collect();
+
+        assert!(csv.starts_with("#separator:Comma\n"));
+        assert_eq!(
+            rows,
+            [
+                ["\"and\"", "\"A+N\"", "\"common\""],
+                ["\"the\"", "\"H+T\"", "\"\""]
+            ]
+        );
+    }
+
+    #[test]
+    fn subset_by_rank_tag_and_keys() {
+        let mut chords = Chords::from_pairs(&[
+            ("H+T", "the"),
+            ("E+H+T", "the"),
+            ("F+O", "of"),
+            ("A+N", "and"),
+        ]);
+        for word in ["the", "and"] {
+            let mut metadata = chords.metadata(word);
+            metadata.tags.insert("core".to_owned());
+            chords.set_metadata(word, metadata);
+        }
+        let mut words = Words::default();
+        words.import("the\nof\nand\n");
+        let subset = |max_rank, tag: Option<&str>, keys| {
+            let mut chords = chords.clone();
+            Subset {
+                max_rank,
+                tag: tag.map(str::to_owned),
+                keys,
+            }
+            .apply(&mut chords, &words);
+            chords
+                .iter()
+                .map(|(chord, word)| format!("{}: {word}", chord.as_str()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            subset(Some(2), None, 1..=usize::MAX),
+            ["E+H+T: the", "F+O: of", "H+T: the"]
+        );
+        assert_eq!(subset(None, Some("core"), 2..=2), ["A+N: and", "H+T: the"]);
+
+        let mut tagged = chords.clone();
+        Subset {
+            max_rank: Some(1),
+            tag: None,
+            keys: 1..=usize::MAX,
+        }
+        .apply(&mut tagged, &words);
+        assert!(tagged.metadata("the").tags.contains("core"));
+        assert!(tagged.metadata("and").tags.is_empty());
+    }
+
+    #[test]
+    fn qmk_combos() {
+        let chords = Chords::from_pairs(&[("H+T", "the"), ("A+[", "say \"a\""), ("DUP+E", "e")]);
+
+        let export = qmk(&chords, &Notation::default());
+
+        assert_eq!(export.skipped, ["DUP+E: e"]);
+        assert_eq!(
+            export.text,
+            r#"const uint16_t PROGMEM combo_0[] = {KC_A, KC_LBRC, COMBO_END}; // say "a"
+const uint16_t PROGMEM combo_1[] = {KC_H, KC_T, COMBO_END}; // the
+
+combo_t key_combos[] = {
+    COMBO_ACTION(combo_0),
+    COMBO_ACTION(combo_1),
+};
+
+void process_combo_event(uint16_t combo_index, bool pressed) {
+    if (!pressed) {
+        return;
+    }
+    switch (combo_index) {
+        case 0:
+            SEND_STRING("say \"a\"");
+            break;
+        case 1:
+            SEND_STRING("the");
+            break;
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn zmk_combos() {
+        let chords = Chords::from_pairs(&[("Q+W", "Qw"), ("A", "a"), ("DUP+E", "e")]);
+        let layout = Layout::qwerty();
+
+        let export = zmk(&chords, &Notation::default(), &layout);
+
+        assert_eq!(export.skipped, ["A: a", "DUP+E: e"]);
+        assert_eq!(
+            export
+                .text
+                .matches("compatible = \"zmk,behavior-macro\"")
+                .count(),
+            1
+        );
+        assert!(export.text.contains("bindings = <&kp LS(Q) &kp W>;"));
+        assert!(export.text.contains(
+            "        // Q+W Qw
+        combo_0 {
+            timeout-ms = <50>;
+            key-positions = <10 11>;
+            bindings = <&word_0>;
+        };"
+        ));
+    }
+
+    #[test]
+    fn karabiner_rules() {
+        let chords = Chords::from_pairs(&[("H+T", "The"), ("A", "a"), ("Q+W", "caf\u{e9}")]);
+
+        let Export {
+            text: rules,
+            skipped,
+        } = karabiner(&chords, &Notation::default());
+
+        assert_eq!(skipped, ["A: a", "Q+W: caf\u{e9}"]);
+        assert_eq!(rules.matches("\"type\": \"basic\"").count(), 1);
+        assert!(
+            rules.contains("\"simultaneous\": [{ \"key_code\": \"h\" }, { \"key_code\": \"t\" }]")
+        );
+        assert!(rules.contains(
+            "\"to\": [{ \"key_code\": \"t\", \"modifiers\": [\"left_shift\"] }, { \"key_code\": \"h\" }, { \"key_code\": \"e\" }]"
+        ));
+        assert!(rules.contains("\"description\": \"H+T -> The\""));
+    }
+
+    #[test]
+    fn espanso_matches() {
+        let chords = Chords::from_pairs(&[("H+T", "the"), ("A+Q", "say \"hi\"")]);
+
+        assert_eq!(
+            espanso(&chords, &Notation::default()),
+            r#"matches:
+  - trigger: ";aq"
+    replace: "say \"hi\""
+    word: true
+  - trigger: ";ht"
+    replace: "the"
+    word: true
+"#
+        );
+    }
+
+    #[test]
+    fn autohotkey_script() {
+        let chords = Chords::from_pairs(&[("H+T", "the"), ("A+N+D", "a;b"), ("O+Q", "\"q\"")]);
+        let notation = Notation::default();
+
+        assert_eq!(
+            autohotkey(&chords, &notation, false),
+            "#Requires AutoHotkey v2.0
+
+:T:;adn::a`;b
+:T:;ht::the
+:T:;oq::\"q\"
+"
+        );
+
+        let hotkeys = autohotkey(&chords, &notation, true);
+        assert!(hotkeys.contains("~h & t:: {\n    Send \"{BS}\"\n    SendText \"the\"\n}\n"));
+        assert!(hotkeys.contains("SendText \"`\"q`\"\""));
+        assert!(!hotkeys.contains("~a & "));
+    }
+
+    #[test]
+    fn kmonad_aliases() {
+        let chords = Chords::from_pairs(&[("H+T", "The"), ("A+N", "a-n"), ("E", "e")]);
+
+        let export = kmonad(&chords, &Notation::default());
+
+        assert_eq!(
+            export.text,
+            "(defalias
+  a-n #(a min n) ;; A+N a-n
+  h-t #(S-t h e) ;; H+T The
+)
+"
+        );
+        assert_eq!(export.skipped, ["E: e"]);
+    }
+
+    #[test]
+    fn plover_dictionary() {
+        let steno = Steno::from_config(&Config::default());
+        let chords =
+            Chords::from_pairs(&[("N+R+W", "the"), ("L", "{^ing}\\"), ("Q+W", "unmapped")]);
+
+        let export = plover(&chords, &Notation::default(), &steno);
+
+        assert_eq!(export.skipped, ["Q+W: unmapped"]);
+        assert_eq!(
+            export.text,
+            r#"{
+"-G": "\\{^ing\\}\\\\",
+"THE": "the"
+}
+"#
+        );
+    }
+
+    #[test]
+    fn unique_format_names() {
+        let names: BTreeMap<_, _> = EXPORTERS
+            .iter()
+            .map(|exporter| (exporter.name(), exporter.description()))
+            .collect();
+
+        assert_eq!(names.len(), EXPORTERS.len());
+        assert!("autohotkey-hotkeys".parse::<Format>().is_ok());
+        assert!("qmk".parse::<Format>().is_ok());
+        assert!("colemak".parse::<Format>().is_err());
+    }
+}
@@ -0,0 +1,31 @@
+use std::{
+    io::{Error, Result},
+    process::{Command, Output},
+};
+
+pub fn is_repository() -> bool {
+    git(&["rev-parse", "--is-inside-work-tree"]).is_ok_and(|output| output.status.success())
+}
+
+pub fn commit(paths: &[&str], message: &str) -> Result<()> {
+    let mut add = vec!["add", "--"];
+    add.extend(paths);
+    check(git(&add)?)?;
+
+    let mut commit = vec!["commit", "--quiet", "--message", message, "--"];
+    commit.extend(paths);
+    check(git(&commit)?)
+}
+
+fn git(args: &[&str]) -> Result<Output> {
+    Command::new("git").args(args).output()
+}
+
+fn check(output: Output) -> Result<()> {
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(Error::other(format!("git failed: {}", stderr.trim())))
+    }
+}
@@ -0,0 +1,463 @@
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{Error, ErrorKind, Result as IoResult},
+    iter::Peekable,
+    path::Path,
+    str::Chars,
+};
+
+use crate::{
+    chords::{Chord, Chords},
+    steno::Steno,
+};
+
+const CSV_HEADERS: [&str; 4] = ["input", "chord", "chords", "keys"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    Text,
+    Json,
+    Plover,
+    CharaChorder,
+    Qmk,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let name = match self {
+            Source::Text => "chords text",
+            Source::Json => "chords JSON",
+            Source::Plover => "Plover JSON",
+            Source::CharaChorder => "CharaChorder CSV",
+            Source::Qmk => "QMK combos",
+        };
+        write!(f, "{name}")
+    }
+}
+
+pub struct Import {
+    pub source: Source,
+    pub chords: Chords,
+    pub skipped: Vec<String>,
+}
+
+pub fn import(path: &Path, contents: &str, steno: &Steno) -> IoResult<Import> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let trimmed = contents.trim_start();
+
+    let json = || -> IoResult<_> {
+        let entries = parse_json_object(contents)?;
+        let plover = entries.iter().any(|(key, _)| !is_chord_notation(key));
+        Ok((if plover { Source::Plover } else { Source::Json }, entries))
+    };
+
+    let (source, entries) = match extension.as_deref() {
+        Some("json") => json()?,
+        Some("csv") => (Source::CharaChorder, parse_csv(contents)),
+        Some("c" | "h" | "keymap") => (Source::Qmk, parse_qmk(contents)),
+        Some("txt") => return text(contents),
+        _ if trimmed.starts_with('{') => json()?,
+        _ if contents.contains("COMBO_END") => (Source::Qmk, parse_qmk(contents)),
+        _ if contents.lines().any(|line| line.contains(':')) => return text(contents),
+        _ if contents.lines().any(|line| line.contains(',')) => {
+            (Source::CharaChorder, parse_csv(contents))
+        }
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Cannot detect the format of {}", path.display()),
+            ))
+        }
+    };
+
+    let mut chords = Chords::default();
+    let mut skipped = Vec::new();
+    for (keys, word) in entries {
+        let chord = match source {
+            Source::Plover => steno.chord(&keys),
+            // only our own formats know about custom key tokens
+            Source::Json => keys.parse().ok(),
+            _ => keys
+                .parse()
+                .ok()
+                .filter(|chord: &Chord| chord.keys().all(|key| key.len() == 1)),
+        };
+        let chord = chord.filter(|chord| !chord.is_empty());
+
+        match chord {
+            Some(chord) if !word.is_empty() && !word.contains('{') => {
+                chords.insert(chord, word);
+            }
+            _ => skipped.push(format!("{keys}: {word}")),
+        }
+    }
+
+    Ok(Import {
+        source,
+        chords,
+        skipped,
+    })
+}
+
+pub struct Columns {
+    pub word: usize,
+    pub order: Option<Order>,
+    pub chord: Option<usize>,
+    pub header: bool,
+}
+
+#[derive(Clone, Copy)]
+pub enum Order {
+    Count(usize),
+    Rank(usize),
+}
+
+pub struct WordList {
+    pub words: Vec<(String, Option<u64>)>,
+    pub chords: Chords,
+    pub skipped: Vec<String>,
+}
+
+pub fn import_words(contents: &str, columns: &Columns) -> IoResult<WordList> {
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+
+    let mut rows = Vec::new();
+    let lines = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    for (number, line) in lines.skip(usize::from(columns.header)) {
+        let fields = split_csv(line);
+        let field = |column: usize| fields.get(column).map_or("", |field| field.trim());
+        // counts are often written with thousands separators
+        let parse = |column: usize| field(column).replace([',', '_', ' '], "").parse::<f64>();
+        let order = match columns.order {
+            Some(Order::Count(column)) => parse(column).map(|count| -count),
+            Some(Order::Rank(column)) => parse(column),
+            None => Ok(0.0),
+        };
+        let order = match order {
+            Ok(order) if !order.is_nan() => order,
+            // a header line names the columns instead
+            _ if rows.is_empty() && !columns.header => continue,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid count or rank on line {}: {line}", number + 1),
+                ))
+            }
+        };
+
+        let count = match columns.order {
+            Some(Order::Count(_)) if order <= 0.0 => Some((-order).round() as u64),
+            _ => None,
+        };
+        let chord = columns.chord.map(field).unwrap_or_default();
+        rows.push((
+            order,
+            field(columns.word).to_owned(),
+            chord.to_owned(),
+            count,
+        ));
+    }
+    rows.sort_by(|(first, ..), (second, ..)| first.total_cmp(second));
+
+    let mut chords = Chords::default();
+    let mut skipped = Vec::new();
+    for (_, word, keys, _) in &rows {
+        if keys.is_empty() || word.is_empty() {
+            continue;
+        }
+        match keys.parse::<Chord>() {
+            Ok(chord) if !chord.is_empty() => {
+                chords.insert(chord, word.clone());
+            }
+            _ => skipped.push(format!("{keys}: {word}")),
+        }
+    }
+
+    Ok(WordList {
+        words: rows
+            .into_iter()
+            .map(|(_, word, _, count)| (word, count))
+            .collect(),
+        chords,
+        skipped,
+    })
+}
+
+fn text(contents: &str) -> IoResult<Import> {
+    Ok(Import {
+        source: Source::Text,
+        chords: Chords::parse_str(contents)?,
+        skipped: Vec::new(),
+    })
+}
+
+fn is_chord_notation(keys: &str) -> bool {
+    keys.contains('+') || keys.chars().count() == 1
+}
+
+fn parse_csv(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = split_csv(line).into_iter();
+            let keys = fields.next()?;
+            let word = fields.next()?;
+            if CSV_HEADERS.contains(&keys.to_ascii_lowercase().as_str()) {
+                return None;
+            }
+
+            let keys = if keys.contains('+') {
+                keys
+            } else {
+                let keys: Vec<_> = keys
+                    .chars()
+                    .filter(|char| !char.is_whitespace())
+                    .map(String::from)
+                    .collect();
+                keys.join("+")
+            };
+
+            Some((keys, word))
+        })
+        .collect()
+}
+
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            char => fields.last_mut().unwrap().push(char),
+        }
+    }
+
+    fields
+        .into_iter()
+        .map(|field| field.trim().to_owned())
+        .collect()
+}
+
+fn parse_qmk(contents: &str) -> Vec<(String, String)> {
+    contents
+        .split(';')
+        .filter(|statement| statement.contains("COMBO_END"))
+        .filter_map(|statement| {
+            let (declaration, keys) = statement.split_once('=')?;
+            let name = declaration.split('[').next()?.split_whitespace().last()?;
+            let word = name
+                .strip_suffix("_combo")
+                .or_else(|| name.strip_prefix("combo_"))
+                .unwrap_or(name)
+                .to_ascii_lowercase();
+
+            let keys: Vec<_> = keys
+                .trim_matches(|char: char| char.is_whitespace() || char == '{' || char == '}')
+                .split(',')
+                .map(str::trim)
+                .filter(|key| *key != "COMBO_END" && !key.is_empty())
+                .map(|key| key.strip_prefix("KC_").unwrap_or(key))
+                .collect();
+
+            Some((keys.join("+"), word))
+        })
+        .collect()
+}
+
+fn parse_json_object(contents: &str) -> IoResult<Vec<(String, String)>> {
+    let invalid =
+        |message: &str| Error::new(ErrorKind::InvalidData, format!("Invalid JSON: {message}"));
+
+    let mut chars = contents.chars().peekable();
+    let mut entries = Vec::new();
+
+    skip_whitespace(&mut chars);
+    if chars.next() != Some('{') {
+        return Err(invalid("expected an object"));
+    }
+    loop {
+        skip_whitespace(&mut chars);
+        match chars.peek() {
+            Some('}') => break,
+            Some('"') => {}
+            _ => return Err(invalid("expected a string key")),
+        }
+        let key = parse_json_string(&mut chars).ok_or_else(|| invalid("unterminated string"))?;
+
+        skip_whitespace(&mut chars);
+        if chars.next() != Some(':') {
+            return Err(invalid("expected ':'"));
+        }
+        skip_whitespace(&mut chars);
+        if chars.peek() != Some(&'"') {
+            return Err(invalid("expected a string value"));
+        }
+        let value = parse_json_string(&mut chars).ok_or_else(|| invalid("unterminated string"))?;
+        entries.push((key, value));
+
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some(',') => {}
+            Some('}') => break,
+            _ => return Err(invalid("expected ',' or '}'")),
+        }
+    }
+
+    Ok(entries)
+}
+
+fn parse_json_string(chars: &mut Peekable<Chars>) -> Option<String> {
+    chars.next();
+
+    let mut string = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(string),
+            '\\' => match chars.next()? {
+                'n' => string.push('\n'),
+                't' => string.push('\t'),
+                'u' => {
+                    let code: String = chars.by_ref().take(4).collect();
+                    string.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+                }
+                char => string.push(char),
+            },
+            char => string.push(char),
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|char| char.is_whitespace()).is_some() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn word(import: &Import, chord: &str) -> Option<String> {
+        import.chords.get(&chord.parse().unwrap()).cloned()
+    }
+
+    #[test]
+    fn detect_formats() {
+        let steno = Steno::from_config(&Config::default());
+
+        let plover = import(
+            Path::new("main.json"),
+            r#"{"TH-E": "the", "SKWR/TPH": "journey", "-G": "{^ing}"}"#,
+            &steno,
+        )
+        .unwrap();
+        assert_eq!(plover.source, Source::Plover);
+        assert_eq!(word(&plover, "N+R+W").as_deref(), Some("the"));
+        assert_eq!(plover.skipped.len(), 2);
+
+        let json = import(
+            Path::new("export"),
+            r#"{"H+T": "the", "A": "a \"b\""}"#,
+            &steno,
+        )
+        .unwrap();
+        assert_eq!(json.source, Source::Json);
+        assert_eq!(word(&json, "A").as_deref(), Some("a \"b\""));
+
+        let csv = import(
+            Path::new("library.csv"),
+            "input,output\nht,the\n\"a+n\",and\n",
+            &steno,
+        )
+        .unwrap();
+        assert_eq!(csv.source, Source::CharaChorder);
+        assert_eq!(word(&csv, "H+T").as_deref(), Some("the"));
+        assert_eq!(word(&csv, "A+N").as_deref(), Some("and"));
+
+        let qmk = import(
+            Path::new("keymap.c"),
+            "const uint16_t PROGMEM the_combo[] = {KC_T, KC_H, COMBO_END};\n\
+             combo_t key_combos[] = {COMBO(the_combo, KC_NO)};",
+            &steno,
+        )
+        .unwrap();
+        assert_eq!(qmk.source, Source::Qmk);
+        assert_eq!(word(&qmk, "H+T").as_deref(), Some("the"));
+
+        let text = import(Path::new("chords"), "H+T: the\n", &steno).unwrap();
+        assert_eq!(text.source, Source::Text);
+        assert_eq!(word(&text, "H+T").as_deref(), Some("the"));
+    }
+
+    #[test]
+    fn map_word_list_columns() {
+        let columns = Columns {
+            word: 1,
+            order: Some(Order::Count(2)),
+            chord: Some(3),
+            header: false,
+        };
+
+        let list = import_words(
+            "id,word,count,chord\n1,and,120,a+n\n2,the,\"1,500\",\n3,of,800,\n4,it,90,??\n",
+            &columns,
+        )
+        .unwrap();
+        assert_eq!(
+            list.words,
+            [
+                ("the".to_owned(), Some(1500)),
+                ("of".to_owned(), Some(800)),
+                ("and".to_owned(), Some(120)),
+                ("it".to_owned(), Some(90)),
+            ]
+        );
+        assert_eq!(
+            list.chords.get(&"A+N".parse().unwrap()).map(String::as_str),
+            Some("and")
+        );
+        assert_eq!(list.skipped, ["??: it"]);
+
+        let columns = Columns {
+            word: 0,
+            order: Some(Order::Rank(1)),
+            chord: None,
+            header: false,
+        };
+        let invalid = import_words("and,2\nthe,first\n", &columns);
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn skip_header_line() {
+        let columns = |header| Columns {
+            word: 0,
+            order: None,
+            chord: Some(1),
+            header,
+        };
+        let contents = "word,chord\nthe,h+t\nof,\n";
+
+        let list = import_words(contents, &columns(true)).unwrap();
+        assert_eq!(
+            list.words,
+            [("the".to_owned(), None), ("of".to_owned(), None)]
+        );
+        assert!(list.skipped.is_empty());
+
+        let list = import_words(contents, &columns(false)).unwrap();
+        assert_eq!(list.words[0], ("word".to_owned(), None));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{
+    chords::{Chord, Chords},
+    layout::{Layout, Violation},
+    words::Words,
+};
+
+const MIN_LEMMA_LENGTH: usize = 3;
+
+pub enum Derived {
+    Assigned { word: String, chord: Chord },
+    Conflict { word: String, existing: String },
+    Impossible { word: String, violation: Violation },
+}
+
+pub fn lemma(word: &str, is_word: impl Fn(&str) -> bool) -> String {
+    candidates(word)
+        .into_iter()
+        .find(|candidate| candidate.chars().count() >= MIN_LEMMA_LENGTH && is_word(candidate))
+        .unwrap_or_else(|| word.to_owned())
+}
+
+fn candidates(word: &str) -> Vec<String> {
+    let mut candidates = Vec::new();
+
+    if let Some(stem) = word.strip_suffix("ies") {
+        candidates.push(format!("{stem}y"));
+    }
+    if let Some(stem) = word.strip_suffix("es") {
+        candidates.push(stem.to_owned());
+    }
+    if let Some(stem) = word.strip_suffix('s').filter(|stem| !stem.ends_with('s')) {
+        candidates.push(stem.to_owned());
+    }
+    if let Some(stem) = word.strip_suffix("ied") {
+        candidates.push(format!("{stem}y"));
+    }
+    if let Some(stem) = word.strip_suffix('d').filter(|stem| stem.ends_with('e')) {
+        candidates.push(stem.to_owned());
+    }
+    for suffix in ["ed", "ing"] {
+        if let Some(stem) = word.strip_suffix(suffix) {
+            candidates.push(stem.to_owned());
+            candidates.push(format!("{stem}e"));
+            candidates.extend(undouble(stem));
+        }
+    }
+
+    candidates
+}
+
+pub fn derive(
+    word: &str,
+    chord: &Chord,
+    suffix_keys: &[(String, char)],
+    words: &Words,
+    chords: &Chords,
+    layout: &Layout,
+) -> Vec<Derived> {
+    let mut derived = Vec::new();
+
+    for (suffix, key) in suffix_keys {
+        let mut derived_chord = chord.clone();
+        if !derived_chord.insert(*key) {
+            continue;
+        }
+        let Some((form, _)) = inflect(word, suffix)
+            .into_iter()
+            .filter_map(|form| words.get_index(words.index_of(&form)?))
+            .find(|(_, entry)| entry.chord.is_empty())
+        else {
+            continue;
+        };
+
+        let violation = layout.violations(&derived_chord).into_iter().next();
+        derived.push(match (chords.get(&derived_chord), violation) {
+            (Some(existing), _) => Derived::Conflict {
+                word: form.clone(),
+                existing: existing.clone(),
+            },
+            (None, Some(violation)) => Derived::Impossible {
+                word: form.clone(),
+                violation,
+            },
+            (None, None) => Derived::Assigned {
+                word: form.clone(),
+                chord: derived_chord,
+            },
+        });
+    }
+
+    derived
+}
+
+fn inflect(word: &str, suffix: &str) -> Vec<String> {
+    let mut forms = vec![format!("{word}{suffix}")];
+
+    if let Some(stem) = word.strip_suffix('y').filter(|_| suffix.starts_with('e')) {
+        forms.push(format!("{stem}i{suffix}"));
+    }
+    if let Some(stem) = word.strip_suffix('e') {
+        forms.push(format!("{stem}{suffix}"));
+    }
+    if suffix == "s" {
+        forms.push(format!("{word}es"));
+        if let Some(stem) = word.strip_suffix('y') {
+            forms.push(format!("{stem}ies"));
+        }
+    }
+    if let Some(last) = word
+        .chars()
+        .last()
+        .filter(|last| !"aeiouwy".contains(*last))
+    {
+        if suffix.starts_with(['a', 'e', 'i', 'o', 'u']) {
+            forms.push(format!("{word}{last}{suffix}"));
+        }
+    }
+
+    forms
+}
+
+fn undouble(stem: &str) -> Option<String> {
+    let mut chars = stem.chars().rev();
+    let last = chars.next()?;
+
+    (chars.next() == Some(last) && !"aeiou".contains(last))
+        .then(|| stem[..stem.len() - last.len_utf8()].to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_lemmas_in_word_list() {
+        let words = ["work", "make", "run", "use", "fly", "hop", "box", "us"];
+        let is_word = |word: &str| words.contains(&word);
+
+        assert_eq!(lemma("works", is_word), "work");
+        assert_eq!(lemma("working", is_word), "work");
+        assert_eq!(lemma("worked", is_word), "work");
+        assert_eq!(lemma("making", is_word), "make");
+        assert_eq!(lemma("running", is_word), "run");
+        assert_eq!(lemma("used", is_word), "use");
+        assert_eq!(lemma("flies", is_word), "fly");
+        assert_eq!(lemma("boxes", is_word), "box");
+        assert_eq!(lemma("hopping", is_word), "hop");
+        assert_eq!(lemma("glass", is_word), "glass");
+        assert_eq!(lemma("uses", is_word), "use");
+    }
+
+    #[test]
+    fn inflected_forms() {
+        assert!(inflect("run", "ing").contains(&"running".to_owned()));
+        assert!(inflect("make", "ing").contains(&"making".to_owned()));
+        assert!(inflect("try", "ed").contains(&"tried".to_owned()));
+        assert!(inflect("fly", "s").contains(&"flies".to_owned()));
+        assert!(inflect("box", "s").contains(&"boxes".to_owned()));
+        assert_eq!(inflect("work", "s")[0], "works");
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Result,
+    num::NonZeroUsize,
+    path::Path,
+};
+
+use crate::{
+    chords::{Chord, Chords, Notation},
+    clock,
+    storage::append,
+    words::Words,
+};
+
+pub enum Change {
+    Chord {
+        word: String,
+        before: Option<Chord>,
+        after: Option<Chord>,
+    },
+    Word {
+        word: String,
+        listed: bool,
+    },
+    Rank {
+        word: String,
+        before: NonZeroUsize,
+        after: NonZeroUsize,
+    },
+}
+
+impl Change {
+    pub fn format(&self, notation: &Notation) -> String {
+        match self {
+            Change::Chord {
+                word,
+                before: None,
+                after: Some(after),
+            } => format!("add {word}: {}", notation.format(after)),
+            Change::Chord {
+                word,
+                before: Some(before),
+                after: Some(after),
+            } => format!(
+                "edit {word}: {} -> {}",
+                notation.format(before),
+                notation.format(after)
+            ),
+            Change::Chord {
+                word,
+                before: Some(before),
+                after: None,
+            } => format!("delete {word}: {}", notation.format(before)),
+            Change::Chord { word, .. } => format!("edit {word}"),
+            Change::Word { word, listed: true } => format!("add-word {word}"),
+            Change::Word {
+                word,
+                listed: false,
+            } => format!("delete-word {word}"),
+            Change::Rank {
+                word,
+                before,
+                after,
+            } => format!("rank {word}: {before} -> {after}"),
+        }
+    }
+}
+
+pub fn summary<'a>(changes: impl IntoIterator<Item = &'a Change>) -> String {
+    let mut counts = [0; 6];
+    for change in changes {
+        let index = match change {
+            Change::Chord { before: None, .. } => 0,
+            Change::Chord { after: None, .. } => 1,
+            Change::Chord { .. } => 2,
+            Change::Word { listed: true, .. } => 3,
+            Change::Word { listed: false, .. } => 4,
+            Change::Rank { .. } => 5,
+        };
+        counts[index] += 1;
+    }
+
+    let [added, removed, changed, words_added, words_removed, reranked] = counts;
+    let mut summary = format!("Update chords: {added} added, {removed} removed, {changed} changed");
+    if words_added + words_removed + reranked > 0 {
+        summary.push_str(&format!(
+            "; words: {words_added} added, {words_removed} removed, {reranked} reranked"
+        ));
+    }
+
+    summary
+}
+
+pub fn diff_chords(before: &Chords, after: &Chords) -> Vec<Change> {
+    let mut words: BTreeMap<String, (Vec<Chord>, Vec<Chord>)> = BTreeMap::new();
+
+    for (chord, word) in before.iter() {
+        if after.get(&chord) != Some(&word) {
+            words.entry(word).or_default().0.push(chord);
+        }
+    }
+    for (chord, word) in after.iter() {
+        if before.get(&chord) != Some(&word) {
+            words.entry(word).or_default().1.push(chord);
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (word, (removed, added)) in words {
+        let mut removed = removed.into_iter();
+        let mut added = added.into_iter();
+
+        loop {
+            let (before, after) = (removed.next(), added.next());
+            if before.is_none() && after.is_none() {
+                break;
+            }
+
+            changes.push(Change::Chord {
+                word: word.clone(),
+                before,
+                after,
+            });
+        }
+    }
+
+    changes
+}
+
+pub fn diff_words(before: &BTreeSet<String>, after: &Words) -> Vec<Change> {
+    let after: BTreeSet<_> = after
+        .iter()
+        .take(after.ranked())
+        .map(|(word, _)| word.as_str())
+        .collect();
+
+    let removed = before
+        .iter()
+        .filter(|word| !after.contains(word.as_str()))
+        .map(|word| Change::Word {
+            word: word.clone(),
+            listed: false,
+        });
+    let added = after
+        .iter()
+        .filter(|word| !before.contains(**word))
+        .map(|word| Change::Word {
+            word: (*word).to_owned(),
+            listed: true,
+        });
+
+    removed.chain(added).collect()
+}
+
+fn ranked(words: &Words) -> BTreeSet<String> {
+    words
+        .iter()
+        .take(words.ranked())
+        .map(|(word, _)| word.clone())
+        .collect()
+}
+
+#[derive(Default)]
+pub struct Journal {
+    chords: Chords,
+    words: BTreeSet<String>,
+    changes: Vec<(u64, Change)>,
+}
+
+impl Journal {
+    pub fn new(chords: &Chords, words: &Words) -> Self {
+        Self {
+            chords: chords.clone(),
+            words: ranked(words),
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, chords: &Chords, words: &Words) {
+        let mut changes = diff_chords(&self.chords, chords);
+        changes.extend(diff_words(&self.words, words));
+        if changes.is_empty() {
+            return;
+        }
+
+        let timestamp = clock::now();
+        self.chords = chords.clone();
+        self.words = ranked(words);
+        self.changes
+            .extend(changes.into_iter().map(|change| (timestamp, change)));
+    }
+
+    pub fn push(&mut self, change: Change) {
+        self.changes.push((clock::now(), change));
+    }
+
+    pub fn changes(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter().map(|(_, change)| change)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn append_to_file(&self, path: impl AsRef<Path>, notation: &Notation) -> Result<()> {
+        if self.changes.is_empty() {
+            return Ok(());
+        }
+
+        let lines: String = self
+            .changes
+            .iter()
+            .map(|(timestamp, change)| format!("{timestamp} {}\n", change.format(notation)))
+            .collect();
+
+        append(path, lines)
+    }
+}
+
+pub fn append_to_file(
+    path: impl AsRef<Path>,
+    changes: &[Change],
+    notation: &Notation,
+) -> Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let timestamp = clock::now();
+    let lines: String = changes
+        .iter()
+        .map(|change| format!("{timestamp} {}\n", change.format(notation)))
+        .collect();
+
+    append(path, lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn diff_chord_entries() {
+        let before = Chords::from_pairs(&[("H+T", "the"), ("A+N", "and"), ("O+F", "of")]);
+        let after = Chords::from_pairs(&[("E+H+T", "the"), ("A+N", "and"), ("T+W", "two")]);
+
+        let lines: Vec<_> = diff_chords(&before, &after)
+            .iter()
+            .map(|change| change.format(&Notation::default()))
+            .collect();
+
+        assert_eq!(
+            lines,
+            ["delete of: F+O", "edit the: H+T -> E+H+T", "add two: T+W"]
+        );
+    }
+
+    #[test]
+    fn stamp_each_change() {
+        let mut words = Words::default();
+        words.import("the\nof\n");
+        let mut chords = Chords::from_pairs(&[("H+T", "the")]);
+        let mut journal = Journal::new(&chords, &words);
+
+        journal.record(&chords, &words);
+        chords.insert("F+O".parse().unwrap(), "of".to_owned());
+        journal.record(&chords, &words);
+        thread::sleep(Duration::from_millis(5));
+        words.set_rank("of", NonZeroUsize::MIN);
+        journal.push(Change::Rank {
+            word: "of".to_owned(),
+            before: NonZeroUsize::new(2).unwrap(),
+            after: NonZeroUsize::MIN,
+        });
+        journal.record(&chords, &words);
+
+        let [(added_at, added), (ranked_at, ranked)] = &journal.changes[..] else {
+            panic!("expected two changes");
+        };
+        assert!(added_at < ranked_at);
+        assert_eq!(added.format(&Notation::default()), "add of: F+O");
+        assert_eq!(ranked.format(&Notation::default()), "rank of: 2 -> 1");
+        assert_eq!(
+            summary(journal.changes()),
+            "Update chords: 1 added, 0 removed, 0 changed; words: 0 added, 0 removed, 1 reranked"
+        );
+    }
+}
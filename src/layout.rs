@@ -0,0 +1,520 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs::read_to_string,
+    io::{Error, ErrorKind, Result as IoResult},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{chords::Chord, config::Config, cost::Cost};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+impl Display for Hand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Hand::Left => write!(f, "left"),
+            Hand::Right => write!(f, "right"),
+        }
+    }
+}
+
+impl FromStr for Hand {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "left" => Ok(Hand::Left),
+            "right" => Ok(Hand::Right),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Finger {
+    Pinky,
+    Ring,
+    Middle,
+    Index,
+    Thumb,
+}
+
+impl Display for Finger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Finger::Pinky => write!(f, "pinky"),
+            Finger::Ring => write!(f, "ring finger"),
+            Finger::Middle => write!(f, "middle finger"),
+            Finger::Index => write!(f, "index finger"),
+            Finger::Thumb => write!(f, "thumb"),
+        }
+    }
+}
+
+impl FromStr for Finger {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "pinky" => Ok(Finger::Pinky),
+            "ring" => Ok(Finger::Ring),
+            "middle" => Ok(Finger::Middle),
+            "index" => Ok(Finger::Index),
+            "thumb" => Ok(Finger::Thumb),
+            _ => Err(()),
+        }
+    }
+}
+
+const ADJACENT_DISTANCE: f64 = 1.25;
+pub const HOME_ROW: u8 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Key {
+    pub hand: Hand,
+    pub finger: Finger,
+    pub row: u8,
+    pub column: u8,
+    pub position: Option<(f64, f64)>,
+}
+
+impl Key {
+    pub fn origin(&self) -> (f64, f64) {
+        self.position
+            .unwrap_or((f64::from(self.column), f64::from(self.row)))
+    }
+
+    fn is_adjacent(&self, other: &Key) -> bool {
+        let ((x, y), (other_x, other_y)) = (self.origin(), other.origin());
+        let distance = (x - other_x).hypot(y - other_y);
+
+        distance > 0.0 && distance <= ADJACENT_DISTANCE
+    }
+}
+
+#[derive(Clone)]
+pub struct Geometry(Vec<(String, Key)>);
+
+impl Geometry {
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn read_from_file_if_exists(path: impl AsRef<Path>) -> IoResult<Option<Self>> {
+        match read_to_string(path) {
+            Ok(contents) => Self::parse(&contents).map(Some),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn parse(contents: &str) -> IoResult<Self> {
+        contents
+            .lines()
+            .enumerate()
+            .map(|(number, line)| (number, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| {
+                parse_key(line).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Invalid geometry line {}: {line}", number + 1),
+                    )
+                })
+            })
+            .collect::<IoResult<_>>()
+            .map(Self)
+    }
+}
+
+fn parse_key(line: &str) -> Option<(String, Key)> {
+    let fields: Vec<_> = line.split_whitespace().collect();
+    let [name, row, column, hand, finger, position @ ..] = fields.as_slice() else {
+        return None;
+    };
+    if name.chars().count() != 1 {
+        return None;
+    }
+
+    let position = match position {
+        [] => None,
+        [x, y] => Some((x.parse().ok()?, y.parse().ok()?)),
+        _ => return None,
+    };
+    let key = Key {
+        hand: hand.parse().ok()?,
+        finger: finger.parse().ok()?,
+        row: row.parse().ok()?,
+        column: column.parse().ok()?,
+        position,
+    };
+
+    Some((name.to_ascii_uppercase(), key))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    UnknownKey(String),
+    SameFinger {
+        keys: (String, String),
+        hand: Hand,
+        finger: Finger,
+    },
+    TooManyKeys {
+        hand: Hand,
+        count: usize,
+        max: usize,
+    },
+    TooManyImpulseKeys {
+        count: usize,
+        max: usize,
+    },
+    TooManyKeysPressed {
+        count: usize,
+        max: usize,
+    },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Violation::UnknownKey(key) => write!(f, "key {key} is not on the keyboard"),
+            Violation::SameFinger {
+                keys: (first, second),
+                hand,
+                finger,
+            } => write!(f, "keys {first} and {second} both use the {hand} {finger}"),
+            Violation::TooManyKeys { hand, count, max } => {
+                write!(f, "{count} keys on the {hand} hand, at most {max} allowed")
+            }
+            Violation::TooManyImpulseKeys { count, max } => {
+                write!(f, "{count} keys in an impulse chord, at most {max} allowed")
+            }
+            Violation::TooManyKeysPressed { count, max } => {
+                write!(f, "{count} keys pressed at once, at most {max} registered")
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Constraints {
+    allow_adjacent_same_finger: bool,
+    max_keys_per_hand: Option<usize>,
+    max_impulse_keys: Option<usize>,
+    max_keys: Option<usize>,
+}
+
+pub struct Layout {
+    keys: BTreeMap<String, Key>,
+    impulse_keys: BTreeSet<String>,
+    constraints: Constraints,
+    cost: Option<Cost>,
+}
+
+impl Layout {
+    pub fn from_config(config: &Config) -> Self {
+        let mut layout = match &config.geometry {
+            Some(Geometry(keys)) => Self {
+                keys: keys.iter().cloned().collect(),
+                impulse_keys: BTreeSet::new(),
+                constraints: Constraints::default(),
+                cost: None,
+            },
+            None => Self::qwerty(),
+        };
+
+        layout.keys.extend(config.custom_keys.iter().cloned());
+        layout
+            .impulse_keys
+            .extend(config.impulse_keys.iter().cloned());
+
+        for (hand, finger, keys) in &config.fingers {
+            for key in keys {
+                if let Some(key) = layout.keys.get_mut(key) {
+                    key.hand = *hand;
+                    key.finger = *finger;
+                }
+            }
+        }
+
+        layout.constraints = Constraints {
+            allow_adjacent_same_finger: config.allow_adjacent_same_finger,
+            max_keys_per_hand: config.max_keys_per_hand,
+            max_impulse_keys: config.max_impulse_keys,
+            max_keys: config.max_keys,
+        };
+        layout.cost.clone_from(&config.cost);
+
+        layout
+    }
+
+    pub fn qwerty() -> Self {
+        const ROWS: [&str; 4] = ["1234567890", "QWERTYUIOP", "ASDFGHJKL", "ZXCVBNM"];
+        const FINGERS: [(Hand, Finger); 10] = [
+            (Hand::Left, Finger::Pinky),
+            (Hand::Left, Finger::Ring),
+            (Hand::Left, Finger::Middle),
+            (Hand::Left, Finger::Index),
+            (Hand::Left, Finger::Index),
+            (Hand::Right, Finger::Index),
+            (Hand::Right, Finger::Index),
+            (Hand::Right, Finger::Middle),
+            (Hand::Right, Finger::Ring),
+            (Hand::Right, Finger::Pinky),
+        ];
+
+        let keys = ROWS
+            .iter()
+            .zip(0..)
+            .flat_map(|(keys, row)| {
+                keys.chars().zip(0..).map(move |(char, column)| {
+                    let (hand, finger) = FINGERS[usize::from(column)];
+                    let key = Key {
+                        hand,
+                        finger,
+                        row,
+                        column,
+                        position: None,
+                    };
+
+                    (char.to_string(), key)
+                })
+            })
+            .collect();
+
+        Self {
+            keys,
+            impulse_keys: BTreeSet::new(),
+            constraints: Constraints::default(),
+            cost: None,
+        }
+    }
+
+    pub fn cost(&self) -> Option<&Cost> {
+        self.cost.as_ref()
+    }
+
+    pub fn key(&self, key: &str) -> Option<&Key> {
+        self.keys.get(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = (&str, &Key)> {
+        self.keys.iter().map(|(name, key)| (name.as_str(), key))
+    }
+
+    pub fn is_impulse(&self, chord: &Chord) -> bool {
+        !chord.is_empty()
+            && chord.keys().all(|key| {
+                key.chars().all(|char| char.is_ascii_digit()) || self.impulse_keys.contains(key)
+            })
+    }
+
+    pub fn violations(&self, chord: &Chord) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut pressed: Vec<(&str, &Key)> = Vec::new();
+
+        for name in chord.keys() {
+            let Some(key) = self.key(name) else {
+                violations.push(Violation::UnknownKey(name.to_owned()));
+                continue;
+            };
+
+            for (other_name, other) in &pressed {
+                let same_finger = other.hand == key.hand && other.finger == key.finger;
+                let allowed = self.constraints.allow_adjacent_same_finger && other.is_adjacent(key);
+
+                if same_finger && !allowed {
+                    violations.push(Violation::SameFinger {
+                        keys: ((*other_name).to_owned(), name.to_owned()),
+                        hand: key.hand,
+                        finger: key.finger,
+                    });
+                }
+            }
+
+            pressed.push((name, key));
+        }
+
+        if self.is_impulse(chord) {
+            if let Some(max) = self.constraints.max_impulse_keys {
+                let count = pressed.len();
+
+                if count > max {
+                    violations.push(Violation::TooManyImpulseKeys { count, max });
+                }
+            }
+        } else if let Some(max) = self.constraints.max_keys_per_hand {
+            for hand in [Hand::Left, Hand::Right] {
+                let count = pressed.iter().filter(|(_, key)| key.hand == hand).count();
+
+                if count > max {
+                    violations.push(Violation::TooManyKeys { hand, count, max });
+                }
+            }
+        }
+        if let Some(max) = self.constraints.max_keys {
+            let count = chord.keys().count();
+
+            if count > max {
+                violations.push(Violation::TooManyKeysPressed { count, max });
+            }
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_violations() {
+        let layout = Layout::qwerty();
+
+        let possible: Chord = "A+S+D+F".parse().unwrap();
+        assert!(layout.violations(&possible).is_empty());
+
+        let same_finger: Chord = "A+Q".parse().unwrap();
+        assert_eq!(
+            layout.violations(&same_finger),
+            [Violation::SameFinger {
+                keys: ("A".to_owned(), "Q".to_owned()),
+                hand: Hand::Left,
+                finger: Finger::Pinky,
+            }]
+        );
+
+        let unknown: Chord = "[+A".parse().unwrap();
+        assert_eq!(
+            layout.violations(&unknown),
+            [Violation::UnknownKey("[".to_owned())]
+        );
+    }
+
+    #[test]
+    fn apply_config_constraints() {
+        let config = Config {
+            fingers: vec![(Hand::Left, Finger::Ring, vec!["Q".to_owned()])],
+            allow_adjacent_same_finger: true,
+            max_keys_per_hand: Some(2),
+            ..Config::default()
+        };
+        let layout = Layout::from_config(&config);
+
+        let reassigned: Chord = "A+Q".parse().unwrap();
+        assert!(layout.violations(&reassigned).is_empty());
+
+        let adjacent: Chord = "R+T".parse().unwrap();
+        assert!(layout.violations(&adjacent).is_empty());
+
+        let distant: Chord = "R+B".parse().unwrap();
+        assert_eq!(layout.violations(&distant).len(), 1);
+
+        let too_many: Chord = "A+S+D".parse().unwrap();
+        assert_eq!(
+            layout.violations(&too_many),
+            [Violation::TooManyKeys {
+                hand: Hand::Left,
+                count: 3,
+                max: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn load_geometry() {
+        let geometry = Geometry::parse(
+            "# key row column hand finger [x y]\nT0 3 0 left index 3.5 3.2\nx 1 0 left pinky\n",
+        );
+        assert!(geometry.is_err());
+
+        let geometry = Geometry::parse(
+            "# key row column hand finger [x y]\nA 0 0 left pinky\nB 0 1 left pinky 1.1 0.1\nC 1 5 right index\n",
+        )
+        .unwrap();
+        let layout = Layout::from_config(&Config {
+            geometry: Some(geometry),
+            allow_adjacent_same_finger: true,
+            ..Config::default()
+        });
+
+        assert!(layout.key("Q").is_none());
+        assert_eq!(layout.key("C").map(|key| key.hand), Some(Hand::Right));
+        assert!(layout.violations(&"A+B".parse().unwrap()).is_empty());
+        assert_eq!(layout.violations(&"A+S".parse().unwrap()).len(), 1);
+    }
+
+    #[test]
+    fn custom_key_tokens() {
+        let thumb = Key {
+            hand: Hand::Right,
+            finger: Finger::Thumb,
+            row: 3,
+            column: 0,
+            position: None,
+        };
+        let layout = Layout::from_config(&Config {
+            custom_keys: vec![("DUP".to_owned(), thumb)],
+            ..Config::default()
+        });
+
+        assert!(layout.violations(&"DUP+E+T".parse().unwrap()).is_empty());
+        assert_eq!(
+            layout.violations(&"MB1+E".parse().unwrap()),
+            [Violation::UnknownKey("MB1".to_owned())]
+        );
+    }
+
+    #[test]
+    fn impulse_chords() {
+        let layout = Layout::from_config(&Config {
+            max_keys_per_hand: Some(2),
+            max_impulse_keys: Some(3),
+            impulse_keys: vec!["DUP".to_owned()],
+            custom_keys: vec![(
+                "DUP".to_owned(),
+                Key {
+                    hand: Hand::Right,
+                    finger: Finger::Thumb,
+                    row: 4,
+                    column: 0,
+                    position: None,
+                },
+            )],
+            ..Config::default()
+        });
+
+        let impulse: Chord = "1+2+3".parse().unwrap();
+        assert!(layout.is_impulse(&impulse));
+        assert!(layout.violations(&impulse).is_empty());
+        assert!(layout.is_impulse(&"DUP+5".parse().unwrap()));
+        assert!(!layout.is_impulse(&"1+A".parse().unwrap()));
+
+        assert_eq!(
+            layout.violations(&"1+2+3+7".parse().unwrap()),
+            [Violation::TooManyImpulseKeys { count: 4, max: 3 }]
+        );
+    }
+
+    #[test]
+    fn max_keys() {
+        let layout = Layout::from_config(&Config {
+            max_keys: Some(5),
+            ..Config::default()
+        });
+
+        assert!(layout.violations(&"A+S+D+F+J".parse().unwrap()).is_empty());
+        assert_eq!(
+            layout.violations(&"A+S+D+F+J+K".parse().unwrap()),
+            [Violation::TooManyKeysPressed { count: 6, max: 5 }]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::{io::Result, num::NonZeroUsize, time::Duration};
+
+use crate::{
+    chords::{Chord, Chords, Notation},
+    config::Config,
+    journal,
+    json::Json,
+    layout::Layout,
+    normalize::Normalization,
+    search::{search, Case, Matching, Query, Sort},
+    suggest::{external, merge, suggest, Suggestion},
+    word_set::WordSet,
+    words::{Entry, Words},
+};
+
+pub struct Paths {
+    pub chords: &'static str,
+    pub journal: &'static str,
+}
+
+pub struct Library {
+    chords: Chords,
+    words: Words,
+    notation: Notation,
+    layout: Layout,
+    blacklist: WordSet,
+    suggest_command: Option<String>,
+    suggest_timeout: Duration,
+    normalization: Normalization,
+    paths: Paths,
+}
+
+impl Library {
+    pub fn new(
+        mut chords: Chords,
+        mut words: Words,
+        blacklist: WordSet,
+        config: &Config,
+        paths: Paths,
+    ) -> Self {
+        chords.normalize(config.normalization);
+        words.normalize(config.normalization);
+
+        Self {
+            chords,
+            words,
+            notation: Notation::from_config(config),
+            layout: Layout::from_config(config),
+            blacklist,
+            suggest_command: config.suggest_command.clone(),
+            suggest_timeout: config.suggest_timeout,
+            normalization: config.normalization,
+            paths,
+        }
+    }
+
+    pub fn notation(&self) -> &Notation {
+        &self.notation
+    }
+
+    pub fn search(&self, query: &str, limit: usize) -> Vec<(&String, &Entry)> {
+        let query = self.normalization.apply(query);
+        let query = Query::new(&query, Case::default(), Matching::default());
+
+        search(&self.words, &query, Sort::Relevance, || false)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|hit| self.words.get_index(hit.index))
+            .take(limit)
+            .collect()
+    }
+
+    pub fn word(&self, chord: &Chord) -> Option<&String> {
+        self.chords.get(chord)
+    }
+
+    pub fn entry(&self, word: &str) -> Option<&Entry> {
+        let index = self.words.index_of(&self.normalization.apply(word))?;
+        self.words.get_index(index).map(|(_, entry)| entry)
+    }
+
+    pub fn chords_of(&self, word: &str) -> Vec<Chord> {
+        let word = self.normalization.apply(word);
+
+        self.chords
+            .iter()
+            .filter(|(_, other)| *other == word)
+            .map(|(chord, _)| chord)
+            .collect()
+    }
+
+    pub fn suggest(&self, word: &str, count: usize) -> Result<Vec<Suggestion>> {
+        let word = &self.normalization.apply(word);
+        let suggestions = suggest(word, &self.chords, &self.layout, &self.blacklist, count);
+        let Some(command) = self
+            .suggest_command
+            .as_deref()
+            .filter(|_| !self.blacklist.contains(word))
+        else {
+            return Ok(suggestions);
+        };
+
+        let candidates = external(
+            command,
+            self.suggest_timeout,
+            word,
+            &self.chords,
+            &self.notation,
+        )?;
+        Ok(merge(
+            suggestions,
+            candidates,
+            &self.chords,
+            &self.layout,
+            count,
+        ))
+    }
+
+    pub fn assign(&mut self, chord: Chord, word: String) -> Result<Option<String>> {
+        let word = self.normalization.apply(&word).into_owned();
+        if let Some(existing) = self
+            .chords
+            .get(&chord)
+            .filter(|existing| **existing != word)
+        {
+            return Ok(Some(existing.clone()));
+        }
+
+        let before = self.chords.clone();
+        self.chords.insert(chord.clone(), word.clone());
+        self.words.update_chord(word, chord);
+        self.save(&before)?;
+
+        Ok(None)
+    }
+
+    pub fn remove(&mut self, chord: &Chord) -> Result<Option<String>> {
+        let before = self.chords.clone();
+        let Some(word) = self.chords.remove(chord) else {
+            return Ok(None);
+        };
+        if self.entry(&word).is_some_and(|entry| entry.chord == *chord) {
+            let remaining = self.chords_of(&word).into_iter().next();
+            self.words
+                .update_chord(word.clone(), remaining.unwrap_or_default());
+        }
+        self.save(&before)?;
+
+        Ok(Some(word))
+    }
+
+    pub fn entry_json(&self, word: &str, entry: Option<&Entry>) -> Json {
+        let chords = self.chords_of(word);
+
+        Json::Object(vec![
+            ("word", word.into()),
+            (
+                "chords",
+                Json::array(chords.iter().map(|chord| self.notation.format(chord))),
+            ),
+            (
+                "rank",
+                entry
+                    .and_then(|entry| entry.rank)
+                    .map(NonZeroUsize::get)
+                    .into(),
+            ),
+        ])
+    }
+
+    fn save(&mut self, before: &Chords) -> Result<()> {
+        let changes = journal::diff_chords(before, &self.chords);
+        journal::append_to_file(self.paths.journal, &changes, &self.notation)?;
+
+        for (word, entry) in self.words.iter() {
+            if !entry.chord.is_empty() {
+                self.chords.set_metadata(word, entry.metadata.clone());
+            }
+        }
+        self.chords.write_to_file(self.paths.chords, &self.notation)
+    }
+}
@@ -1,27 +1,1096 @@
+#[cfg(feature = "sqlite")]
+mod apkg;
 mod chords;
+mod cli;
+mod clock;
+mod completions;
+mod config;
+mod cost;
+mod coverage;
+#[cfg(unix)]
+mod daemon;
+mod export;
+mod git;
+mod import;
+mod inflection;
+mod journal;
+mod json;
+mod layout;
+mod library;
+mod merge;
+mod normalize;
+mod practice;
+mod search;
+mod server;
+mod simulate;
+mod stats;
+mod steno;
+mod storage;
+mod suggest;
+mod transform;
 mod tui;
+mod usage;
+mod validate;
+mod word_set;
 mod words;
 
-use std::io::Result;
+use std::{
+    collections::BTreeSet,
+    env::args,
+    error::Error as StdError,
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs::{self, read_to_string},
+    io::{stdin, stdout, BufRead, BufWriter, Error, ErrorKind, Result, Write},
+    num::NonZeroUsize,
+    path::Path,
+    process::ExitCode,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
 
-use chords::Chords;
-use tui::Tui;
+use chords::{Chords, Notation};
+use cli::{Command, MappingSource, Resolution};
+use config::Config;
+use export::{Context, Format, Subset, EXPORTERS};
+use import::Columns;
+use json::Json;
+use layout::{Geometry, Layout};
+use library::{Library, Paths};
+use practice::{History, Table};
+use simulate::Model;
+use stats::HandLoad;
+use steno::Steno;
+use transform::{Collision, Mapping};
+use tui::{Session, Tui};
+use usage::Log;
+use validate::Warning;
+use word_set::WordSet;
 use words::Words;
 
-fn main() -> Result<()> {
-    const CHORDS_PATH: &str = "chords.txt";
-    const WORDS_PATH: &str = "words.txt";
+const CHORDS_PATH: &str = "chords.txt";
+const WORDS_PATH: &str = "words.txt";
+const CONFIG_PATH: &str = "config.txt";
+const USAGE_PATH: &str = "usage.log";
+const SESSION_PATH: &str = "session.txt";
+const STOP_WORDS_PATH: &str = "stopwords.txt";
+const BLACKLIST_PATH: &str = "blacklist.txt";
+const PINNED_PATH: &str = "pinned.txt";
+const JOURNAL_PATH: &str = "journal.log";
+const GEOMETRY_PATH: &str = "geometry.txt";
+const PRACTICE_PATH: &str = "practice.log";
+const BASELINE_PATH: &str = "baseline.txt";
+const PERSONAL_PATH: &str = "personal.txt";
+const DATA_PATHS: &[(&str, storage::Table)] = &[
+    (CHORDS_PATH, storage::Table::Entries),
+    (WORDS_PATH, storage::Table::Words),
+    (USAGE_PATH, storage::Table::History),
+    (SESSION_PATH, storage::Table::Session),
+    (STOP_WORDS_PATH, storage::Table::Words),
+    (BLACKLIST_PATH, storage::Table::Words),
+    (PINNED_PATH, storage::Table::Words),
+    (JOURNAL_PATH, storage::Table::History),
+    (PRACTICE_PATH, storage::Table::History),
+    (BASELINE_PATH, storage::Table::Words),
+    (PERSONAL_PATH, storage::Table::Words),
+];
+const REBALANCE_SUGGESTIONS: usize = 5;
+const REBALANCE_ALTERNATIVES: usize = 3;
+const WEAKEST_CHORDS: usize = 5;
 
-    let mut chords = Chords::read_from_file(CHORDS_PATH)?;
-    let words = Words::read_from_file_and_chords(WORDS_PATH, &chords)?;
-    let mut tui = Tui::new(words)?;
+const EXIT_WARNINGS: u8 = 1;
+const EXIT_ERROR: u8 = 2;
+const EXIT_CONFLICTS: u8 = 3;
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+macro_rules! output {
+    ($($arg:tt)*) => {
+        if !QUIET.load(Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
+
+#[derive(Debug)]
+struct Conflicts {
+    count: usize,
+    kind: &'static str,
+}
+
+impl Display for Conflicts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {}, nothing written", self.count, self.kind)
+    }
+}
+
+impl StdError for Conflicts {}
+
+impl From<Conflicts> for Error {
+    fn from(conflicts: Conflicts) -> Self {
+        Error::new(ErrorKind::InvalidData, conflicts)
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("Error: {error}");
+
+            let conflicts = error.get_ref().is_some_and(|inner| inner.is::<Conflicts>());
+            ExitCode::from(if conflicts {
+                EXIT_CONFLICTS
+            } else {
+                EXIT_ERROR
+            })
+        }
+    }
+}
+
+fn run() -> Result<ExitCode> {
+    let mut args: Vec<_> = args().skip(1).collect();
+    QUIET.store(cli::take_flag(&mut args, "quiet"), Ordering::Relaxed);
+
+    let command = Command::parse(args.into_iter())?;
+    let mut config = Config::read_from_file(CONFIG_PATH)?;
+    config.geometry = Geometry::read_from_file_if_exists(GEOMETRY_PATH)?;
+    storage::open(&config, DATA_PATHS)?;
+
+    let result = match command {
+        Command::Tui => run_tui(&config),
+        Command::Validate { json } => return run_validate(&config, json),
+        Command::Search { query, limit, json } => run_search(&config, &query, limit, json),
+        Command::Export {
+            format,
+            output,
+            subset,
+        } => run_export(&config, &format, output.as_deref(), &subset),
+        Command::ListFormats => run_list_formats(),
+        Command::ExportPractice { table } => run_export_practice(&config, &table),
+        Command::Misfires { log } => run_misfires(&log),
+        Command::Stats { corpus, json } => run_stats(&config, corpus.as_deref(), json),
+        Command::ImportWords {
+            list,
+            columns,
+            replace,
+        } => run_import_words(&config, &list, columns.as_ref(), replace),
+        Command::Reweight {
+            corpus,
+            weight,
+            min_count,
+        } => run_reweight(&config, &corpus, weight, min_count),
+        Command::Import { file } => run_import(&config, &file),
+        Command::Suggest { word, count } => run_suggest(&config, &word, count),
+        Command::Filter { chorded, max_rank } => run_filter(chorded, max_rank),
+        Command::Coverage { corpus, top, json } => run_coverage(&corpus, top, json),
+        Command::Simulate {
+            corpus,
+            chord_time,
+            char_time,
+            json,
+        } => run_simulate(&config, &corpus, chord_time, char_time, json),
+        Command::Completions { shell } => {
+            print!("{}", completions::completions(&shell));
+            Ok(())
+        }
+        Command::Serve { port } => run_serve(&config, port),
+        #[cfg(unix)]
+        Command::Daemon { socket } => daemon::run(&open_library(&config)?, Path::new(&socket)),
+        Command::Substitute { from, to, apply } => run_substitute(&config, from, to, apply),
+        Command::MigrateLayout { source, apply } => run_migrate_layout(&config, &source, apply),
+        Command::Suppress {
+            word,
+            warning,
+            clear,
+        } => run_suppress(&config, &word, &warning, clear),
+        Command::Orphans { resolution } => run_orphans(&config, resolution.as_ref()),
+        Command::Merge {
+            base,
+            ours,
+            theirs,
+            output,
+        } => run_merge(&config, &base, &ours, &theirs, output.as_deref()),
+    };
+
+    result.map(|()| ExitCode::SUCCESS)
+}
+
+fn run_tui(config: &Config) -> Result<()> {
+    let mut tui = Tui::new(config)?;
+
+    let normalization = config.normalization;
+    let loaded = tui.load(move |progress| {
+        let mut chords = Chords::read_from_file_with_progress(CHORDS_PATH, &progress.chords)?;
+        chords.normalize(normalization);
+        let mut words =
+            Words::read_from_file_and_chords_with_progress(WORDS_PATH, &chords, &progress.words)?;
+        words.normalize(normalization);
+        let log = Log::read_from_file_if_exists(USAGE_PATH, &progress.events)?;
+        let stop_words = WordSet::read_from_file_if_exists(STOP_WORDS_PATH)?;
+        let blacklist = WordSet::read_from_file_if_exists(BLACKLIST_PATH)?;
+        let pinned = WordSet::read_from_file_if_exists(PINNED_PATH)?;
+        let session = Session::read_from_file_if_exists(SESSION_PATH)?;
+        let practice = History::read_from_file_if_exists(PRACTICE_PATH)?;
+
+        Ok((
+            chords, words, log, stop_words, blacklist, pinned, session, practice,
+        ))
+    });
+    let (mut chords, words, log, stop_words, blacklist, pinned, session, practice) = match loaded {
+        Ok(Some(loaded)) => loaded,
+        Ok(None) => return tui.finish(),
+        Err(error) => {
+            tui.finish()?;
+            return Err(error);
+        }
+    };
+    tui.open(words, &log, stop_words, blacklist, pinned, session);
+    tui.open_practice(practice);
 
     if let Err(error) = tui.run_event_loop(&mut chords) {
         eprintln!("Error when running event loop: {error}");
     }
 
-    chords.write_to_file(CHORDS_PATH)?;
+    let notation = Notation::from_config(config);
+    let journal = tui.journal(&chords);
+    journal.append_to_file(JOURNAL_PATH, &notation)?;
+    let summary = (!journal.is_empty()).then(|| journal::summary(journal.changes()));
+
+    for (word, entry) in tui.words().iter() {
+        if !entry.chord.is_empty() {
+            chords.set_metadata(word, entry.metadata.clone());
+        }
+    }
+    chords.write_to_file(CHORDS_PATH, &notation)?;
+    let words_changed = tui.words_changed();
+    if words_changed {
+        tui.words().write_to_file(WORDS_PATH)?;
+    }
+    if tui.stop_words_changed() {
+        tui.stop_words().write_to_file(STOP_WORDS_PATH)?;
+    }
+    if tui.pinned_changed() {
+        tui.pinned().write_to_file(PINNED_PATH)?;
+    }
+    tui.session().write_to_file(SESSION_PATH)?;
+    History::append_to_file(PRACTICE_PATH, tui.new_practice_records())?;
+    if tui.pane_ratio() != config.pane_ratio {
+        Config::update_file(CONFIG_PATH, "pane_ratio", &tui.pane_ratio().to_string())?;
+    }
+    if tui.columns() != config.columns {
+        let columns: Vec<_> = tui.columns().iter().map(|column| column.name()).collect();
+        Config::update_file(CONFIG_PATH, "columns", &columns.join(" "))?;
+    }
+    let model = tui.model();
+    if model != config.model {
+        Config::update_file(
+            CONFIG_PATH,
+            "chord_time",
+            &format!("{:.0}", model.chord_time),
+        )?;
+        Config::update_file(CONFIG_PATH, "char_time", &format!("{:.0}", model.char_time))?;
+    }
     tui.finish()?;
 
+    let summary = summary.filter(|_| config.auto_commit && git::is_repository());
+    if let Some(summary) = summary {
+        let mut paths = vec![CHORDS_PATH];
+        if words_changed {
+            paths.push(WORDS_PATH);
+        }
+        git::commit(&storage::tracked(config, paths), &summary)?;
+    }
+
+    Ok(())
+}
+
+fn run_validate(config: &Config, json: bool) -> Result<ExitCode> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let words = Words::read_from_file_and_chords(WORDS_PATH, &chords)?;
+    let warnings = validate::validate(&chords, &words, &Layout::from_config(config), config);
+
+    if json {
+        let warnings = warnings.iter().map(Warning::to_json);
+        output!(
+            "{}",
+            Json::Object(vec![("warnings", Json::array(warnings))])
+        );
+    } else {
+        for warning in &warnings {
+            output!("{warning}");
+        }
+    }
+
+    Ok(if warnings.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_WARNINGS)
+    })
+}
+
+fn open_library(config: &Config) -> Result<Library> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let words = Words::read_from_file_and_chords(WORDS_PATH, &chords)?;
+    let blacklist = WordSet::read_from_file_if_exists(BLACKLIST_PATH)?;
+
+    Ok(Library::new(
+        chords,
+        words,
+        blacklist,
+        config,
+        Paths {
+            chords: CHORDS_PATH,
+            journal: JOURNAL_PATH,
+        },
+    ))
+}
+
+fn run_serve(config: &Config, port: u16) -> Result<()> {
+    server::serve(&mut open_library(config)?, port)
+}
+
+fn run_search(config: &Config, query: &str, limit: usize, json: bool) -> Result<()> {
+    let library = open_library(config)?;
+    let notation = library.notation();
+    let results = library.search(query, limit).into_iter();
+
+    if json {
+        let results = results.map(|(word, entry)| {
+            Json::Object(vec![
+                ("word", word.as_str().into()),
+                (
+                    "chord",
+                    Some(&entry.chord)
+                        .filter(|chord| !chord.is_empty())
+                        .map(|chord| notation.format(chord))
+                        .into(),
+                ),
+                ("rank", entry.rank.map(NonZeroUsize::get).into()),
+            ])
+        });
+        output!("{}", Json::Object(vec![("results", Json::array(results))]));
+    } else {
+        for (word, entry) in results {
+            let rank = entry.rank.map_or(String::new(), |rank| rank.to_string());
+            output!("{word}\t{}\t{rank}", notation.format(&entry.chord));
+        }
+    }
+
+    Ok(())
+}
+
+fn run_export(
+    config: &Config,
+    format: &Format,
+    output: Option<&str>,
+    subset: &Subset,
+) -> Result<()> {
+    let mut chords = Chords::read_from_file(CHORDS_PATH)?;
+    let words = Words::read_from_file_and_chords(WORDS_PATH, &chords)?;
+    let notation = Notation::from_config(config);
+    let layout = Layout::from_config(config);
+    let steno = Steno::from_config(config);
+
+    subset.apply(&mut chords, &words);
+
+    if let Some(output) = output.filter(|output| output.ends_with(".apkg")) {
+        if format.name() != "anki" {
+            return Err(cli::invalid_input(
+                "Only the anki format can be written as .apkg".to_owned(),
+            ));
+        }
+        let cards = export::anki_cards(&chords, &words, &notation, &layout);
+        return write_apkg(output, &cards);
+    }
+
+    let export = format.export(&Context {
+        chords: &chords,
+        words: &words,
+        notation: &notation,
+        layout: &layout,
+        steno: &steno,
+    });
+    match output {
+        Some(output) => fs::write(output, &export.text)?,
+        None => print!("{}", export.text),
+    }
+    for entry in &export.skipped {
+        eprintln!("skipped: {entry}");
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+fn write_apkg(output: &str, cards: &[export::Card]) -> Result<()> {
+    fs::write(output, apkg::package(cards)?)
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn write_apkg(_: &str, _: &[export::Card]) -> Result<()> {
+    Err(Error::other(
+        "writing .apkg decks requires building with the sqlite feature",
+    ))
+}
+
+fn run_list_formats() -> Result<()> {
+    let width = EXPORTERS
+        .iter()
+        .map(|exporter| exporter.name().len())
+        .max()
+        .unwrap_or_default();
+
+    for exporter in EXPORTERS {
+        output!("{:width$}  {}", exporter.name(), exporter.description());
+    }
+
+    Ok(())
+}
+
+fn run_stats(config: &Config, corpus: Option<&str>, json: bool) -> Result<()> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let layout = Layout::from_config(config);
+    let notation = Notation::from_config(config);
+    let log = Log::read_from_file_if_exists(USAGE_PATH, &AtomicUsize::new(0))?;
+    let uses = log.uses();
+
+    let keys = stats::hand_load(&chords, &layout, |_| 1);
+    let activations = stats::hand_load(&chords, &layout, |chord| {
+        uses.get(chord).copied().unwrap_or(0)
+    });
+    let use_activations = activations.total() > 0;
+    let load = if use_activations { activations } else { keys };
+
+    let weight = |chord: &_| {
+        if use_activations {
+            uses.get(chord).copied().unwrap_or(0)
+        } else {
+            1
+        }
+    };
+    let blacklist = WordSet::read_from_file_if_exists(BLACKLIST_PATH)?;
+    let suggestions = stats::rebalance(
+        &chords,
+        &layout,
+        &blacklist,
+        weight,
+        REBALANCE_SUGGESTIONS,
+        REBALANCE_ALTERNATIVES,
+    );
+
+    // the chord time measured from the usage log, the baseline typing speed from the config
+    let model = Model {
+        chord_time: log.chord_time().unwrap_or(config.model.chord_time),
+        char_time: config.model.char_time,
+    };
+    let simulation = match corpus {
+        Some(corpus) => Some(simulate::simulate(&read_to_string(corpus)?, &chords, model)),
+        None => None,
+    };
+    let practice = History::read_from_file_if_exists(PRACTICE_PATH)?;
+    let now = clock::now();
+    let weakest = practice.weakest(WEAKEST_CHORDS);
+
+    if json {
+        let hand_load = |load: HandLoad| {
+            Json::Object(vec![
+                ("left", load.left.into()),
+                ("right", load.right.into()),
+            ])
+        };
+        let imbalance = load.heavier().map(|(hand, imbalance)| {
+            Json::Object(vec![
+                ("hand", hand.to_string().into()),
+                ("share", imbalance.into()),
+            ])
+        });
+        let rebalance = suggestions.iter().map(|rebalance| {
+            Json::Object(vec![
+                ("chord", notation.format(&rebalance.chord).into()),
+                ("word", rebalance.word.as_str().into()),
+                (
+                    "alternatives",
+                    Json::array(
+                        rebalance
+                            .alternatives
+                            .iter()
+                            .map(|suggestion| notation.format(&suggestion.chord)),
+                    ),
+                ),
+            ])
+        });
+
+        let stats = Json::Object(vec![
+            ("chords", chords.iter().count().into()),
+            ("keys", hand_load(keys)),
+            (
+                "activations",
+                use_activations.then(|| hand_load(activations)).into(),
+            ),
+            ("imbalance", imbalance.into()),
+            ("rebalance", Json::array(rebalance)),
+            (
+                "speed",
+                simulation
+                    .as_ref()
+                    .map(|simulation| {
+                        Json::Object(vec![
+                            ("chord_time", model.chord_time.into()),
+                            ("char_time", model.char_time.into()),
+                            ("typed_wpm", simulation.typed_wpm().into()),
+                            ("chorded_wpm", simulation.chorded_wpm().into()),
+                        ])
+                    })
+                    .into(),
+            ),
+            (
+                "practice",
+                (!practice.is_empty())
+                    .then(|| {
+                        let weakest = weakest.iter().map(|weakness| {
+                            Json::Object(vec![
+                                ("chord", notation.format(&weakness.chord).into()),
+                                ("word", weakness.word.as_str().into()),
+                                ("attempts", weakness.attempts.into()),
+                                ("accuracy", weakness.accuracy.into()),
+                            ])
+                        });
+                        Json::Object(vec![
+                            ("attempts", practice.records().len().into()),
+                            ("streak", practice.streak(now).into()),
+                            ("retention", practice.average_retention(now).into()),
+                            ("weakest", Json::array(weakest)),
+                        ])
+                    })
+                    .into(),
+            ),
+        ]);
+        output!("{stats}");
+
+        return Ok(());
+    }
+
+    output!("Chords: {}", chords.iter().count());
+    output!("Chord keys: {keys}");
+    if use_activations {
+        output!("Activations: {activations}");
+    }
+    match load.heavier() {
+        Some((hand, imbalance)) => output!(
+            "Imbalance: {hand} hand carries {:.1} percentage points more",
+            imbalance * 100.0
+        ),
+        None => output!("Imbalance: none"),
+    }
+    if let Some(simulation) = simulation {
+        output!(
+            "Estimated speed: {:.1} WPM typed, {:.1} WPM with chords ({:+.1})",
+            simulation.typed_wpm(),
+            simulation.chorded_wpm(),
+            simulation.chorded_wpm() - simulation.typed_wpm()
+        );
+    }
+    if !practice.is_empty() {
+        output!(
+            "Practice: {} answers, {} day streak, {:.0}% estimated retention",
+            practice.records().len(),
+            practice.streak(now),
+            practice.average_retention(now).unwrap_or_default() * 100.0
+        );
+    }
+    for weakness in &weakest {
+        output!(
+            "  weak: {} ({}) {:.0}% of {}",
+            notation.format(&weakness.chord),
+            weakness.word,
+            weakness.accuracy * 100.0,
+            weakness.attempts
+        );
+    }
+
+    if !suggestions.is_empty() {
+        output!("\nRebalancing suggestions:");
+    }
+    for rebalance in suggestions {
+        let alternatives: Vec<_> = rebalance
+            .alternatives
+            .iter()
+            .map(|suggestion| notation.format(&suggestion.chord))
+            .collect();
+        output!(
+            "{chord} ({word}): try {alternatives}",
+            chord = notation.format(&rebalance.chord),
+            word = rebalance.word,
+            alternatives = alternatives.join(", "),
+        );
+    }
+
+    Ok(())
+}
+
+fn run_export_practice(config: &Config, table: &Table) -> Result<()> {
+    let history = History::read_from_file_if_exists(PRACTICE_PATH)?;
+    let uses = Log::read_from_file_if_exists(USAGE_PATH, &AtomicUsize::new(0))?.uses();
+    let notation = Notation::from_config(config);
+
+    print!("{}", history.to_csv(table, &uses, &notation, clock::now()));
+
+    Ok(())
+}
+
+fn run_misfires(log: &str) -> Result<()> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let log = Log::read_from_file(log)?;
+
+    for misfire in usage::misfires(&log, &chords) {
+        let retyped: Vec<_> = misfire
+            .retyped
+            .iter()
+            .map(|(word, count)| format!("{word} ({count})"))
+            .collect();
+
+        output!(
+            "{chord} ({word}): {misfired} of {fired} activations deleted, retyped as {retyped}",
+            chord = misfire.chord.as_str(),
+            word = misfire.word,
+            misfired = misfire.misfired,
+            fired = misfire.fired,
+            retyped = if retyped.is_empty() {
+                String::from("nothing")
+            } else {
+                retyped.join(", ")
+            },
+        );
+    }
+
+    Ok(())
+}
+
+fn run_import_words(
+    config: &Config,
+    list: &str,
+    columns: Option<&Columns>,
+    replace: bool,
+) -> Result<()> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let mut words = Words::read_from_file_and_chords(WORDS_PATH, &chords)?;
+
+    let mut list = read_to_string(list)?;
+    if let Some(columns) = columns {
+        let mut csv = import::import_words(&list, columns)?;
+        for entry in &csv.skipped {
+            eprintln!("skipped: {entry}");
+        }
+
+        csv.chords.normalize(config.normalization);
+        let added = merge_chords(config, &chords, &csv.chords)?;
+        if added > 0 {
+            output!("Imported {added} new chords into {CHORDS_PATH}");
+        }
+        list = csv
+            .words
+            .iter()
+            .map(|(word, count)| match count {
+                Some(count) => format!("{word}\t{count}\n"),
+                None => format!("{word}\n"),
+            })
+            .collect();
+    }
+
+    let list = config.normalization.apply(&list);
+    let imported = if replace {
+        words.replace(&list)
+    } else {
+        words.import(&list)
+    };
+    let normalized = words.normalize(config.normalization).len();
+    if normalized > 0 {
+        output!("Normalized {normalized} words");
+    }
+    if config.fold_case {
+        for (word, kept) in words.fold_case() {
+            output!("Folded {word} into {kept}");
+        }
+    }
+    words.write_to_file(WORDS_PATH)?;
+
+    if replace {
+        let notation = Notation::from_config(config);
+        for (word, entry) in words.iter().skip(words.ranked()) {
+            output!("unmatched: {}: {word}", notation.format(&entry.chord));
+        }
+        output!("Replaced the word list with {imported} words");
+    } else {
+        output!(
+            "Imported {imported} new words, {} ranked in total",
+            words.ranked()
+        );
+    }
+
+    Ok(())
+}
+
+fn run_reweight(
+    config: &Config,
+    corpus: &str,
+    weight: Option<f64>,
+    min_count: usize,
+) -> Result<()> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let mut words = match Words::read_from_file_and_chords(BASELINE_PATH, &chords) {
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            storage::write(BASELINE_PATH, storage::read_to_string(WORDS_PATH)?)?;
+            Words::read_from_file_and_chords(BASELINE_PATH, &chords)?
+        }
+        words => words?,
+    };
+
+    let corpus = read_to_string(corpus)?;
+    let mut counts = words::read_counts_if_exists(PERSONAL_PATH)?;
+    let mut counted = 0;
+    for token in coverage::tokens(&corpus) {
+        // capitalized at the start of a sentence, but listed in lowercase
+        let lowercase = token.to_lowercase();
+        let word = if !words.contains(token) && words.contains(&lowercase) {
+            lowercase
+        } else {
+            token.to_owned()
+        };
+        *counts.entry(word).or_default() += 1;
+        counted += 1;
+    }
+    words::write_counts(PERSONAL_PATH, &counts)?;
+
+    let before = words.ranked();
+    words.reweight(&counts, weight.unwrap_or(config.personal_weight), min_count);
+    words.write_to_file(WORDS_PATH)?;
+
+    output!(
+        "Reranked {} words from {counted} counted tokens, {} newly ranked",
+        words.ranked(),
+        words.ranked() - before
+    );
+
+    Ok(())
+}
+
+fn run_import(config: &Config, file: &str) -> Result<()> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let steno = Steno::from_config(config);
+    let mut imported = import::import(Path::new(file), &read_to_string(file)?, &steno)?;
+    imported.chords.normalize(config.normalization);
+
+    output!(
+        "Detected {} with {} chords",
+        imported.source,
+        imported.chords.iter().count()
+    );
+    for entry in &imported.skipped {
+        eprintln!("skipped: {entry}");
+    }
+
+    let added = merge_chords(config, &chords, &imported.chords)?;
+    output!("Imported {added} new chords into {CHORDS_PATH}");
+
+    Ok(())
+}
+
+fn merge_chords(config: &Config, chords: &Chords, imported: &Chords) -> Result<usize> {
+    let notation = Notation::from_config(config);
+    let mut declared: Chords = imported
+        .iter()
+        .filter(|(chord, word)| match notation.check(chord) {
+            Ok(()) => true,
+            Err(error) => {
+                eprintln!("skipped: {word}: {error}");
+                false
+            }
+        })
+        .collect();
+    declared.inherit_metadata(imported);
+
+    let (merged, conflicts) = merge::merge(&Chords::default(), chords, &declared);
+    if !conflicts.is_empty() {
+        for conflict in &conflicts {
+            eprintln!("conflict: {conflict}");
+        }
+
+        return Err(Conflicts {
+            count: conflicts.len(),
+            kind: "conflicts",
+        }
+        .into());
+    }
+
+    let added = merged.iter().count() - chords.iter().count();
+    merged.write_to_file(CHORDS_PATH, &notation)?;
+
+    Ok(added)
+}
+
+fn run_suggest(config: &Config, word: &str, count: usize) -> Result<()> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let layout = Layout::from_config(config);
+    let notation = Notation::from_config(config);
+    let blacklist = WordSet::read_from_file_if_exists(BLACKLIST_PATH)?;
+
+    if blacklist.contains(word) {
+        output!("{word} is blacklisted");
+        return Ok(());
+    }
+    for (chord, _) in chords.iter().filter(|(_, other)| other == word) {
+        output!("{word} currently uses {}", notation.format(&chord));
+    }
+
+    let mut suggestions = suggest::suggest(word, &chords, &layout, &blacklist, count);
+    if let Some(command) = &config.suggest_command {
+        let candidates =
+            suggest::external(command, config.suggest_timeout, word, &chords, &notation)?;
+        suggestions = suggest::merge(suggestions, candidates, &chords, &layout, count);
+    }
+
+    for (suggestion, number) in suggestions.iter().zip(1..) {
+        let near: Vec<_> = chords
+            .iter()
+            .filter(|(chord, _)| chord.distance(&suggestion.chord) <= config.near_miss_distance)
+            .map(|(chord, word)| format!("{} ({word})", notation.format(&chord)))
+            .collect();
+        let status = if near.is_empty() {
+            String::from("free")
+        } else {
+            format!("near {}", near.join(", "))
+        };
+
+        output!(
+            "{number}. {chord}  difficulty {score}  {status}",
+            chord = notation.format(&suggestion.chord),
+            score = suggestion.score,
+        );
+    }
+
+    Ok(())
+}
+
+fn run_filter(chorded: Option<bool>, max_rank: Option<usize>) -> Result<()> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let words = Words::read_from_file_and_chords(WORDS_PATH, &chords)?;
+
+    let accepts = |word: &str| {
+        let entry = words
+            .index_of(word)
+            .and_then(|index| words.get_index(index));
+        let has_chord = entry.is_some_and(|(_, entry)| !entry.chord.is_empty());
+        let rank = entry.and_then(|(_, entry)| entry.rank);
+
+        chorded.is_none_or(|chorded| chorded == has_chord)
+            && max_rank.is_none_or(|max_rank| rank.is_some_and(|rank| rank.get() <= max_rank))
+    };
+
+    let mut output = BufWriter::new(stdout().lock());
+    let filtered = stdin().lock().lines().try_for_each(|line| {
+        let line = line?;
+        let word = line.trim();
+        if word.is_empty() || !accepts(word) {
+            return Ok(());
+        }
+        writeln!(output, "{word}")
+    });
+
+    match filtered.and_then(|()| output.flush()) {
+        // the reading end of the pipe is allowed to stop early, e.g. with head
+        Err(error) if error.kind() == ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn run_coverage(corpus: &str, top: usize, json: bool) -> Result<()> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let stop_words = WordSet::read_from_file_if_exists(STOP_WORDS_PATH)?;
+
+    let coverage = coverage::coverage(&read_to_string(corpus)?, &chords, &stop_words, top);
+    if json {
+        output!("{}", coverage.to_json());
+    } else {
+        output!("{coverage}");
+    }
+
+    Ok(())
+}
+
+fn run_simulate(
+    config: &Config,
+    corpus: &str,
+    chord_time: Option<f64>,
+    char_time: Option<f64>,
+    json: bool,
+) -> Result<()> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let model = Model {
+        chord_time: chord_time.unwrap_or(config.model.chord_time),
+        char_time: char_time.unwrap_or(config.model.char_time),
+    };
+
+    let simulation = simulate::simulate(&read_to_string(corpus)?, &chords, model);
+    if json {
+        output!("{}", simulation.to_json());
+    } else {
+        output!("{simulation}");
+    }
+
+    Ok(())
+}
+
+fn run_substitute(config: &Config, from: char, to: char, apply: bool) -> Result<()> {
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let (substituted, collisions) = transform::substitute(&chords, from, to);
+
+    write_transformed(config, &chords, &substituted, &collisions, apply)
+}
+
+fn run_migrate_layout(config: &Config, source: &MappingSource, apply: bool) -> Result<()> {
+    let mapping = match source {
+        MappingSource::Layouts { from, to } => Mapping::between(from, to)?,
+        MappingSource::File(path) => Mapping::parse(&read_to_string(path)?)?,
+    };
+
+    let chords = Chords::read_from_file(CHORDS_PATH)?;
+    let (migrated, collisions) = transform::migrate(&chords, &mapping);
+
+    write_transformed(config, &chords, &migrated, &collisions, apply)
+}
+
+fn write_transformed(
+    config: &Config,
+    chords: &Chords,
+    transformed: &Chords,
+    collisions: &[Collision],
+    apply: bool,
+) -> Result<()> {
+    for collision in collisions {
+        eprintln!("collision: {collision}");
+    }
+    if !collisions.is_empty() {
+        return Err(Conflicts {
+            count: collisions.len(),
+            kind: "collisions",
+        }
+        .into());
+    }
+
+    let notation = Notation::from_config(config);
+    let changes = journal::diff_chords(chords, transformed);
+    if !apply {
+        for change in &changes {
+            output!("{}", change.format(&notation));
+        }
+        output!(
+            "{} chords would change, run again with --apply to write them",
+            changes.len()
+        );
+        return Ok(());
+    }
+
+    journal::append_to_file(JOURNAL_PATH, &changes, &notation)?;
+    transformed.write_to_file(CHORDS_PATH, &notation)?;
+    output!("Changed {} chords", changes.len());
+
+    Ok(())
+}
+
+fn run_suppress(config: &Config, word: &str, warning: &str, clear: bool) -> Result<()> {
+    let mut chords = Chords::read_from_file(CHORDS_PATH)?;
+    if !chords.iter().any(|(_, assigned)| assigned == word) {
+        return Err(cli::invalid_input(format!("{word} has no chord")));
+    }
+
+    let mut metadata = chords.metadata(word);
+    *metadata.suppressed.flag(warning).ok_or_else(|| {
+        cli::invalid_input(format!(
+            "Unknown warning {warning}, expected duplicate or near-miss"
+        ))
+    })? = !clear;
+    chords.set_metadata(word, metadata);
+    chords.write_to_file(CHORDS_PATH, &Notation::from_config(config))?;
+
+    let action = if clear { "Cleared" } else { "Suppressed" };
+    output!("{action} {warning} warnings for {word}");
+
+    Ok(())
+}
+
+fn run_orphans(config: &Config, resolution: Option<&Resolution>) -> Result<()> {
+    let mut chords = Chords::read_from_file(CHORDS_PATH)?;
+    let mut words = Words::read_from_file_and_chords(WORDS_PATH, &chords)?;
+    let notation = Notation::from_config(config);
+
+    let orphans: Vec<_> = chords
+        .iter()
+        .filter(|(_, word)| words.rank(word).is_none())
+        .collect();
+    for (chord, word) in &orphans {
+        output!("{}\t{word}", notation.format(chord));
+    }
+
+    match resolution {
+        None => output!("{} chords without a ranked word", orphans.len()),
+        Some(Resolution::Delete) => {
+            let original = chords.clone();
+            for (chord, _) in &orphans {
+                chords.remove(chord);
+            }
+
+            let changes = journal::diff_chords(&original, &chords);
+            journal::append_to_file(JOURNAL_PATH, &changes, &notation)?;
+            chords.write_to_file(CHORDS_PATH, &notation)?;
+            output!("Deleted {} chords", orphans.len());
+        }
+        Some(Resolution::Restore) => {
+            let original: BTreeSet<_> = words
+                .iter()
+                .take(words.ranked())
+                .map(|(word, _)| word.clone())
+                .collect();
+            for (_, word) in &orphans {
+                words.insert(word.clone(), None);
+            }
+
+            let changes = journal::diff_words(&original, &words);
+            journal::append_to_file(JOURNAL_PATH, &changes, &notation)?;
+            words.write_to_file(WORDS_PATH)?;
+            output!("Added {} words to the end of the word list", changes.len());
+        }
+    }
+
+    Ok(())
+}
+
+fn run_merge(
+    config: &Config,
+    base: &str,
+    ours: &str,
+    theirs: &str,
+    output: Option<&str>,
+) -> Result<()> {
+    let base = Chords::read_from_file(base)?;
+    let ours = Chords::read_from_file(ours)?;
+    let theirs = Chords::read_from_file(theirs)?;
+
+    let (merged, conflicts) = merge::merge(&base, &ours, &theirs);
+
+    if !conflicts.is_empty() {
+        for conflict in &conflicts {
+            eprintln!("conflict: {conflict}");
+        }
+
+        return Err(Conflicts {
+            count: conflicts.len(),
+            kind: "conflicts",
+        }
+        .into());
+    }
+
+    let output = output.unwrap_or(CHORDS_PATH);
+    merged.write_to_file(output, &Notation::from_config(config))?;
+    output!("Merged into {output}");
+
     Ok(())
 }
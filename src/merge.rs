@@ -0,0 +1,136 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+use crate::chords::{Chord, Chords};
+
+pub enum Conflict {
+    Chord {
+        chord: Chord,
+        base: Option<String>,
+        ours: Option<String>,
+        theirs: Option<String>,
+    },
+    Word {
+        word: String,
+        chords: Vec<Chord>,
+    },
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let side = |word: &Option<String>| word.clone().unwrap_or_else(|| "(none)".to_owned());
+
+        match self {
+            Conflict::Chord {
+                chord,
+                base,
+                ours,
+                theirs,
+            } => write!(
+                f,
+                "{}: base {}, ours {}, theirs {}",
+                chord.as_str(),
+                side(base),
+                side(ours),
+                side(theirs)
+            ),
+            Conflict::Word { word, chords } => {
+                let chords: Vec<_> = chords.iter().map(Chord::as_str).collect();
+                write!(f, "{word}: assigned different chords {}", chords.join(", "))
+            }
+        }
+    }
+}
+
+pub fn merge(base: &Chords, ours: &Chords, theirs: &Chords) -> (Chords, Vec<Conflict>) {
+    let keys: BTreeSet<_> = [base, ours, theirs]
+        .into_iter()
+        .flat_map(|chords| chords.iter().map(|(chord, _)| chord))
+        .collect();
+
+    let mut merged = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for chord in keys {
+        let base = base.get(&chord).cloned();
+        let ours = ours.get(&chord).cloned();
+        let theirs = theirs.get(&chord).cloned();
+
+        let word = if ours == theirs || theirs == base {
+            ours
+        } else if ours == base {
+            theirs
+        } else {
+            conflicts.push(Conflict::Chord {
+                chord,
+                base,
+                ours,
+                theirs,
+            });
+            continue;
+        };
+
+        if let Some(word) = word {
+            merged.push((chord, word));
+        }
+    }
+
+    let mut merged: Chords = merged.into_iter().collect();
+    for chords in [base, ours, theirs] {
+        merged.inherit_metadata(chords);
+    }
+
+    let chords_per_word = |chords: &Chords| {
+        let mut counts: BTreeMap<String, Vec<Chord>> = BTreeMap::new();
+        for (chord, word) in chords.iter() {
+            counts.entry(word).or_default().push(chord);
+        }
+        counts
+    };
+    let (ours, theirs) = (chords_per_word(ours), chords_per_word(theirs));
+    let count =
+        |counts: &BTreeMap<String, Vec<Chord>>, word: &str| counts.get(word).map_or(0, Vec::len);
+
+    for (word, chords) in chords_per_word(&merged) {
+        if chords.len() > count(&ours, &word).max(count(&theirs, &word)) {
+            conflicts.push(Conflict::Word { word, chords });
+        }
+    }
+
+    (merged, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_entries() {
+        let base = Chords::from_pairs(&[("H+T", "the"), ("A+N", "and"), ("O+F", "of")]);
+        let ours = Chords::from_pairs(&[
+            ("E+H+T", "the"),
+            ("A+N", "and"),
+            ("O+F", "off"),
+            ("W+T", "two"),
+        ]);
+        let theirs = Chords::from_pairs(&[
+            ("H+T", "the"),
+            ("O+F", "of"),
+            ("O+F+R", "for"),
+            ("T+W", "tow"),
+        ]);
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+
+        let merged: Vec<_> = merged
+            .iter()
+            .map(|(chord, word)| format!("{}: {word}", chord.as_str()))
+            .collect();
+        assert_eq!(merged, ["E+H+T: the", "F+O: off", "F+O+R: for"]);
+
+        let conflicts: Vec<_> = conflicts.iter().map(ToString::to_string).collect();
+        assert_eq!(conflicts, ["T+W: base (none), ours two, theirs tow"]);
+    }
+}
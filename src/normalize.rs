@@ -0,0 +1,57 @@
+use std::{borrow::Cow, str::FromStr};
+
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Normalization {
+    #[default]
+    None,
+    LatinComposed,
+    LatinDecomposed,
+}
+
+impl Normalization {
+    pub fn apply(self, text: &str) -> Cow<'_, str> {
+        if self == Normalization::None || text.is_ascii() {
+            return Cow::Borrowed(text);
+        }
+
+        Cow::Owned(match self {
+            Normalization::LatinDecomposed => text.nfd().collect(),
+            _ => text.nfc().collect(),
+        })
+    }
+}
+
+impl FromStr for Normalization {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "none" => Ok(Normalization::None),
+            "latin-composed" => Ok(Normalization::LatinComposed),
+            "latin-decomposed" => Ok(Normalization::LatinDecomposed),
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_forms() {
+        let composed = "Grüße, Ǘ";
+        let decomposed = "Gru\u{308}ße, U\u{308}\u{301}";
+
+        assert_eq!(Normalization::LatinDecomposed.apply(composed), decomposed);
+        assert_eq!(Normalization::LatinComposed.apply(decomposed), composed);
+        assert_eq!(Normalization::LatinComposed.apply(composed), composed);
+        assert_eq!(Normalization::None.apply(decomposed), decomposed);
+        assert_eq!(
+            Normalization::LatinComposed.apply("Vie\u{323}\u{302}t"),
+            "Việt"
+        );
+    }
+}
@@ -0,0 +1,436 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{Error, ErrorKind, Result},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{
+    chords::{Chord, Notation},
+    cli::invalid_input,
+    clock::DAY_MS,
+    export::escape_csv,
+    storage::{append, read_to_string},
+};
+
+pub enum Table {
+    Entries,
+    Sessions,
+}
+
+impl FromStr for Table {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self> {
+        match string {
+            "entries" => Ok(Table::Entries),
+            "sessions" => Ok(Table::Sessions),
+            table => Err(invalid_input(format!(
+                "Unknown table {table}, expected entries or sessions"
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Grade {
+    pub const ALL: [Grade; 4] = [Grade::Again, Grade::Hard, Grade::Good, Grade::Easy];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Grade::Again => "again",
+            Grade::Hard => "hard",
+            Grade::Good => "good",
+            Grade::Easy => "easy",
+        }
+    }
+}
+
+pub struct Record {
+    pub timestamp: u64,
+    pub session: u64,
+    pub grade: Grade,
+    pub time: u64,
+    pub chord: Chord,
+    pub word: String,
+}
+
+impl Record {
+    pub fn correct(&self) -> bool {
+        self.grade != Grade::Again
+    }
+
+    fn format(&self) -> String {
+        format!(
+            "{} {} {} {} {} {}",
+            self.timestamp,
+            self.session,
+            match self.grade {
+                Grade::Again => "wrong",
+                Grade::Good => "correct",
+                grade => grade.name(),
+            },
+            self.time,
+            self.chord.as_str(),
+            self.word,
+        )
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        // the word comes last and may contain spaces
+        let mut fields = line.splitn(6, ' ');
+        let timestamp = fields.next()?.parse().ok()?;
+        let session = fields.next()?.parse().ok()?;
+        let grade = match fields.next()? {
+            "wrong" => Grade::Again,
+            "hard" => Grade::Hard,
+            "correct" => Grade::Good,
+            "easy" => Grade::Easy,
+            _ => return None,
+        };
+        let time = fields.next()?.parse().ok()?;
+        let chord = fields.next()?.parse().ok()?;
+        let word = fields.next()?.to_owned();
+
+        Some(Self {
+            timestamp,
+            session,
+            grade,
+            time,
+            chord,
+            word,
+        })
+    }
+}
+
+pub struct Weakness {
+    pub chord: Chord,
+    pub word: String,
+    pub attempts: usize,
+    pub accuracy: f64,
+}
+
+#[derive(Default)]
+pub struct History(Vec<Record>);
+
+impl History {
+    pub fn read_from_file_if_exists(path: impl AsRef<Path>) -> Result<Self> {
+        let lines = match read_to_string(path) {
+            Ok(lines) => lines,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+
+        lines
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                Record::parse(line).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Invalid practice line {}: {line}", number + 1),
+                    )
+                })
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+
+    pub fn append_to_file(path: impl AsRef<Path>, records: &[Record]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let lines: String = records
+            .iter()
+            .map(|record| format!("{}\n", record.format()))
+            .collect();
+
+        append(path, lines)
+    }
+
+    pub fn push(&mut self, record: Record) {
+        self.0.push(record);
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn streak(&self, now: u64) -> usize {
+        let days: BTreeSet<_> = self
+            .0
+            .iter()
+            .map(|record| record.timestamp / DAY_MS)
+            .collect();
+        let today = now / DAY_MS;
+        let Some(mut day) = [today, today.saturating_sub(1)]
+            .into_iter()
+            .find(|day| days.contains(day))
+        else {
+            return 0;
+        };
+
+        let mut streak = 0;
+        while days.contains(&day) {
+            streak += 1;
+            let Some(previous) = day.checked_sub(1) else {
+                break;
+            };
+            day = previous;
+        }
+
+        streak
+    }
+
+    /// Forgets exponentially, with a stability growing with every successful review.
+    pub fn retention(&self, word: &str, now: u64) -> Option<f64> {
+        let mut last = None;
+        let mut stability: f64 = 0.5;
+        for record in self.0.iter().filter(|record| record.word == word) {
+            stability = match record.grade {
+                Grade::Again => 0.5,
+                Grade::Hard => (stability * 1.2).max(1.0),
+                Grade::Good => (stability * 2.0).max(1.0),
+                Grade::Easy => (stability * 4.0).max(2.0),
+            };
+            last = Some(record.timestamp);
+        }
+        let elapsed = now.saturating_sub(last?) as f64 / DAY_MS as f64;
+
+        Some((-elapsed / stability).exp())
+    }
+
+    pub fn due<'a>(
+        &self,
+        entries: impl Iterator<Item = (&'a String, &'a Chord)>,
+        now: u64,
+        count: usize,
+    ) -> Vec<(String, Chord)> {
+        let mut entries: Vec<_> = entries
+            .map(|(word, chord)| {
+                let retention = self.retention(word, now).unwrap_or(0.5);
+                (retention, word, chord)
+            })
+            .collect();
+        entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        entries
+            .into_iter()
+            .take(count)
+            .map(|(_, word, chord)| (word.clone(), chord.clone()))
+            .collect()
+    }
+
+    pub fn average_retention(&self, now: u64) -> Option<f64> {
+        let words: BTreeSet<_> = self.0.iter().map(|record| record.word.as_str()).collect();
+        let retention: Vec<_> = words
+            .into_iter()
+            .filter_map(|word| self.retention(word, now))
+            .collect();
+
+        (!retention.is_empty()).then(|| retention.iter().sum::<f64>() / retention.len() as f64)
+    }
+
+    pub fn daily_accuracy(&self) -> Vec<(u64, f64)> {
+        let mut days: BTreeMap<u64, (usize, usize)> = BTreeMap::new();
+        for record in &self.0 {
+            let (answers, correct) = days.entry(record.timestamp / DAY_MS).or_default();
+            *answers += 1;
+            *correct += usize::from(record.correct());
+        }
+
+        days.into_iter()
+            .map(|(day, (answers, correct))| (day, correct as f64 / answers as f64))
+            .collect()
+    }
+
+    pub fn learned(&self) -> Vec<(u64, usize)> {
+        let mut learned = BTreeSet::new();
+        let mut days: Vec<(u64, usize)> = Vec::new();
+        for record in &self.0 {
+            if record.correct() {
+                learned.insert(record.word.as_str());
+            }
+
+            let day = record.timestamp / DAY_MS;
+            match days.last_mut() {
+                Some((last, count)) if *last == day => *count = learned.len(),
+                _ => days.push((day, learned.len())),
+            }
+        }
+
+        days
+    }
+
+    pub fn to_csv(
+        &self,
+        table: &Table,
+        uses: &BTreeMap<Chord, usize>,
+        notation: &Notation,
+        now: u64,
+    ) -> String {
+        match table {
+            Table::Entries => self.entries_csv(uses, notation, now),
+            Table::Sessions => self.sessions_csv(),
+        }
+    }
+
+    fn entries_csv(&self, uses: &BTreeMap<Chord, usize>, notation: &Notation, now: u64) -> String {
+        let mut entries: BTreeMap<(&str, &Chord), Vec<&Record>> = BTreeMap::new();
+        for record in &self.0 {
+            entries
+                .entry((record.word.as_str(), &record.chord))
+                .or_default()
+                .push(record);
+        }
+
+        let mut csv =
+            String::from("word,chord,answers,correct,mean_time_ms,last_practiced,retention,uses\n");
+        for ((word, chord), records) in entries {
+            let correct = records.iter().filter(|record| record.correct()).count();
+            let time: u64 = records.iter().map(|record| record.time).sum();
+            let last = records.last().map_or(0, |record| record.timestamp);
+
+            csv.push_str(&format!(
+                "{},{},{},{correct},{},{last},{:.3},{}\n",
+                escape_csv(word),
+                escape_csv(&notation.format(chord)),
+                records.len(),
+                time / records.len() as u64,
+                self.retention(word, now).unwrap_or_default(),
+                uses.get(chord).copied().unwrap_or_default(),
+            ));
+        }
+
+        csv
+    }
+
+    fn sessions_csv(&self) -> String {
+        let mut sessions: BTreeMap<u64, (usize, usize, u64, u64)> = BTreeMap::new();
+        for record in &self.0 {
+            let (answers, correct, time, end) = sessions.entry(record.session).or_default();
+            *answers += 1;
+            *correct += usize::from(record.correct());
+            *time += record.time;
+            *end = (*end).max(record.timestamp);
+        }
+
+        let mut csv = String::from("session,end,answers,correct,accuracy,mean_time_ms\n");
+        for (session, (answers, correct, time, end)) in sessions {
+            csv.push_str(&format!(
+                "{session},{end},{answers},{correct},{:.3},{}\n",
+                correct as f64 / answers as f64,
+                time / answers as u64,
+            ));
+        }
+
+        csv
+    }
+
+    pub fn weakest(&self, count: usize) -> Vec<Weakness> {
+        let mut results: BTreeMap<(&str, &Chord), (usize, usize)> = BTreeMap::new();
+        for record in &self.0 {
+            let (attempts, correct) = results
+                .entry((record.word.as_str(), &record.chord))
+                .or_default();
+            *attempts += 1;
+            *correct += usize::from(record.correct());
+        }
+
+        let mut weakest: Vec<_> = results
+            .into_iter()
+            .filter(|(_, (attempts, correct))| correct < attempts)
+            .map(|((word, chord), (attempts, correct))| Weakness {
+                chord: chord.clone(),
+                word: word.to_owned(),
+                attempts,
+                accuracy: correct as f64 / attempts as f64,
+            })
+            .collect();
+        weakest.sort_by(|a, b| {
+            a.accuracy
+                .total_cmp(&b.accuracy)
+                .then(b.attempts.cmp(&a.attempts))
+        });
+        weakest.truncate(count);
+
+        weakest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(day: u64, correct: bool, word: &str) -> Record {
+        Record {
+            timestamp: day * DAY_MS,
+            session: day,
+            grade: if correct { Grade::Good } else { Grade::Again },
+            time: 500,
+            chord: "H+T".parse().unwrap(),
+            word: word.to_owned(),
+        }
+    }
+
+    #[test]
+    fn summarize_history() {
+        let history = History(vec![
+            record(1, false, "the"),
+            record(3, true, "the"),
+            record(4, true, "the"),
+            record(4, true, "that"),
+            record(5, false, "that"),
+        ]);
+
+        assert_eq!(history.streak(5 * DAY_MS), 3);
+        assert_eq!(history.streak(6 * DAY_MS), 3);
+        assert_eq!(history.streak(7 * DAY_MS), 0);
+        assert_eq!(history.retention("the", 4 * DAY_MS), Some(1.0));
+        assert!(history.retention("the", 6 * DAY_MS).unwrap() < 0.5);
+        assert_eq!(history.retention("then", 6 * DAY_MS), None);
+
+        let weakest = history.weakest(5);
+        assert_eq!(weakest[0].word, "that");
+        assert_eq!(weakest[1].word, "the");
+        assert!((weakest[1].accuracy - 2.0 / 3.0).abs() < 1e-9);
+
+        assert_eq!(history.learned(), [(1, 0), (3, 1), (4, 2), (5, 2)]);
+        assert_eq!(history.daily_accuracy()[2], (4, 1.0));
+
+        let notation = Notation::default();
+        let csv = history.to_csv(&Table::Sessions, &BTreeMap::new(), &notation, 0);
+        assert_eq!(csv.lines().count(), 5);
+        let csv = history.to_csv(&Table::Entries, &BTreeMap::new(), &notation, 0);
+        assert!(csv.contains("\n\"the\",\"H+T\",3,2,500,"));
+
+        let line = history.0[0].format();
+        assert_eq!(line, format!("{} 1 wrong 500 H+T the", DAY_MS));
+        assert!(Record::parse(&line).is_some());
+    }
+
+    #[test]
+    fn round_trip_phrase() {
+        let line = record(2, true, "as well").format();
+
+        let parsed = Record::parse(&line).unwrap();
+
+        assert_eq!(parsed.word, "as well");
+        assert_eq!(parsed.chord.as_str(), "H+T");
+        assert_eq!(parsed.session, 2);
+        assert!(Record::parse("1 1 correct 500 H+T").is_none());
+    }
+}
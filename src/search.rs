@@ -0,0 +1,547 @@
+use std::{
+    cmp::Reverse,
+    fmt::{Display, Formatter, Result as FmtResult},
+    num::NonZeroUsize,
+    ops::RangeInclusive,
+    result::Result as StdResult,
+    str::FromStr,
+};
+
+use crate::{
+    clock::{self, DAY_MS},
+    words::{Cutoff, Entry, Words},
+};
+
+const CANCEL_CHECK_INTERVAL: usize = 1024;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Case {
+    #[default]
+    Smart,
+    Sensitive,
+    Insensitive,
+}
+
+impl Case {
+    pub fn next(self) -> Self {
+        match self {
+            Case::Smart => Case::Sensitive,
+            Case::Sensitive => Case::Insensitive,
+            Case::Insensitive => Case::Smart,
+        }
+    }
+}
+
+impl Display for Case {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Case::Smart => write!(f, "smart case"),
+            Case::Sensitive => write!(f, "case sensitive"),
+            Case::Insensitive => write!(f, "case insensitive"),
+        }
+    }
+}
+
+impl FromStr for Case {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "smart case" => Ok(Case::Smart),
+            "case sensitive" => Ok(Case::Sensitive),
+            "case insensitive" => Ok(Case::Insensitive),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Matching {
+    #[default]
+    Substring,
+    Prefix,
+    Fuzzy,
+}
+
+impl Matching {
+    pub fn next(self) -> Self {
+        match self {
+            Matching::Substring => Matching::Prefix,
+            Matching::Prefix => Matching::Fuzzy,
+            Matching::Fuzzy => Matching::Substring,
+        }
+    }
+}
+
+impl Display for Matching {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Matching::Substring => write!(f, "substring"),
+            Matching::Prefix => write!(f, "prefix"),
+            Matching::Fuzzy => write!(f, "fuzzy"),
+        }
+    }
+}
+
+impl FromStr for Matching {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "substring" => Ok(Matching::Substring),
+            "prefix" => Ok(Matching::Prefix),
+            "fuzzy" => Ok(Matching::Fuzzy),
+            _ => Err(()),
+        }
+    }
+}
+
+const CHAR_SCORE: i32 = 10;
+const CONSECUTIVE_BONUS: i32 = 15;
+const START_BONUS: i32 = 20;
+const GAP_PENALTY: i32 = 1;
+const FREQUENCY_WEIGHT: f64 = 8.0;
+
+#[derive(Default)]
+pub struct Match {
+    pub indices: Vec<usize>,
+    pub score: i32,
+}
+
+impl Match {
+    fn new(positions: impl Iterator<Item = (usize, usize)>) -> Self {
+        let mut score = 0;
+        let mut next = 0;
+
+        let indices = positions
+            .map(|(position, index)| {
+                score += CHAR_SCORE;
+                if position == 0 {
+                    score += START_BONUS;
+                } else if position == next {
+                    score += CONSECUTIVE_BONUS;
+                }
+                score -= GAP_PENALTY * i32::try_from(position - next).unwrap_or(i32::MAX);
+                next = position + 1;
+
+                index
+            })
+            .collect();
+
+        Self { indices, score }
+    }
+
+    pub fn relevance(&self, rank: usize) -> f64 {
+        f64::from(self.score) - FREQUENCY_WEIGHT * (rank as f64).ln()
+    }
+}
+
+pub struct Query {
+    text: String,
+    chars: Vec<char>,
+    case_sensitive: bool,
+    matching: Matching,
+    ranks: Option<RangeInclusive<usize>>,
+    added: Option<RangeInclusive<u64>>,
+    cutoff: Option<Cutoff>,
+}
+
+impl Query {
+    pub fn new(input: &str, case: Case, matching: Matching) -> Self {
+        let mut text = Vec::new();
+        let mut ranks: Option<RangeInclusive<usize>> = None;
+        let mut added: Option<RangeInclusive<u64>> = None;
+
+        for token in input.split(' ') {
+            if let Some(range) = parse_added_filter(token) {
+                added = range.or(added);
+                continue;
+            }
+            match parse_rank_filter(token) {
+                Some(Some(range)) => {
+                    ranks = Some(ranks.map_or(range.clone(), |ranks| {
+                        *ranks.start().max(range.start())..=*ranks.end().min(range.end())
+                    }));
+                }
+                Some(None) => {}
+                None => text.push(token),
+            }
+        }
+        let text = text.join(" ").trim().to_owned();
+
+        let case_sensitive = match case {
+            Case::Smart => text.chars().any(char::is_uppercase),
+            Case::Sensitive => true,
+            Case::Insensitive => false,
+        };
+
+        Self {
+            chars: text.chars().collect(),
+            text,
+            case_sensitive,
+            matching,
+            ranks,
+            added,
+            cutoff: None,
+        }
+    }
+
+    pub fn with_cutoff(mut self, cutoff: Option<Cutoff>) -> Self {
+        self.cutoff = cutoff;
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn matching(&self) -> Matching {
+        self.matching
+    }
+
+    pub fn accepts_rank(&self, rank: Option<NonZeroUsize>) -> bool {
+        match (&self.ranks, rank) {
+            (None, _) => true,
+            (Some(ranks), Some(rank)) => ranks.contains(&rank.get()),
+            (Some(_), None) => false,
+        }
+    }
+
+    pub fn accepts_added(&self, created: Option<u64>, now: u64) -> bool {
+        match (&self.added, created) {
+            (None, _) => true,
+            (Some(ages), Some(created)) => ages.contains(&now.saturating_sub(created)),
+            (Some(_), None) => false,
+        }
+    }
+
+    pub fn accepts_frequency(&self, entry: &Entry) -> bool {
+        let filtered = !self.text.is_empty() || self.ranks.is_some() || self.added.is_some();
+
+        filtered || self.cutoff.is_none_or(|cutoff| !cutoff.excludes(entry))
+    }
+
+    pub fn matches(&self, word: &str) -> Option<Match> {
+        if self.chars.is_empty() {
+            return Some(Match::default());
+        }
+
+        match self.matching {
+            Matching::Substring => self.substring(word),
+            Matching::Prefix => self.prefix_at(word, 0, 0),
+            Matching::Fuzzy => self.substring(word).or_else(|| self.subsequence(word)),
+        }
+    }
+
+    fn prefix_at(&self, word: &str, position: usize, start: usize) -> Option<Match> {
+        let mut chars = word[start..].chars();
+        let matched = self
+            .chars
+            .iter()
+            .all(|query| chars.next().is_some_and(|char| self.eq(char, *query)));
+
+        matched.then(|| {
+            let positions = word[start..]
+                .char_indices()
+                .take(self.chars.len())
+                .enumerate()
+                .map(|(offset, (index, _))| (position + offset, start + index));
+
+            Match::new(positions)
+        })
+    }
+
+    fn substring(&self, word: &str) -> Option<Match> {
+        word.char_indices()
+            .enumerate()
+            .find_map(|(position, (start, _))| self.prefix_at(word, position, start))
+    }
+
+    fn subsequence(&self, word: &str) -> Option<Match> {
+        let mut remaining = word.chars();
+        if !self
+            .chars
+            .iter()
+            .all(|query| remaining.any(|char| self.eq(char, *query)))
+        {
+            return None;
+        }
+
+        let mut remaining = word.char_indices().enumerate();
+        let positions = self.chars.iter().filter_map(|query| {
+            remaining
+                .find(|(_, (_, char))| self.eq(*char, *query))
+                .map(|(position, (index, _))| (position, index))
+        });
+
+        Some(Match::new(positions))
+    }
+
+    fn eq(&self, char: char, query: char) -> bool {
+        if self.case_sensitive {
+            char == query
+        } else {
+            char.to_lowercase().eq(query.to_lowercase())
+        }
+    }
+}
+
+fn parse_rank_filter(token: &str) -> Option<Option<RangeInclusive<usize>>> {
+    let condition = token.strip_prefix("rank")?;
+    let (operator, number) = ["<=", ">=", "<", ">", "="]
+        .into_iter()
+        .find_map(|operator| Some((operator, condition.strip_prefix(operator)?)))?;
+
+    let Ok(number) = number.parse::<usize>() else {
+        return Some(None);
+    };
+
+    Some(Some(match operator {
+        "<=" => 1..=number,
+        "<" => 1..=number.saturating_sub(1),
+        ">=" => number..=usize::MAX,
+        ">" => number.saturating_add(1)..=usize::MAX,
+        _ => number..=number,
+    }))
+}
+
+fn parse_added_filter(token: &str) -> Option<Option<RangeInclusive<u64>>> {
+    let condition = token.strip_prefix("added")?;
+    let (operator, days) = ["<=", ">=", "<", ">"]
+        .into_iter()
+        .find_map(|operator| Some((operator, condition.strip_prefix(operator)?)))?;
+
+    let Ok(days) = days.strip_suffix('d').unwrap_or(days).parse::<u64>() else {
+        return Some(None);
+    };
+    let age = days.saturating_mul(DAY_MS);
+
+    Some(Some(match operator {
+        "<=" => 0..=age,
+        "<" => 0..=age.saturating_sub(1),
+        ">=" => age..=u64::MAX,
+        _ => age.saturating_add(1)..=u64::MAX,
+    }))
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Sort {
+    #[default]
+    Relevance,
+    Rank,
+    Word,
+    Chord,
+    Added,
+    Difficulty,
+}
+
+impl Sort {
+    pub fn next(self) -> Self {
+        match self {
+            Sort::Relevance => Sort::Rank,
+            Sort::Rank => Sort::Word,
+            Sort::Word => Sort::Chord,
+            Sort::Chord => Sort::Added,
+            Sort::Added => Sort::Difficulty,
+            Sort::Difficulty => Sort::Relevance,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Sort::Relevance => "relevance",
+            Sort::Rank => "rank",
+            Sort::Word => "word",
+            Sort::Chord => "chord",
+            Sort::Added => "added",
+            Sort::Difficulty => "difficulty",
+        }
+    }
+}
+
+impl FromStr for Sort {
+    type Err = ();
+
+    fn from_str(string: &str) -> StdResult<Self, Self::Err> {
+        match string {
+            "relevance" => Ok(Sort::Relevance),
+            "rank" => Ok(Sort::Rank),
+            "word" => Ok(Sort::Word),
+            "chord" => Ok(Sort::Chord),
+            "added" => Ok(Sort::Added),
+            "difficulty" => Ok(Sort::Difficulty),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Group {
+    #[default]
+    Single,
+    Head {
+        forms: usize,
+        rank: Option<usize>,
+    },
+    Member,
+}
+
+pub struct Hit {
+    pub index: usize,
+    pub matches: Vec<usize>,
+    pub score: f64,
+    pub group: Group,
+}
+
+pub fn search(
+    words: &Words,
+    query: &Query,
+    sort: Sort,
+    cancelled: impl Fn() -> bool,
+) -> Option<Vec<Hit>> {
+    let unranked = words.ranked() + 1;
+    let now = clock::now();
+
+    let candidates: Box<dyn Iterator<Item = _>> = match query.matching() {
+        Matching::Prefix if !query.text().is_empty() => Box::new(words.with_prefix(query.text())),
+        Matching::Substring => Box::new(words.with_substring(query.text())),
+        _ => Box::new(words.with_chars(query.text())),
+    };
+
+    let mut hits = Vec::new();
+    for (count, (index, word, entry)) in candidates.enumerate() {
+        if count % CANCEL_CHECK_INTERVAL == 0 && cancelled() {
+            return None;
+        }
+        if !query.accepts_rank(entry.rank)
+            || !query.accepts_added(entry.metadata.created, now)
+            || !query.accepts_frequency(entry)
+        {
+            continue;
+        }
+        let Some(found) = query.matches(word) else {
+            continue;
+        };
+
+        let score = found.relevance(entry.rank.map_or(unranked, NonZeroUsize::get));
+        hits.push(Hit {
+            index,
+            matches: found.indices,
+            score,
+            group: Group::Single,
+        });
+    }
+
+    if cancelled() {
+        return None;
+    }
+
+    let word = |hit: &Hit| words.get_index(hit.index).map(|(word, _)| word);
+    let chord = |hit: &Hit| words.get_index(hit.index).map(|(_, entry)| &entry.chord);
+    match sort {
+        Sort::Relevance => hits.sort_by(|a, b| b.score.total_cmp(&a.score)),
+        Sort::Rank | Sort::Difficulty => {}
+        Sort::Word => hits.sort_by_key(|hit| word(hit)),
+        Sort::Chord => hits.sort_by_key(|hit| chord(hit).map(|chord| (chord.is_empty(), chord))),
+        Sort::Added => hits.sort_by_key(|hit| {
+            Reverse(
+                words
+                    .get_index(hit.index)
+                    .and_then(|(_, entry)| entry.metadata.created),
+            )
+        }),
+    }
+
+    Some(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smart_case_matching() {
+        let indices = |query: &Query, word| query.matches(word).map(|m| m.indices);
+
+        let lowercase = Query::new("the", Case::Smart, Matching::Substring);
+        assert_eq!(indices(&lowercase, "The"), Some(vec![0, 1, 2]));
+        assert_eq!(indices(&lowercase, "other"), Some(vec![1, 2, 3]));
+        assert_eq!(indices(&lowercase, "tea"), None);
+
+        let mixed = Query::new("The", Case::Smart, Matching::Substring);
+        assert_eq!(indices(&mixed, "The"), Some(vec![0, 1, 2]));
+        assert_eq!(indices(&mixed, "the"), None);
+
+        let insensitive = Query::new("ÜBER", Case::Insensitive, Matching::Substring);
+        assert_eq!(indices(&insensitive, "drüber"), Some(vec![2, 4, 5, 6]));
+
+        let empty = Query::new("", Case::Smart, Matching::Substring);
+        assert_eq!(indices(&empty, "word"), Some(vec![]));
+
+        let prefix = Query::new("he", Case::Smart, Matching::Prefix);
+        assert_eq!(indices(&prefix, "Hello"), Some(vec![0, 1]));
+        assert_eq!(indices(&prefix, "the"), None);
+    }
+
+    #[test]
+    fn rank_filter() {
+        let rank = NonZeroUsize::new;
+
+        let query = Query::new("rank>10 th rank<=2000", Case::Smart, Matching::Substring);
+        assert_eq!(query.text(), "th");
+        assert!(query.accepts_rank(rank(11)));
+        assert!(query.accepts_rank(rank(2000)));
+        assert!(!query.accepts_rank(rank(10)));
+        assert!(!query.accepts_rank(None));
+
+        let incomplete = Query::new("rank<", Case::Smart, Matching::Substring);
+        assert_eq!(incomplete.text(), "");
+        assert!(incomplete.accepts_rank(None));
+
+        assert_eq!(
+            Query::new("rank", Case::Smart, Matching::Substring).text(),
+            "rank"
+        );
+    }
+
+    #[test]
+    fn added_filter() {
+        let now = 30 * DAY_MS;
+
+        let query = Query::new("added<7d th", Case::Smart, Matching::Substring);
+        assert_eq!(query.text(), "th");
+        assert!(query.accepts_added(Some(now - DAY_MS), now));
+        assert!(!query.accepts_added(Some(now - 8 * DAY_MS), now));
+        assert!(!query.accepts_added(None, now));
+
+        let older = Query::new("added>=7", Case::Smart, Matching::Substring);
+        assert!(older.accepts_added(Some(0), now));
+        assert!(!older.accepts_added(Some(now), now));
+
+        let week_ago = Some(now - 7 * DAY_MS);
+        let accepts = |filter| {
+            Query::new(filter, Case::Smart, Matching::Substring).accepts_added(week_ago, now)
+        };
+        assert!(!accepts("added<7d"));
+        assert!(accepts("added<=7d"));
+        assert!(!accepts("added>7d"));
+        assert!(accepts("added>=7d"));
+
+        let incomplete = Query::new("added<", Case::Smart, Matching::Substring);
+        assert_eq!(incomplete.text(), "");
+        assert!(incomplete.accepts_added(None, now));
+    }
+
+    #[test]
+    fn fuzzy_ranking() {
+        let query = Query::new("th", Case::Smart, Matching::Fuzzy);
+        let relevance = |word, rank| query.matches(word).unwrap().relevance(rank);
+
+        assert_eq!(query.matches("tooth").unwrap().indices, vec![3, 4]);
+        assert_eq!(query.matches("torch").unwrap().indices, vec![0, 4]);
+        assert!(query.matches("hat").is_none());
+        assert!(relevance("the", 1) > relevance("with", 15));
+        assert!(relevance("that", 12) > relevance("torch", 4000));
+        assert!(relevance("with", 15) > relevance("toothbrush", 20000));
+    }
+}
@@ -0,0 +1,350 @@
+use std::{
+    hash::{BuildHasher, RandomState},
+    io::{BufRead, BufReader, Error, ErrorKind, Read, Result, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    time::Duration,
+};
+
+use crate::{chords::Chord, json::Json, library::Library};
+
+const DEFAULT_LIMIT: usize = 20;
+const DEFAULT_SUGGESTIONS: usize = 5;
+const MAX_BODY: usize = 64 * 1024;
+const MAX_LINE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Response {
+    status: &'static str,
+    body: Json,
+}
+
+impl Response {
+    fn ok(body: Json) -> Self {
+        Self {
+            status: "200 OK",
+            body,
+        }
+    }
+
+    fn error(status: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: Json::Object(vec![("error", message.into().into())]),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Headers {
+    length: usize,
+    host: Option<String>,
+    origin: Option<String>,
+    authorization: Option<String>,
+}
+
+pub fn serve(library: &mut Library, port: u16) -> Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    let token = token();
+    println!("Listening on http://{}", listener.local_addr()?);
+    println!("Send \"Authorization: Bearer {token}\" with PUT and DELETE requests");
+
+    for stream in listener.incoming() {
+        if let Err(error) = stream.and_then(|stream| handle(stream, library, &token)) {
+            eprintln!("request failed: {error}");
+        }
+    }
+
+    Ok(())
+}
+
+// the keys of a fresh RandomState are seeded from the OS
+fn token() -> String {
+    let state = RandomState::new();
+    (0..2u8)
+        .map(|half| format!("{:016x}", state.hash_one(half)))
+        .collect()
+}
+
+fn handle(mut stream: TcpStream, library: &mut Library, token: &str) -> Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+
+    let request_line = read_line(&mut reader)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_owned();
+    let target = parts.next().unwrap_or_default().to_owned();
+
+    let mut headers = Headers::default();
+    for count in 0.. {
+        let header = read_line(&mut reader)?;
+        if header.trim().is_empty() {
+            break;
+        }
+        if count == MAX_HEADERS {
+            return Err(Error::new(ErrorKind::InvalidData, "too many headers"));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => headers.length = value.parse().unwrap_or(0),
+                "host" => headers.host = Some(value.to_owned()),
+                "origin" => headers.origin = Some(value.to_owned()),
+                "authorization" => headers.authorization = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+    }
+
+    let response = if let Some(rejection) = reject(&method, &headers, token) {
+        rejection
+    } else if headers.length > MAX_BODY {
+        Response::error("413 Payload Too Large", "request body too large")
+    } else {
+        let mut body = vec![0; headers.length];
+        reader.read_exact(&mut body)?;
+        route(&method, &target, &String::from_utf8_lossy(&body), library)
+    };
+
+    let body = response.body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        response.status,
+        body.len(),
+    )?;
+    stream.flush()
+}
+
+// guards against DNS rebinding and cross-site requests from browsers
+fn reject(method: &str, headers: &Headers, token: &str) -> Option<Response> {
+    if !headers.host.as_deref().is_some_and(is_local) {
+        return Some(Response::error("403 Forbidden", "host must be localhost"));
+    }
+    let local_origin = |origin: &str| origin.strip_prefix("http://").is_some_and(is_local);
+    if !headers.origin.as_deref().is_none_or(local_origin) {
+        return Some(Response::error("403 Forbidden", "cross-origin request"));
+    }
+
+    let authorized = headers
+        .authorization
+        .as_deref()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| given.trim() == token);
+    if matches!(method, "PUT" | "DELETE") && !authorized {
+        return Some(Response::error(
+            "401 Unauthorized",
+            "missing or invalid token",
+        ));
+    }
+
+    None
+}
+
+fn is_local(authority: &str) -> bool {
+    let host = authority
+        .rsplit_once(':')
+        .filter(|(_, port)| port.parse::<u16>().is_ok())
+        .map_or(authority, |(host, _)| host);
+
+    matches!(host, "127.0.0.1" | "localhost")
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String> {
+    let mut line = String::new();
+    reader.take(MAX_LINE as u64).read_line(&mut line)?;
+
+    if line.len() == MAX_LINE && !line.ends_with('\n') {
+        return Err(Error::new(ErrorKind::InvalidData, "request line too long"));
+    }
+
+    Ok(line)
+}
+
+fn route(method: &str, target: &str, body: &str, library: &mut Library) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments: Vec<_> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(decode)
+        .collect();
+    let segments: Vec<_> = segments.iter().map(String::as_str).collect();
+
+    let parameter = |name| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| decode(&value.replace('+', " ")))
+    };
+
+    match (method, segments.as_slice()) {
+        ("GET", ["search"]) => {
+            let text = parameter("q").unwrap_or_default();
+            let limit = parameter("limit")
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(DEFAULT_LIMIT);
+
+            let results = library
+                .search(&text, limit)
+                .into_iter()
+                .map(|(word, entry)| library.entry_json(word, Some(entry)));
+            Response::ok(Json::Object(vec![("results", Json::array(results))]))
+        }
+        ("GET", ["suggest"]) => {
+            let Some(word) = parameter("word") else {
+                return Response::error("400 Bad Request", "missing word parameter");
+            };
+            let count = parameter("count")
+                .and_then(|count| count.parse().ok())
+                .unwrap_or(DEFAULT_SUGGESTIONS);
+
+            let suggestions = match library.suggest(&word, count) {
+                Ok(suggestions) => suggestions,
+                Err(error) => {
+                    return Response::error("500 Internal Server Error", error.to_string())
+                }
+            };
+            let suggestions = suggestions.into_iter().map(|suggestion| {
+                Json::Object(vec![
+                    ("chord", library.notation().format(&suggestion.chord).into()),
+                    ("score", suggestion.score.into()),
+                ])
+            });
+            Response::ok(Json::Object(vec![(
+                "suggestions",
+                Json::array(suggestions),
+            )]))
+        }
+        ("GET", ["words", word]) => {
+            let entry = library.entry(word);
+            if entry.is_none() && library.chords_of(word).is_empty() {
+                return Response::error("404 Not Found", format!("unknown word {word}"));
+            }
+            Response::ok(library.entry_json(word, entry))
+        }
+        (_, ["chords", chord]) => {
+            let Ok(chord) = library.notation().parse(chord) else {
+                return Response::error("400 Bad Request", format!("invalid chord {chord}"));
+            };
+            chord_route(method, &chord, body.trim(), library)
+        }
+        (_, ["search" | "suggest" | "words", ..] | ["chords", ..]) => {
+            Response::error("405 Method Not Allowed", "method not allowed")
+        }
+        _ => Response::error("404 Not Found", format!("unknown path {path}")),
+    }
+}
+
+fn chord_route(method: &str, chord: &Chord, word: &str, library: &mut Library) -> Response {
+    let entry = |library: &Library, word: &str| {
+        Json::Object(vec![
+            ("chord", library.notation().format(chord).into()),
+            ("word", word.into()),
+        ])
+    };
+
+    match method {
+        "GET" => match library.word(chord) {
+            Some(word) => Response::ok(entry(library, word)),
+            None => Response::error("404 Not Found", "chord not assigned"),
+        },
+        "PUT" if word.is_empty() => Response::error("400 Bad Request", "missing word in body"),
+        "PUT" => match library.assign(chord.clone(), word.to_owned()) {
+            Ok(None) => Response::ok(entry(library, word)),
+            Ok(Some(existing)) => Response::error(
+                "409 Conflict",
+                format!("chord already assigned to {existing}"),
+            ),
+            Err(error) => Response::error("500 Internal Server Error", error.to_string()),
+        },
+        "DELETE" => match library.remove(chord) {
+            Ok(Some(word)) => Response::ok(entry(library, &word)),
+            Ok(None) => Response::error("404 Not Found", "chord not assigned"),
+            Err(error) => Response::error("500 Internal Server Error", error.to_string()),
+        },
+        _ => Response::error("405 Method Not Allowed", "method not allowed"),
+    }
+}
+
+fn decode(string: &str) -> String {
+    let bytes = string.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(decode("H%2BT"), "H+T");
+        assert_eq!(decode("caf%C3%A9"), "café");
+        assert_eq!(decode("100%"), "100%");
+    }
+
+    #[test]
+    fn reject_foreign_and_unauthorized_requests() {
+        let headers = |host: &str, origin: Option<&str>, authorization: Option<&str>| Headers {
+            length: 0,
+            host: Some(host.to_owned()),
+            origin: origin.map(str::to_owned),
+            authorization: authorization.map(str::to_owned),
+        };
+        let status = |method, headers| reject(method, &headers, "secret").map(|r| r.status);
+
+        assert_eq!(status("GET", headers("localhost:7878", None, None)), None);
+        assert_eq!(
+            status(
+                "GET",
+                headers("127.0.0.1", Some("http://localhost:7878"), None)
+            ),
+            None
+        );
+        assert_eq!(status("GET", Headers::default()), Some("403 Forbidden"));
+        assert_eq!(
+            status("GET", headers("evil.example:7878", None, None)),
+            Some("403 Forbidden")
+        );
+        assert_eq!(
+            status(
+                "GET",
+                headers("localhost", Some("https://evil.example"), None)
+            ),
+            Some("403 Forbidden")
+        );
+        assert_eq!(
+            status("PUT", headers("localhost", None, None)),
+            Some("401 Unauthorized")
+        );
+        assert_eq!(
+            status("DELETE", headers("localhost", None, Some("Bearer guess"))),
+            Some("401 Unauthorized")
+        );
+        assert_eq!(
+            status("PUT", headers("localhost", None, Some("Bearer secret"))),
+            None
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::{
+    chords::Chords,
+    coverage::{ratio, shortest_chords, tokens},
+    json::Json,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Model {
+    pub chord_time: f64,
+    pub char_time: f64,
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            chord_time: 300.0,
+            char_time: 200.0,
+        }
+    }
+}
+
+pub struct Simulation {
+    pub tokens: usize,
+    pub chorded: usize,
+    pub typed_keystrokes: usize,
+    pub chorded_keystrokes: usize,
+    pub typed_time: f64,
+    pub chorded_time: f64,
+}
+
+impl Simulation {
+    pub fn time_saved(&self) -> f64 {
+        self.typed_time - self.chorded_time
+    }
+
+    pub fn time_saved_share(&self) -> f64 {
+        if self.typed_time > 0.0 {
+            self.time_saved() / self.typed_time
+        } else {
+            0.0
+        }
+    }
+
+    pub fn typed_wpm(&self) -> f64 {
+        self.wpm(self.typed_time)
+    }
+
+    pub fn chorded_wpm(&self) -> f64 {
+        self.wpm(self.chorded_time)
+    }
+
+    fn wpm(&self, time: f64) -> f64 {
+        if time > 0.0 {
+            self.typed_keystrokes as f64 / 5.0 / (time / 60_000.0)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn keystrokes_saved_share(&self) -> f64 {
+        ratio(
+            self.typed_keystrokes
+                .saturating_sub(self.chorded_keystrokes),
+            self.typed_keystrokes,
+        )
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("tokens", self.tokens.into()),
+            ("chorded", self.chorded.into()),
+            ("typed_keystrokes", self.typed_keystrokes.into()),
+            ("chorded_keystrokes", self.chorded_keystrokes.into()),
+            ("typed_seconds", (self.typed_time / 1000.0).into()),
+            ("chorded_seconds", (self.chorded_time / 1000.0).into()),
+            ("typed_wpm", self.typed_wpm().into()),
+            ("chorded_wpm", self.chorded_wpm().into()),
+        ])
+    }
+}
+
+impl Display for Simulation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(
+            f,
+            "Tokens: {} ({} chorded, {:.1}%)",
+            self.tokens,
+            self.chorded,
+            ratio(self.chorded, self.tokens) * 100.0
+        )?;
+        writeln!(
+            f,
+            "Keystrokes: {} typed, {} with chords ({:.1}% saved)",
+            self.typed_keystrokes,
+            self.chorded_keystrokes,
+            self.keystrokes_saved_share() * 100.0
+        )?;
+        writeln!(
+            f,
+            "Time: {:.1}s typed, {:.1}s with chords ({:.1}s or {:.1}% saved)",
+            self.typed_time / 1000.0,
+            self.chorded_time / 1000.0,
+            self.time_saved() / 1000.0,
+            self.time_saved_share() * 100.0
+        )?;
+        write!(
+            f,
+            "Speed: {:.1} WPM typed, {:.1} WPM with chords ({:+.1})",
+            self.typed_wpm(),
+            self.chorded_wpm(),
+            self.chorded_wpm() - self.typed_wpm()
+        )
+    }
+}
+
+pub fn simulate(text: &str, chords: &Chords, model: Model) -> Simulation {
+    let by_word = shortest_chords(chords);
+
+    let mut simulation = Simulation {
+        tokens: 0,
+        chorded: 0,
+        typed_keystrokes: 0,
+        chorded_keystrokes: 0,
+        typed_time: 0.0,
+        chorded_time: 0.0,
+    };
+
+    for token in tokens(text) {
+        let typed = token.chars().count() + 1;
+        simulation.tokens += 1;
+        simulation.typed_keystrokes += typed;
+        simulation.typed_time += typed as f64 * model.char_time;
+
+        match by_word
+            .get(token)
+            .or_else(|| by_word.get(&token.to_lowercase()))
+        {
+            Some(chord) => {
+                simulation.chorded += 1;
+                simulation.chorded_keystrokes += chord.keys().count();
+                simulation.chorded_time += model.chord_time;
+            }
+            None => {
+                simulation.chorded_keystrokes += typed;
+                simulation.chorded_time += typed as f64 * model.char_time;
+            }
+        }
+    }
+
+    simulation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_text() {
+        let chords = Chords::from_pairs(&[("H+T", "the")]);
+        let model = Model {
+            chord_time: 300.0,
+            char_time: 100.0,
+        };
+
+        let simulation = simulate("The cat, the dog", &chords, model);
+
+        assert_eq!(simulation.tokens, 4);
+        assert_eq!(simulation.chorded, 2);
+        assert_eq!(simulation.typed_keystrokes, 16);
+        assert_eq!(simulation.chorded_keystrokes, 12);
+        assert_eq!(simulation.time_saved(), 200.0);
+        assert_eq!(simulation.typed_wpm(), 120.0);
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::{
+    chords::{Chord, Chords},
+    layout::{Hand, Layout},
+    suggest::{suggest, Suggestion},
+    word_set::WordSet,
+};
+
+const CANDIDATE_SUGGESTIONS: usize = 50;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HandLoad {
+    pub left: usize,
+    pub right: usize,
+}
+
+impl HandLoad {
+    pub fn get(&self, hand: Hand) -> usize {
+        match hand {
+            Hand::Left => self.left,
+            Hand::Right => self.right,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.left + self.right
+    }
+
+    pub fn share(&self, hand: Hand) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.get(hand) as f64 / total as f64,
+        }
+    }
+
+    pub fn heavier(&self) -> Option<(Hand, f64)> {
+        let imbalance = self.share(Hand::Left) - self.share(Hand::Right);
+
+        if imbalance > 0.0 {
+            Some((Hand::Left, imbalance))
+        } else if imbalance < 0.0 {
+            Some((Hand::Right, -imbalance))
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for HandLoad {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "left {left} ({left_share:.1}%), right {right} ({right_share:.1}%)",
+            left = self.left,
+            right = self.right,
+            left_share = self.share(Hand::Left) * 100.0,
+            right_share = self.share(Hand::Right) * 100.0,
+        )
+    }
+}
+
+pub fn hand_load(chords: &Chords, layout: &Layout, weight: impl Fn(&Chord) -> usize) -> HandLoad {
+    let mut load = HandLoad::default();
+
+    for (chord, _) in chords.iter() {
+        let weight = weight(&chord);
+
+        for key in chord.keys().filter_map(|key| layout.key(key)) {
+            match key.hand {
+                Hand::Left => load.left += weight,
+                Hand::Right => load.right += weight,
+            }
+        }
+    }
+
+    load
+}
+
+pub struct Rebalance {
+    pub chord: Chord,
+    pub word: String,
+    pub alternatives: Vec<Suggestion>,
+}
+
+pub fn rebalance(
+    chords: &Chords,
+    layout: &Layout,
+    blacklist: &WordSet,
+    weight: impl Fn(&Chord) -> usize,
+    count: usize,
+    alternatives: usize,
+) -> Vec<Rebalance> {
+    let Some((heavy, _)) = hand_load(chords, layout, &weight).heavier() else {
+        return Vec::new();
+    };
+
+    let mut offenders: Vec<_> = chords
+        .iter()
+        .filter_map(|(chord, word)| {
+            let heavy_keys = keys_on(&chord, layout, heavy);
+            let light_keys = chord.keys().count() - heavy_keys;
+            let excess = heavy_keys
+                .checked_sub(light_keys)
+                .filter(|&excess| excess > 0)?;
+
+            Some((weight(&chord) * excess, chord, word))
+        })
+        .collect();
+    offenders.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    offenders
+        .into_iter()
+        .filter_map(|(_, chord, word)| {
+            let heavy_keys = keys_on(&chord, layout, heavy);
+            let alternatives: Vec<_> =
+                suggest(&word, chords, layout, blacklist, CANDIDATE_SUGGESTIONS)
+                    .into_iter()
+                    .filter(|suggestion| keys_on(&suggestion.chord, layout, heavy) < heavy_keys)
+                    .take(alternatives)
+                    .collect();
+
+            (!alternatives.is_empty()).then_some(Rebalance {
+                chord,
+                word,
+                alternatives,
+            })
+        })
+        .take(count)
+        .collect()
+}
+
+fn keys_on(chord: &Chord, layout: &Layout, hand: Hand) -> usize {
+    chord
+        .keys()
+        .filter_map(|key| layout.key(key))
+        .filter(|key| key.hand == hand)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_keys_by_hand() {
+        let chords = Chords::from_pairs(&[("A+S+J", "as"), ("D+F", "did"), ("[+K", "ok")]);
+        let layout = Layout::qwerty();
+
+        let keys = hand_load(&chords, &layout, |_| 1);
+        assert_eq!(keys, HandLoad { left: 4, right: 2 });
+        assert_eq!(keys.heavier().map(|(hand, _)| hand), Some(Hand::Left));
+
+        let activations = hand_load(&chords, &layout, |chord| {
+            if chord.as_str() == "K+[" {
+                10
+            } else {
+                0
+            }
+        });
+        assert_eq!(activations, HandLoad { left: 0, right: 10 });
+        assert_eq!(activations.heavier(), Some((Hand::Right, 1.0)));
+    }
+
+    #[test]
+    fn move_load_to_lighter_hand() {
+        let chords = Chords::from_pairs(&[("S+T", "stop"), ("A+D", "add"), ("J+K", "jk")]);
+        let layout = Layout::qwerty();
+
+        let suggestions = rebalance(&chords, &layout, &WordSet::default(), |_| 1, 5, 3);
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].word, "stop");
+        assert!(suggestions[0].alternatives.iter().all(|suggestion| keys_on(
+            &suggestion.chord,
+            &layout,
+            Hand::Left
+        ) < 2));
+    }
+}
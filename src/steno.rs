@@ -0,0 +1,110 @@
+use crate::{chords::Chord, config::Config};
+
+const KEYS: [&str; 23] = [
+    "#", "S-", "T-", "K-", "P-", "W-", "H-", "R-", "A-", "O-", "*", "-E", "-U", "-F", "-R", "-P",
+    "-B", "-L", "-G", "-T", "-S", "-D", "-Z",
+];
+const VOWELS: [&str; 5] = ["A-", "O-", "*", "-E", "-U"];
+
+const QWERTY: [&str; 23] = [
+    "1", "A", "W", "S", "E", "D", "R", "F", "C", "V", "T", "N", "M", "U", "J", "I", "K", "O", "L",
+    "P", ";", "[", "'",
+];
+
+pub fn is_key(key: &str) -> bool {
+    KEYS.contains(&key)
+}
+
+/// Maps steno keys to ours, like Plover's QWERTY layout unless configured otherwise.
+pub struct Steno {
+    tokens: Vec<String>,
+}
+
+impl Steno {
+    pub fn from_config(config: &Config) -> Self {
+        let tokens = KEYS
+            .iter()
+            .zip(QWERTY)
+            .map(|(key, default)| {
+                config
+                    .steno_keys
+                    .iter()
+                    .rev()
+                    .find(|(other, _)| other == key)
+                    .map_or(default, |(_, token)| token.as_str())
+                    .to_owned()
+            })
+            .collect();
+
+        Self { tokens }
+    }
+
+    pub fn chord(&self, stroke: &str) -> Option<Chord> {
+        let mut position = 0;
+        let mut keys = Vec::new();
+
+        for char in stroke.chars() {
+            if char == '-' {
+                position = position.max(KEYS.iter().position(|key| *key == "-E")?);
+                continue;
+            }
+
+            let letter = char.to_string();
+            position += KEYS[position..]
+                .iter()
+                .position(|key| key.trim_matches('-') == letter)?;
+            keys.push(self.tokens[position].as_str());
+            position += 1;
+        }
+
+        keys.join("+")
+            .parse()
+            .ok()
+            .filter(|chord: &Chord| !chord.is_empty())
+    }
+
+    pub fn stroke(&self, chord: &Chord) -> Option<String> {
+        let mut positions = chord
+            .keys()
+            .map(|key| self.tokens.iter().position(|token| token == key))
+            .collect::<Option<Vec<_>>>()?;
+        positions.sort_unstable();
+
+        let vowel = positions
+            .iter()
+            .any(|position| VOWELS.contains(&KEYS[*position]));
+        let mut stroke = String::new();
+        for position in positions {
+            let key = KEYS[position];
+            if !vowel && key.starts_with('-') && !stroke.contains('-') {
+                stroke.push('-');
+            }
+            stroke.push_str(key.trim_matches('-'));
+        }
+
+        Some(stroke).filter(|stroke| !stroke.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translate_strokes() {
+        let steno = Steno::from_config(&Config::default());
+
+        let chord = steno.chord("TH-E").unwrap();
+        assert_eq!(chord.as_str(), "N+R+W");
+        assert_eq!(steno.stroke(&chord).as_deref(), Some("THE"));
+
+        let right = steno.chord("-PBLG").unwrap();
+        assert_eq!(right.as_str(), "I+K+L+O");
+        assert_eq!(steno.stroke(&right).as_deref(), Some("-PBLG"));
+        assert_eq!(steno.chord("STS").unwrap().as_str(), ";+A+W");
+
+        assert!(steno.chord("SKWR/TPH").is_none());
+        assert!(steno.chord("TS-T").is_none());
+        assert!(steno.stroke(&"X+Y".parse().unwrap()).is_none());
+    }
+}
@@ -0,0 +1,541 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{Error, Result, Write},
+    path::Path,
+    sync::OnceLock,
+};
+
+use crate::config::Config;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Table {
+    Entries,
+    Words,
+    History,
+    Session,
+}
+
+pub trait Storage: Send + Sync {
+    fn read_to_string(&self, path: &Path) -> Result<String>;
+
+    fn write(&self, path: &Path, contents: &str) -> Result<()>;
+
+    fn append(&self, path: &Path, contents: &str) -> Result<()>;
+}
+
+struct Files;
+
+impl Storage for Files {
+    fn read_to_string(&self, path: &Path) -> Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn append(&self, path: &Path, contents: &str) -> Result<()> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(contents.as_bytes())
+    }
+}
+
+static STORAGE: OnceLock<Box<dyn Storage>> = OnceLock::new();
+
+pub fn open(config: &Config, data: &'static [(&'static str, Table)]) -> Result<()> {
+    let storage: Box<dyn Storage> = match &config.database {
+        None => Box::new(Files),
+        #[cfg(feature = "sqlite")]
+        Some(database) => Box::new(sqlite::Sqlite::open(database, data)?),
+        #[cfg(not(feature = "sqlite"))]
+        Some(_) => {
+            let _ = data;
+            return Err(Error::other(
+                "the database setting requires building with the sqlite feature",
+            ));
+        }
+    };
+
+    STORAGE
+        .set(storage)
+        .map_err(|_| Error::other("storage opened twice"))
+}
+
+fn storage() -> &'static dyn Storage {
+    STORAGE.get().map_or(&Files, Box::as_ref)
+}
+
+pub fn read_to_string(path: impl AsRef<Path>) -> Result<String> {
+    storage().read_to_string(path.as_ref())
+}
+
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<str>) -> Result<()> {
+    storage().write(path.as_ref(), contents.as_ref())
+}
+
+pub fn append(path: impl AsRef<Path>, contents: impl AsRef<str>) -> Result<()> {
+    storage().append(path.as_ref(), contents.as_ref())
+}
+
+pub fn tracked<'a>(config: &'a Config, paths: Vec<&'a str>) -> Vec<&'a str> {
+    match &config.database {
+        Some(database) => vec![database.as_str()],
+        None => paths,
+    }
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use std::{
+        io::{Error, ErrorKind, Result},
+        path::Path,
+        sync::{Mutex, MutexGuard, PoisonError},
+    };
+
+    use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+    use crate::chords::{Chords, Metadata, Notation};
+
+    use super::{Files, Storage, Table};
+
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS files (
+            name TEXT PRIMARY KEY
+        );
+        CREATE TABLE IF NOT EXISTS entries (
+            chord TEXT PRIMARY KEY,
+            word TEXT NOT NULL,
+            created INTEGER,
+            modified INTEGER,
+            suppress_duplicate INTEGER NOT NULL,
+            suppress_near_miss INTEGER NOT NULL,
+            note TEXT
+        );
+        CREATE TABLE IF NOT EXISTS tags (
+            word TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (word, tag)
+        );
+        CREATE TABLE IF NOT EXISTS words (
+            file TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            word TEXT NOT NULL,
+            count INTEGER,
+            PRIMARY KEY (file, position)
+        );
+        CREATE TABLE IF NOT EXISTS history (
+            file TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            timestamp INTEGER,
+            event TEXT NOT NULL,
+            PRIMARY KEY (file, position)
+        );
+        CREATE TABLE IF NOT EXISTS sessions (
+            file TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            value TEXT NOT NULL,
+            PRIMARY KEY (file, position)
+        );";
+
+    enum Rows {
+        Entries(Chords),
+        Words(Vec<(String, Option<u64>)>),
+        History(Vec<(Option<u64>, String)>),
+        Session(Vec<(String, String)>),
+    }
+
+    impl Rows {
+        fn parse(table: Table, contents: &str) -> Result<Self> {
+            let lines = contents.lines().filter(|line| !line.trim().is_empty());
+
+            Ok(match table {
+                Table::Entries => Rows::Entries(Chords::parse_str(contents)?),
+                Table::Words => Rows::Words(
+                    lines
+                        .map(|line| match line.split_once('\t') {
+                            Some((word, count)) if count.trim().parse::<u64>().is_ok() => {
+                                (word.trim().to_owned(), count.trim().parse().ok())
+                            }
+                            _ => (line.trim().to_owned(), None),
+                        })
+                        .collect(),
+                ),
+                Table::History => Rows::History(
+                    lines
+                        .map(|line| match line.split_once(' ') {
+                            Some((timestamp, event)) if timestamp.parse::<u64>().is_ok() => {
+                                (timestamp.parse().ok(), event.to_owned())
+                            }
+                            _ => (None, line.to_owned()),
+                        })
+                        .collect(),
+                ),
+                Table::Session => Rows::Session(
+                    lines
+                        .filter_map(|line| line.split_once(" = "))
+                        .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                        .collect(),
+                ),
+            })
+        }
+
+        fn format(&self) -> String {
+            match self {
+                Rows::Entries(chords) => chords.format(&Notation::default()),
+                Rows::Words(words) => words
+                    .iter()
+                    .map(|(word, count)| match count {
+                        Some(count) => format!("{word}\t{count}\n"),
+                        None => format!("{word}\n"),
+                    })
+                    .collect(),
+                Rows::History(events) => events
+                    .iter()
+                    .map(|(timestamp, event)| match timestamp {
+                        Some(timestamp) => format!("{timestamp} {event}\n"),
+                        None => format!("{event}\n"),
+                    })
+                    .collect(),
+                Rows::Session(settings) => settings
+                    .iter()
+                    .map(|(name, value)| format!("{name} = {value}\n"))
+                    .collect(),
+            }
+        }
+
+        fn select(connection: &Connection, name: &str, table: Table) -> rusqlite::Result<Self> {
+            match table {
+                Table::Entries => {
+                    let mut chords = Chords::default();
+                    let mut statement = connection.prepare(
+                        "SELECT chord, word, created, modified, suppress_duplicate,
+                        suppress_near_miss, note FROM entries",
+                    )?;
+                    let mut rows = statement.query([])?;
+                    while let Some(row) = rows.next()? {
+                        let chord: String = row.get(0)?;
+                        let word: String = row.get(1)?;
+                        let mut metadata = Metadata {
+                            created: row.get(2)?,
+                            modified: row.get(3)?,
+                            note: row.get(6)?,
+                            ..Metadata::default()
+                        };
+                        metadata.suppressed.duplicate = row.get(4)?;
+                        metadata.suppressed.near_miss = row.get(5)?;
+
+                        let mut tags =
+                            connection.prepare_cached("SELECT tag FROM tags WHERE word = ?1")?;
+                        metadata.tags = tags
+                            .query_map([&word], |row| row.get(0))?
+                            .collect::<rusqlite::Result<_>>()?;
+
+                        let Ok(chord) = chord.parse() else {
+                            continue;
+                        };
+                        chords.set_metadata(&word, metadata);
+                        chords.insert(chord, word);
+                    }
+                    Ok(Rows::Entries(chords))
+                }
+                Table::Words => {
+                    let mut statement = connection.prepare(
+                        "SELECT word, count FROM words WHERE file = ?1 ORDER BY position",
+                    )?;
+                    let words = statement
+                        .query_map([name], |row| Ok((row.get(0)?, row.get(1)?)))?
+                        .collect::<rusqlite::Result<_>>()?;
+                    Ok(Rows::Words(words))
+                }
+                Table::History => {
+                    let mut statement = connection.prepare(
+                        "SELECT timestamp, event FROM history WHERE file = ?1 ORDER BY position",
+                    )?;
+                    let events = statement
+                        .query_map([name], |row| Ok((row.get(0)?, row.get(1)?)))?
+                        .collect::<rusqlite::Result<_>>()?;
+                    Ok(Rows::History(events))
+                }
+                Table::Session => {
+                    let mut statement = connection.prepare(
+                        "SELECT name, value FROM sessions WHERE file = ?1 ORDER BY position",
+                    )?;
+                    let settings = statement
+                        .query_map([name], |row| Ok((row.get(0)?, row.get(1)?)))?
+                        .collect::<rusqlite::Result<_>>()?;
+                    Ok(Rows::Session(settings))
+                }
+            }
+        }
+
+        fn insert(&self, transaction: &Transaction, name: &str) -> rusqlite::Result<()> {
+            transaction.execute("INSERT OR IGNORE INTO files (name) VALUES (?1)", [name])?;
+
+            match self {
+                Rows::Entries(chords) => {
+                    for (chord, word) in chords.iter() {
+                        let metadata = chords.metadata(&word);
+                        transaction.execute(
+                            "INSERT INTO entries VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                            params![
+                                chord.as_str(),
+                                word,
+                                metadata.created,
+                                metadata.modified,
+                                metadata.suppressed.duplicate,
+                                metadata.suppressed.near_miss,
+                                metadata.note,
+                            ],
+                        )?;
+                        for tag in &metadata.tags {
+                            transaction.execute(
+                                "INSERT OR IGNORE INTO tags VALUES (?1, ?2)",
+                                [&word, tag],
+                            )?;
+                        }
+                    }
+                }
+                Rows::Words(words) => {
+                    let start = next_position(transaction, "words", name)?;
+                    for ((word, count), position) in words.iter().zip(start..) {
+                        transaction.execute(
+                            "INSERT INTO words VALUES (?1, ?2, ?3, ?4)",
+                            params![name, position, word, count],
+                        )?;
+                    }
+                }
+                Rows::History(events) => {
+                    let start = next_position(transaction, "history", name)?;
+                    for ((timestamp, event), position) in events.iter().zip(start..) {
+                        transaction.execute(
+                            "INSERT INTO history VALUES (?1, ?2, ?3, ?4)",
+                            params![name, position, timestamp, event],
+                        )?;
+                    }
+                }
+                Rows::Session(settings) => {
+                    let start = next_position(transaction, "sessions", name)?;
+                    for ((setting, value), position) in settings.iter().zip(start..) {
+                        transaction.execute(
+                            "INSERT INTO sessions VALUES (?1, ?2, ?3, ?4)",
+                            params![name, position, setting, value],
+                        )?;
+                    }
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    fn next_position(transaction: &Transaction, table: &str, name: &str) -> rusqlite::Result<i64> {
+        transaction.query_row(
+            &format!("SELECT COALESCE(MAX(position) + 1, 0) FROM {table} WHERE file = ?1"),
+            [name],
+            |row| row.get(0),
+        )
+    }
+
+    fn clear(transaction: &Transaction, name: &str, table: Table) -> rusqlite::Result<()> {
+        match table {
+            Table::Entries => transaction.execute_batch("DELETE FROM entries; DELETE FROM tags;"),
+            Table::Words => transaction
+                .execute("DELETE FROM words WHERE file = ?1", [name])
+                .map(|_| ()),
+            Table::History => transaction
+                .execute("DELETE FROM history WHERE file = ?1", [name])
+                .map(|_| ()),
+            Table::Session => transaction
+                .execute("DELETE FROM sessions WHERE file = ?1", [name])
+                .map(|_| ()),
+        }
+    }
+
+    fn error(error: rusqlite::Error) -> Error {
+        Error::other(format!("database: {error}"))
+    }
+
+    pub struct Sqlite {
+        connection: Mutex<Connection>,
+        data: &'static [(&'static str, Table)],
+    }
+
+    impl Sqlite {
+        pub fn open(path: &str, data: &'static [(&'static str, Table)]) -> Result<Self> {
+            let connection = Connection::open(path).map_err(error)?;
+            connection.execute_batch(SCHEMA).map_err(error)?;
+
+            let sqlite = Self {
+                connection: Mutex::new(connection),
+                data,
+            };
+            sqlite.import()?;
+            Ok(sqlite)
+        }
+
+        // each file is imported once, afterwards the database is the source of truth
+        fn import(&self) -> Result<()> {
+            for (name, _) in self.data {
+                let path = Path::new(name);
+                if self.stored(name)? {
+                    continue;
+                }
+                let Ok(contents) = Files.read_to_string(path) else {
+                    continue;
+                };
+
+                self.write(path, &contents)?;
+            }
+
+            Ok(())
+        }
+
+        fn connection(&self) -> MutexGuard<'_, Connection> {
+            self.connection
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+        }
+
+        fn table<'a>(&self, path: &'a Path) -> Option<(&'a str, Table)> {
+            let name = path.to_str()?;
+            let (_, table) = self.data.iter().find(|(other, _)| *other == name)?;
+
+            Some((name, *table))
+        }
+
+        fn stored(&self, name: &str) -> Result<bool> {
+            self.connection()
+                .query_row("SELECT 1 FROM files WHERE name = ?1", [name], |_| Ok(()))
+                .optional()
+                .map(|row| row.is_some())
+                .map_err(error)
+        }
+
+        fn store(&self, name: &str, rows: &Rows, table: Table, replace: bool) -> Result<()> {
+            let mut connection = self.connection();
+            let transaction = connection.transaction().map_err(error)?;
+            if replace {
+                clear(&transaction, name, table).map_err(error)?;
+            }
+            rows.insert(&transaction, name).map_err(error)?;
+
+            transaction.commit().map_err(error)
+        }
+    }
+
+    impl Storage for Sqlite {
+        fn read_to_string(&self, path: &Path) -> Result<String> {
+            let Some((name, table)) = self.table(path) else {
+                return Files.read_to_string(path);
+            };
+            if !self.stored(name)? {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("{name} not in database"),
+                ));
+            }
+
+            let rows = Rows::select(&self.connection(), name, table).map_err(error)?;
+            Ok(rows.format())
+        }
+
+        fn write(&self, path: &Path, contents: &str) -> Result<()> {
+            let Some((name, table)) = self.table(path) else {
+                return Files.write(path, contents);
+            };
+
+            self.store(name, &Rows::parse(table, contents)?, table, true)
+        }
+
+        fn append(&self, path: &Path, contents: &str) -> Result<()> {
+            let Some((name, table)) = self.table(path) else {
+                return Files.append(path, contents);
+            };
+
+            // chord entries are keyed by chord, so appending to them rewrites the file
+            if table == Table::Entries {
+                let existing = match self.read_to_string(path) {
+                    Err(error) if error.kind() == ErrorKind::NotFound => String::new(),
+                    existing => existing?,
+                };
+                return self.write(path, &(existing + contents));
+            }
+
+            self.store(name, &Rows::parse(table, contents)?, table, false)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const DATA: &[(&str, Table)] = &[
+            ("chords.txt", Table::Entries),
+            ("words.txt", Table::Words),
+            ("journal.log", Table::History),
+            ("session.txt", Table::Session),
+        ];
+
+        #[test]
+        fn store_files_in_tables() {
+            let database = std::env::temp_dir().join(format!("chords-{}.db", std::process::id()));
+            let sqlite = Sqlite::open(database.to_str().unwrap(), DATA).unwrap();
+            let path = |name| Path::new(name);
+
+            let missing = sqlite.read_to_string(path("journal.log")).unwrap_err();
+            assert_eq!(missing.kind(), ErrorKind::NotFound);
+
+            let chords = "# chords format 2\nE+H+T: the | created=5 suppress=near-miss tags=a,b note=x y\nA+N: and\n";
+            sqlite.write(path("chords.txt"), chords).unwrap();
+            assert_eq!(
+                sqlite.read_to_string(path("chords.txt")).unwrap(),
+                "# chords format 2\nA+N: and\nE+H+T: the | created=5 suppress=near-miss tags=a,b note=x y\n"
+            );
+            sqlite.write(path("chords.txt"), "T+W: two\n").unwrap();
+            assert_eq!(
+                sqlite.read_to_string(path("chords.txt")).unwrap(),
+                "# chords format 2\nT+W: two\n"
+            );
+
+            sqlite.write(path("words.txt"), "the\t12\nof\n").unwrap();
+            assert_eq!(
+                sqlite.read_to_string(path("words.txt")).unwrap(),
+                "the\t12\nof\n"
+            );
+
+            sqlite
+                .append(path("journal.log"), "1 add the: H+T\n")
+                .unwrap();
+            sqlite
+                .append(path("journal.log"), "2 delete of: F+O\n")
+                .unwrap();
+            assert_eq!(
+                sqlite.read_to_string(path("journal.log")).unwrap(),
+                "1 add the: H+T\n2 delete of: F+O\n"
+            );
+
+            sqlite
+                .write(path("session.txt"), "search = th\nsort = rank\n")
+                .unwrap();
+            assert_eq!(
+                sqlite.read_to_string(path("session.txt")).unwrap(),
+                "search = th\nsort = rank\n"
+            );
+
+            let stored: i64 = sqlite
+                .connection()
+                .query_row("SELECT COUNT(*) FROM tags WHERE word = 'the'", [], |row| {
+                    row.get(0)
+                })
+                .unwrap();
+            assert_eq!(stored, 0);
+
+            drop(sqlite);
+            std::fs::remove_file(database).unwrap();
+        }
+    }
+}
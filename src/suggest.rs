@@ -0,0 +1,342 @@
+use std::{
+    collections::BTreeSet,
+    io::{Error, ErrorKind, Read, Result, Write},
+    process::{Command, Stdio},
+    sync::mpsc::channel,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    chords::{Chord, Chords, Notation},
+    cost::Features,
+    json::Json,
+    layout::{Finger, Hand, Layout, HOME_ROW},
+    word_set::WordSet,
+};
+
+const MIN_KEYS: usize = 2;
+const MAX_KEYS: usize = 4;
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+pub struct Suggestion {
+    pub chord: Chord,
+    pub score: u32,
+}
+
+pub fn suggest(
+    word: &str,
+    chords: &Chords,
+    layout: &Layout,
+    blacklist: &WordSet,
+    count: usize,
+) -> Vec<Suggestion> {
+    if blacklist.contains(word) {
+        return Vec::new();
+    }
+
+    let letters: Vec<_> = word
+        .chars()
+        .map(|char| char.to_ascii_uppercase())
+        .filter(char::is_ascii_uppercase)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let first_letter = word
+        .chars()
+        .next()
+        .map(|char| char.to_ascii_uppercase().to_string());
+
+    let mut suggestions = Vec::new();
+    for keys in MIN_KEYS..=MAX_KEYS.min(letters.len()) {
+        for combination in combinations(&letters, keys) {
+            let mut chord = Chord::default();
+            for key in combination {
+                chord.insert(key);
+            }
+
+            if chords.contains(&chord) || !layout.violations(&chord).is_empty() {
+                continue;
+            }
+
+            let mut score = difficulty(&chord, layout);
+            if !first_letter
+                .as_deref()
+                .is_some_and(|first| chord.keys().any(|key| key == first))
+            {
+                score += 2;
+            }
+
+            suggestions.push(Suggestion { chord, score });
+        }
+    }
+
+    suggestions.sort_by(|a, b| a.score.cmp(&b.score).then_with(|| a.chord.cmp(&b.chord)));
+    suggestions.truncate(count);
+
+    suggestions
+}
+
+/// Passes the word and the chords as JSON on stdin and reads one chord per line.
+pub fn external(
+    command: &str,
+    timeout: Duration,
+    word: &str,
+    chords: &Chords,
+    notation: &Notation,
+) -> Result<Vec<Chord>> {
+    let input = Json::Object(vec![
+        ("word", word.into()),
+        (
+            "chords",
+            Json::array(chords.iter().map(|(chord, word)| {
+                Json::Object(vec![
+                    ("chord", notation.format(&chord).into()),
+                    ("word", word.into()),
+                ])
+            })),
+        ),
+    ])
+    .to_string();
+
+    let output = run(command, input, timeout)?;
+
+    String::from_utf8_lossy(&output)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            notation.parse(line).map_err(|_| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Suggestion command returned invalid chord {line}"),
+                )
+            })
+        })
+        .collect()
+}
+
+fn run(command: &str, input: String, timeout: Duration) -> Result<Vec<u8>> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+
+    // write and read from other threads so a command printing before reading cannot deadlock,
+    // and so neither blocks past the timeout if the command hangs
+    thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = sender.send(stdout.read_to_end(&mut output).map(|_| output));
+    });
+
+    let deadline = Instant::now() + timeout;
+    let output = receiver.recv_timeout(timeout);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if Instant::now() >= deadline {
+            break None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let (Ok(output), Some(status)) = (output, status) else {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(Error::new(
+            ErrorKind::TimedOut,
+            format!(
+                "Suggestion command did not finish within {}s",
+                timeout.as_secs_f64()
+            ),
+        ));
+    };
+    if !status.success() {
+        return Err(Error::other(format!(
+            "Suggestion command failed with {status}"
+        )));
+    }
+
+    output
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
+
+pub fn merge(
+    mut suggestions: Vec<Suggestion>,
+    candidates: Vec<Chord>,
+    chords: &Chords,
+    layout: &Layout,
+    count: usize,
+) -> Vec<Suggestion> {
+    for chord in candidates {
+        if chords.contains(&chord)
+            || !layout.violations(&chord).is_empty()
+            || suggestions
+                .iter()
+                .any(|suggestion| suggestion.chord == chord)
+        {
+            continue;
+        }
+
+        let score = difficulty(&chord, layout);
+        suggestions.push(Suggestion { chord, score });
+    }
+
+    suggestions.sort_by(|a, b| a.score.cmp(&b.score).then_with(|| a.chord.cmp(&b.chord)));
+    suggestions.truncate(count);
+
+    suggestions
+}
+
+pub fn difficulty(chord: &Chord, layout: &Layout) -> u32 {
+    let features = features(chord, layout);
+
+    match layout.cost() {
+        Some(cost) => cost.evaluate(&features),
+        None => {
+            features.top
+                + 2 * features.reach
+                + features.ring
+                + 2 * features.pinky
+                + 4 * features.unknown
+                + features.keys.saturating_sub(2)
+        }
+    }
+}
+
+fn features(chord: &Chord, layout: &Layout) -> Features {
+    let mut features = Features::default();
+
+    for key in chord.keys() {
+        features.keys += 1;
+
+        let Some(key) = layout.key(key) else {
+            features.unknown += 1;
+            continue;
+        };
+
+        match key.row.abs_diff(HOME_ROW) {
+            0 => features.home += 1,
+            1 if key.row < HOME_ROW => features.top += 1,
+            _ => features.reach += 1,
+        }
+        match key.finger {
+            Finger::Thumb => features.thumb += 1,
+            Finger::Index => features.index += 1,
+            Finger::Middle => features.middle += 1,
+            Finger::Ring => features.ring += 1,
+            Finger::Pinky => features.pinky += 1,
+        }
+        match key.hand {
+            Hand::Left => features.left += 1,
+            Hand::Right => features.right += 1,
+        }
+    }
+
+    features
+}
+
+fn combinations(letters: &[char], size: usize) -> Vec<Vec<char>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+
+    (0..letters.len())
+        .flat_map(|index| {
+            combinations(&letters[index + 1..], size - 1)
+                .into_iter()
+                .map(move |mut combination| {
+                    combination.insert(0, letters[index]);
+                    combination
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggest_unused_possible_chords() {
+        let layout = Layout::qwerty();
+        let chords = Chords::from_pairs(&[("H+T", "that")]);
+
+        let suggestions = suggest("the", &chords, &layout, &WordSet::default(), 10);
+
+        assert!(!suggestions.is_empty());
+        assert!(suggestions
+            .iter()
+            .all(|suggestion| suggestion.chord.as_str() != "H+T"));
+        assert!(suggestions
+            .windows(2)
+            .all(|pair| pair[0].score <= pair[1].score));
+        assert_eq!(suggestions[0].chord.as_str(), "E+T");
+    }
+
+    #[test]
+    fn skip_blacklisted_words() {
+        let layout = Layout::qwerty();
+        let chords: Chords = [].into_iter().collect();
+        let blacklist = WordSet::from_iter(["the".to_owned()]);
+
+        assert!(suggest("the", &chords, &layout, &blacklist, 10).is_empty());
+        assert!(!suggest("then", &chords, &layout, &blacklist, 10).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn merge_external_candidates() {
+        let layout = Layout::qwerty();
+        let chords = Chords::from_pairs(&[("H+T", "that")]);
+        let notation = Notation::default();
+
+        let timeout = Duration::from_secs(1);
+
+        let candidates = external(
+            "cat > /dev/null; printf 'H+T\\nE+H\\n\\nT+E\\n'",
+            timeout,
+            "the",
+            &chords,
+            &notation,
+        )
+        .unwrap();
+        assert_eq!(candidates.len(), 3);
+
+        let suggestions = merge(Vec::new(), candidates, &chords, &layout, 10);
+        let suggested: Vec<_> = suggestions
+            .iter()
+            .map(|suggestion| suggestion.chord.as_str())
+            .collect();
+        assert_eq!(suggested, ["E+H", "E+T"]);
+        assert!(external("exit 1", timeout, "the", &chords, &notation).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kill_slow_command() {
+        let started = Instant::now();
+        let error = run("sleep 1", String::new(), Duration::from_millis(50)).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+}
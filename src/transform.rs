@@ -0,0 +1,230 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{Error, ErrorKind, Result as IoResult},
+};
+
+use crate::chords::{Chord, Chords};
+
+const LAYOUTS: [(&str, [&str; 3]); 6] = [
+    ("qwerty", ["QWERTYUIOP", "ASDFGHJKL;", "ZXCVBNM,./"]),
+    ("colemak", ["QWFPGJLUY;", "ARSTDHNEIO", "ZXCVBKM,./"]),
+    ("colemak-dh", ["QWFPBJLUY;", "ARSTGMNEIO", "ZXCDVKH,./"]),
+    ("dvorak", ["',.PYFGCRL", "AOEUIDHTNS", ";QJKXBMWVZ"]),
+    ("workman", ["QDRWBJFUP;", "ASHTGYNEOI", "ZXMCVKL,./"]),
+    ("azerty", ["AZERTYUIOP", "QSDFGHJKLM", "WXCVBN,;:!"]),
+];
+
+pub struct Mapping(BTreeMap<String, String>);
+
+impl Mapping {
+    pub fn between(from: &str, to: &str) -> IoResult<Self> {
+        let rows = |name: &str| {
+            LAYOUTS
+                .iter()
+                .find(|(layout, _)| *layout == name)
+                .map(|(_, rows)| rows)
+                .ok_or_else(|| {
+                    let known: Vec<_> = LAYOUTS.iter().map(|(layout, _)| *layout).collect();
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "Unknown layout {name}, expected one of {}",
+                            known.join(", ")
+                        ),
+                    )
+                })
+        };
+        let (from, to) = (rows(from)?, rows(to)?);
+
+        Ok(Self(
+            from.iter()
+                .zip(to)
+                .flat_map(|(from, to)| from.chars().zip(to.chars()))
+                .map(|(from, to)| (from.to_string(), to.to_string()))
+                .collect(),
+        ))
+    }
+
+    pub fn parse(contents: &str) -> IoResult<Self> {
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (from, to) = line
+                    .split_once('=')
+                    .map(|(from, to)| (from.trim(), to.trim()))
+                    .filter(|(from, to)| from.chars().count() == 1 && to.chars().count() == 1)
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::InvalidData,
+                            format!("Invalid mapping '{line}', expected '<key> = <key>'"),
+                        )
+                    })?;
+
+                Ok((from.to_uppercase(), to.to_uppercase()))
+            })
+            .collect::<IoResult<_>>()
+            .map(Self)
+    }
+
+    fn map(&self, key: &str) -> String {
+        self.0.get(key).cloned().unwrap_or_else(|| key.to_owned())
+    }
+}
+
+pub enum Collision {
+    Shared {
+        chord: Chord,
+        words: Vec<String>,
+    },
+    Merged {
+        word: String,
+        before: Chord,
+        after: Chord,
+    },
+    Invalid {
+        word: String,
+        before: Chord,
+        keys: String,
+    },
+}
+
+impl Display for Collision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Collision::Shared { chord, words } => write!(
+                f,
+                "{}: would be shared by {}",
+                chord.as_str(),
+                words.join(", ")
+            ),
+            Collision::Merged {
+                word,
+                before,
+                after,
+            } => write!(
+                f,
+                "{} ({word}): keys would merge into {}",
+                before.as_str(),
+                after.as_str()
+            ),
+            Collision::Invalid { word, before, keys } => write!(
+                f,
+                "{} ({word}): keys would become {keys}, which is not a valid chord",
+                before.as_str()
+            ),
+        }
+    }
+}
+
+pub fn transform(chords: &Chords, map: impl Fn(&str) -> String) -> (Chords, Vec<Collision>) {
+    let mut words_per_chord: BTreeMap<Chord, Vec<String>> = BTreeMap::new();
+    let mut collisions = Vec::new();
+
+    for (chord, word) in chords.iter() {
+        let keys: Vec<_> = chord.keys().map(&map).collect();
+        let keys = keys.join("+");
+        let Ok(mapped) = keys.parse::<Chord>() else {
+            collisions.push(Collision::Invalid {
+                word,
+                before: chord,
+                keys,
+            });
+            continue;
+        };
+
+        if mapped.keys().count() < chord.keys().count() {
+            collisions.push(Collision::Merged {
+                word: word.clone(),
+                before: chord,
+                after: mapped.clone(),
+            });
+        }
+        words_per_chord.entry(mapped).or_default().push(word);
+    }
+
+    collisions.extend(
+        words_per_chord
+            .iter()
+            .filter(|(_, words)| words.len() > 1)
+            .map(|(chord, words)| Collision::Shared {
+                chord: chord.clone(),
+                words: words.clone(),
+            }),
+    );
+
+    let mut transformed: Chords = words_per_chord
+        .into_iter()
+        .flat_map(|(chord, words)| words.into_iter().map(move |word| (chord.clone(), word)))
+        .collect();
+    transformed.inherit_metadata(chords);
+
+    (transformed, collisions)
+}
+
+pub fn substitute(chords: &Chords, from: char, to: char) -> (Chords, Vec<Collision>) {
+    let (from, to) = (
+        from.to_ascii_uppercase().to_string(),
+        to.to_ascii_uppercase(),
+    );
+
+    transform(chords, |key| {
+        if key == from {
+            to.to_string()
+        } else {
+            key.to_owned()
+        }
+    })
+}
+
+pub fn migrate(chords: &Chords, mapping: &Mapping) -> (Chords, Vec<Collision>) {
+    transform(chords, |key| mapping.map(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substitute_key_and_report_collisions() {
+        let chords = Chords::from_pairs(&[("A+B", "ab"), ("B+C", "bc"), ("C+D", "cd")]);
+
+        let (substituted, collisions) = substitute(&chords, 'd', 'x');
+        assert!(collisions.is_empty());
+        assert_eq!(substituted.get(&"C+X".parse().unwrap()).unwrap(), "cd");
+
+        let (_, collisions) = substitute(&chords, 'a', 'c');
+        let collisions: Vec<_> = collisions.iter().map(ToString::to_string).collect();
+        assert_eq!(collisions, ["B+C: would be shared by ab, bc"]);
+
+        let (_, collisions) = substitute(&chords, 'b', 'c');
+        let collisions: Vec<_> = collisions.iter().map(ToString::to_string).collect();
+        assert_eq!(collisions, ["B+C (bc): keys would merge into C"]);
+
+        let (_, collisions) = substitute(&chords, 'a', ' ');
+        let collisions: Vec<_> = collisions.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            collisions,
+            ["A+B (ab): keys would become  +B, which is not a valid chord"]
+        );
+    }
+
+    #[test]
+    fn migrate_between_layouts() {
+        let chords = Chords::from_pairs(&[("E+K", "ek")]);
+
+        let mapping = Mapping::between("qwerty", "colemak").unwrap();
+        let (migrated, collisions) = migrate(&chords, &mapping);
+        assert!(collisions.is_empty());
+        assert_eq!(migrated.get(&"E+F".parse().unwrap()).unwrap(), "ek");
+
+        let mapping = Mapping::parse("# swap\ne = k\nk = e\n").unwrap();
+        let (migrated, _) = migrate(&chords, &mapping);
+        assert_eq!(migrated.get(&"E+K".parse().unwrap()).unwrap(), "ek");
+
+        assert!(Mapping::between("qwerty", "unknown").is_err());
+        assert!(Mapping::parse("ab = c").is_err());
+    }
+}
@@ -1,51 +1,267 @@
-use std::io::{stdout, Result, Stdout};
+mod calibration;
+mod charts;
+mod column;
+mod detail;
+mod drill;
+mod edit;
+mod flashcards;
+mod lemmas;
+mod loading;
+mod menu;
+mod quiz;
+mod remap;
+mod resolver;
+mod searcher;
+mod session;
+mod sprint;
+mod tree;
+mod warnings;
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    io::{stdout, Error, Result, Stdout, Write},
+    mem,
+    num::NonZeroUsize,
+    ops::RangeInclusive,
+    sync::{
+        mpsc::{channel, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 use crossterm::{
     event::{
-        read, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
-        KeyModifiers,
+        poll, read, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste,
+        EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton,
+        MouseEvent, MouseEventKind,
     },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Style, Stylize},
-    text::{Span, Text},
+    text::{Line, Span, Text},
     widgets::{Block, Paragraph, Row as TableRow, Table, TableState},
     Terminal,
 };
+use unicode_width::UnicodeWidthStr;
 
 use crate::{
-    chords::{Chord, Chords},
-    words::Words,
+    chords::{parse_note, parse_tag, Chord, Chords, Metadata, Notation},
+    clock,
+    config::Config,
+    inflection::{self, Derived},
+    journal::{Change, Journal},
+    layout::{Layout as KeyboardLayout, Violation},
+    practice::{History, Record},
+    search::{search, Case, Group, Hit, Matching, Query, Sort},
+    simulate::Model,
+    suggest::{difficulty, external, suggest},
+    usage::{Log, HISTORY_BUCKETS},
+    validate,
+    word_set::WordSet,
+    words::{Entry, Words},
 };
 
+use calibration::Calibration;
+use charts::Charts;
+pub use column::Column;
+use column::Picker;
+use detail::Popup;
+use drill::Drill;
+use edit::EditBuffer;
+use flashcards::Flashcards;
+use loading::Progress;
+use menu::{Item, Menu};
+use quiz::Quiz;
+use remap::{Move, Remap, SUGGESTIONS};
+use resolver::Conflict;
+use searcher::Searcher;
+pub use session::Session;
+use sprint::Sprint;
+use tree::{Action, Tree};
+use warnings::Warnings;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Search,
+    Normal,
+    Edit(Field),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Chord,
+    Rank,
+    NewWord,
+    NewRank,
+    Tags,
+    Note,
+    Delete,
+}
+
+impl Mode {
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Search => "SEARCH",
+            Mode::Normal => "NORMAL",
+            Mode::Edit(_) => "EDIT",
+        }
+    }
+
+    fn help(self) -> &'static str {
+        match self {
+            Mode::Search => "Type to search · Enter/Esc normal mode · rank<=N · added<Nd filter · Ctrl+W/U delete word/line · Ctrl+H clear · Ctrl+F matching",
+            Mode::Normal => {
+                "/ search · Enter details · e edit · r rank · n new word · Space mark · p pin · y copy · T tag · N note · Menu/right-click actions · P pinned first · x stop word · d delete · Backspace clear chord · j/k move · o sort · m remap · u undo remap · t tree · v columns · w warnings · g group · z expand · a sprint · l practice · L flashcards · Q quiz · S progress · C calibrate · c case · f matching · </> resize · q quit"
+            }
+            Mode::Edit(Field::Chord) => "Type chord keys · Backspace clear · Enter/Esc normal mode",
+            Mode::Edit(Field::Rank | Field::NewRank) => "Type new rank · Enter apply · Esc cancel",
+            Mode::Edit(Field::NewWord) => "Type new word · Enter continue · Esc cancel",
+            Mode::Edit(Field::Tags) => "Type tags separated by commas · Enter apply · Esc cancel",
+            Mode::Edit(Field::Note) => "Type note · Enter apply, empty to remove · Esc cancel",
+            Mode::Edit(Field::Delete) => "y delete · n/Esc cancel",
+        }
+    }
+}
+
+const TICK_RATE: Duration = Duration::from_millis(50);
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+const NARROW_WIDTH: u16 = 100;
+pub const PANE_RATIOS: RangeInclusive<u16> = 20..=80;
+
+enum Overlay {
+    Sprint(Sprint),
+    Calibration(Calibration),
+    Drill(Drill),
+    Flashcards(Flashcards),
+    Quiz(Quiz),
+    Charts(Charts),
+    Popup(Popup),
+    Tree(Tree),
+    Warnings(Warnings),
+    Remap(Remap),
+    Conflict(Conflict),
+    Columns(Picker),
+}
+
 pub struct Tui {
     terminal: Terminal<CrosstermBackend<Stdout>>,
-    words: Words,
-    rows: Vec<Row>,
-    search: String,
+    words: Arc<Words>,
+    searcher: Searcher,
+    uses: BTreeMap<Chord, usize>,
+    history: Option<BTreeMap<Chord, [usize; HISTORY_BUCKETS]>>,
+    layout: KeyboardLayout,
+    notation: Notation,
+    mode: Mode,
+    rows: Vec<Hit>,
+    offset: usize,
+    marked: BTreeSet<String>,
+    sort: Sort,
+    search: EditBuffer,
+    input: String,
+    new_word: String,
+    edit_origin: Option<(String, Chord)>,
+    message: Option<String>,
+    words_changed: bool,
+    edited: bool,
+    journal: Journal,
+    stop_words: WordSet,
+    stop_words_changed: bool,
+    blacklist: WordSet,
+    pinned: WordSet,
+    pinned_changed: bool,
+    pinned_first: bool,
+    case: Case,
+    matching: Matching,
+    pane_ratio: u16,
+    columns: Vec<Column>,
+    suffix_keys: Vec<(String, char)>,
     table_state: TableState,
+    overlay: Option<Overlay>,
+    model: Model,
+    coverage: Vec<(u64, f64)>,
+    practice: History,
+    practice_loaded: usize,
+    external: Option<Receiver<(String, Result<Vec<Chord>>)>>,
+    menu: Option<Menu>,
+    undo: Vec<Vec<Move>>,
+    grouped: bool,
+    expanded: BTreeSet<String>,
+    ticks: usize,
+    config: Config,
+    table_area: Rect,
+    last_click: Option<(Instant, usize)>,
 }
 
 impl Tui {
-    pub fn new(words: Words) -> Result<Self> {
+    pub fn new(config: &Config) -> Result<Self> {
         enable_raw_mode()?;
 
         let mut stdout = stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        execute!(
+            stdout,
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste
+        )?;
 
         let backend = CrosstermBackend::new(stdout);
         let terminal = Terminal::new(backend)?;
 
         Ok(Self {
             terminal,
-            words,
+            words: Arc::default(),
+            searcher: Searcher::spawn(),
+            uses: BTreeMap::new(),
+            history: None,
+            layout: KeyboardLayout::from_config(config),
+            notation: Notation::from_config(config),
+            mode: Mode::Search,
             rows: Vec::new(),
-            search: String::new(),
+            offset: 0,
+            marked: BTreeSet::new(),
+            sort: Sort::Relevance,
+            search: EditBuffer::default(),
+            input: String::new(),
+            new_word: String::new(),
+            edit_origin: None,
+            message: None,
+            words_changed: false,
+            edited: false,
+            journal: Journal::default(),
+            stop_words: WordSet::default(),
+            stop_words_changed: false,
+            blacklist: WordSet::default(),
+            pinned: WordSet::default(),
+            pinned_changed: false,
+            pinned_first: false,
+            case: Case::default(),
+            matching: Matching::default(),
+            pane_ratio: config.pane_ratio,
+            columns: config.columns.clone(),
+            suffix_keys: config.suffix_keys.clone(),
             table_state: TableState::new(),
+            overlay: None,
+            model: config.model,
+            coverage: Vec::new(),
+            practice: History::default(),
+            practice_loaded: 0,
+            external: None,
+            menu: None,
+            undo: Vec::new(),
+            grouped: false,
+            expanded: BTreeSet::new(),
+            ticks: 0,
+            config: config.clone(),
+            table_area: Rect::default(),
+            last_click: None,
         })
     }
 
@@ -54,153 +270,1545 @@ impl Tui {
         execute!(
             self.terminal.backend_mut(),
             LeaveAlternateScreen,
-            DisableMouseCapture
+            DisableMouseCapture,
+            DisableBracketedPaste
         )?;
 
         Ok(())
     }
 
-    pub fn run_event_loop(&mut self, chords: &mut Chords) -> Result<()> {
+    pub fn load<T: Send + 'static>(
+        &mut self,
+        task: impl FnOnce(&Progress) -> Result<T> + Send + 'static,
+    ) -> Result<Option<T>> {
+        let progress = Arc::new(Progress::default());
+        let handle = {
+            let progress = progress.clone();
+            thread::spawn(move || task(&progress))
+        };
+
+        while !handle.is_finished() {
+            self.terminal
+                .draw(|frame| loading::draw(frame, frame.area(), &progress, self.ticks))?;
+
+            if poll(TICK_RATE)? {
+                if let Event::Key(key) = read()? {
+                    if key.modifiers.contains(KeyModifiers::CONTROL)
+                        && key.code == KeyCode::Char('c')
+                    {
+                        return Ok(None);
+                    }
+                }
+            }
+            self.ticks = self.ticks.wrapping_add(1);
+        }
+
+        handle
+            .join()
+            .map_err(|_| Error::other("Loading thread panicked"))?
+            .map(Some)
+    }
+
+    pub fn open(
+        &mut self,
+        words: Words,
+        log: &Log,
+        stop_words: WordSet,
+        blacklist: WordSet,
+        pinned: WordSet,
+        session: Session,
+    ) {
+        self.words = Arc::new(words);
+        self.uses = log.uses();
+        self.history = (!log.is_empty()).then(|| log.history(clock::now()));
+        self.coverage = log.daily_coverage();
+        self.stop_words = stop_words;
+        self.blacklist = blacklist;
+        self.pinned = pinned;
+        self.search = EditBuffer::from(session.search);
+        self.case = session.case;
+        self.matching = session.matching;
+        self.sort = session.sort;
+        self.pinned_first = session.pinned_first;
+
         self.update_rows();
+        if let Some(selected) = session.selected {
+            if self.select_visible_word(&selected) {
+                self.mode = Mode::Normal;
+            }
+        }
+    }
+
+    pub fn session(&self) -> Session {
+        Session {
+            search: self.search.as_str().to_owned(),
+            case: self.case,
+            matching: self.matching,
+            sort: self.sort,
+            pinned_first: self.pinned_first,
+            selected: self.selected().map(|(word, _)| word.clone()),
+        }
+    }
 
+    pub fn pane_ratio(&self) -> u16 {
+        self.pane_ratio
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    pub fn open_practice(&mut self, history: History) {
+        self.practice_loaded = history.records().len();
+        self.practice = history;
+    }
+
+    pub fn new_practice_records(&self) -> &[Record] {
+        &self.practice.records()[self.practice_loaded..]
+    }
+
+    pub fn words(&self) -> &Words {
+        &self.words
+    }
+
+    pub fn words_changed(&self) -> bool {
+        self.words_changed
+    }
+
+    pub fn stop_words(&self) -> &WordSet {
+        &self.stop_words
+    }
+
+    pub fn stop_words_changed(&self) -> bool {
+        self.stop_words_changed
+    }
+
+    pub fn pinned(&self) -> &WordSet {
+        &self.pinned
+    }
+
+    pub fn pinned_changed(&self) -> bool {
+        self.pinned_changed
+    }
+
+    pub fn run_event_loop(&mut self, chords: &mut Chords) -> Result<()> {
+        self.journal = Journal::new(chords, &self.words);
+
+        let mut last_tick = Instant::now();
         loop {
             self.draw()?;
 
-            let event = read()?;
-            if let Event::Key(key) = event {
-                if self.handle_key(key, chords) {
-                    break;
+            let timeout = TICK_RATE.saturating_sub(last_tick.elapsed());
+            if poll(timeout)? {
+                match read()? {
+                    Event::Key(key) if self.handle_key(key, chords) => break,
+                    Event::Mouse(mouse) => self.handle_mouse(mouse, chords),
+                    Event::Paste(text) => self.handle_paste(&text),
+                    _ => {}
                 }
             }
+
+            // chord edits and overlays are journaled once they are finished
+            if self.edited && self.mode != Mode::Edit(Field::Chord) && self.overlay.is_none() {
+                self.edited = false;
+                self.journal.record(chords, &self.words);
+            }
+
+            if last_tick.elapsed() >= TICK_RATE {
+                self.tick(chords);
+                last_tick = Instant::now();
+            }
         }
 
         Ok(())
     }
 
+    pub fn journal(&mut self, chords: &Chords) -> &Journal {
+        self.journal.record(chords, &self.words);
+        &self.journal
+    }
+
+    fn tick(&mut self, chords: &Chords) {
+        self.ticks = self.ticks.wrapping_add(1);
+
+        if let Some(rows) = self.searcher.try_recv() {
+            self.set_rows(rows);
+        }
+        self.receive_external(chords);
+    }
+
+    fn receive_external(&mut self, chords: &Chords) {
+        let Some(external) = &self.external else {
+            return;
+        };
+        let (word, candidates) = match external.try_recv() {
+            Ok(received) => received,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.external = None;
+                return;
+            }
+        };
+        self.external = None;
+
+        match candidates {
+            Ok(candidates) => {
+                if let Some(Overlay::Remap(remap)) = &mut self.overlay {
+                    if remap.word() == word {
+                        remap.add_candidates(candidates, chords, &self.layout);
+                    }
+                }
+            }
+            Err(error) => self.message = Some(error.to_string()),
+        }
+    }
+
     pub fn draw(&mut self) -> Result<()> {
+        let prompt = self.prompt();
+
         self.terminal.draw(|frame| {
-            let layout =
-                Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).split(frame.area());
+            let area = frame.area();
+            match &mut self.overlay {
+                Some(Overlay::Sprint(sprint)) => {
+                    return sprint.draw(frame, area, &self.notation, &self.layout)
+                }
+                Some(Overlay::Calibration(calibration)) => {
+                    return calibration.draw(frame, area, &self.notation, self.model)
+                }
+                Some(Overlay::Drill(drill)) => {
+                    return drill.draw(frame, area, &self.notation, &self.practice)
+                }
+                Some(Overlay::Flashcards(flashcards)) => {
+                    return flashcards.draw(frame, area, &self.notation)
+                }
+                Some(Overlay::Quiz(quiz)) => {
+                    return quiz.draw(frame, area, &self.notation, &self.layout)
+                }
+                Some(Overlay::Charts(charts)) => return charts.draw(frame, area),
+                Some(Overlay::Popup(popup)) => return popup.draw(frame, area),
+                Some(Overlay::Tree(tree)) => {
+                    return tree.draw(frame, area, &self.words, &self.notation, &self.layout)
+                }
+                Some(Overlay::Warnings(warnings)) => return warnings.draw(frame, area),
+                Some(Overlay::Remap(remap)) => return remap.draw(frame, area, &self.notation),
+                Some(Overlay::Conflict(_) | Overlay::Columns(_)) | None => {}
+            }
+
+            let layout = Layout::vertical([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(frame.area());
 
             let text = Text::from(self.search.as_str());
-            let block =
+            let mut block =
                 Block::bordered().title(Span::from("Search chords").style(Style::new().bold()));
+            if self.searcher.is_pending() {
+                let spinner = SPINNER[self.ticks % SPINNER.len()];
+                block = block.title(Line::from(format!(" {spinner} searching ")).dim());
+            }
+            let block = block
+                .title(Line::from(format!(" {} · {} ", self.case, self.matching)).right_aligned());
             let paragraph = Paragraph::new(text).block(block);
             frame.render_widget(paragraph, layout[0]);
 
-            let widths = [
-                Constraint::Ratio(1, 3),
-                Constraint::Ratio(1, 3),
-                Constraint::Ratio(1, 3),
-            ];
-            let header = TableRow::new(["Rank", "Word", "Chord"]).style(Style::new().bold());
-            let block = Block::bordered();
-            let table = Table::new(&self.rows, widths)
+            let narrow = layout[1].width < NARROW_WIDTH;
+            let columns: Vec<_> = self
+                .columns
+                .iter()
+                .copied()
+                .filter(|column| !narrow || *column != Column::Rank)
+                .collect();
+            let direction = if narrow {
+                Direction::Vertical
+            } else {
+                Direction::Horizontal
+            };
+            let panes = Layout::new(
+                direction,
+                [
+                    Constraint::Percentage(self.pane_ratio),
+                    Constraint::Percentage(100 - self.pane_ratio),
+                ],
+            )
+            .split(layout[1]);
+
+            self.table_area = panes[0];
+            let header = TableRow::new(columns.iter().map(|column| column.header()))
+                .style(Style::new().bold());
+            let height = usize::from(panes[0].height.saturating_sub(3)).max(1);
+            let selected = self.table_state.selected();
+            if let Some(selected) = selected {
+                self.offset = self
+                    .offset
+                    .clamp(selected.saturating_sub(height - 1), selected);
+            }
+            self.offset = self.offset.min(self.rows.len().saturating_sub(height));
+
+            let visible: Vec<_> = self.rows[self.offset..]
+                .iter()
+                .take(height)
+                .filter_map(|hit| {
+                    Row::new(hit, &self.words, &self.notation, &self.layout, &self.uses)
+                })
+                .collect();
+            let selected = selected.and_then(|index| index.checked_sub(self.offset));
+            let row = selected.and_then(|index| visible.get(index));
+
+            let widths: Vec<_> = if narrow {
+                // fit everything but the word to its content, leaving the rest to the word
+                columns
+                    .iter()
+                    .map(|column| match column {
+                        Column::Word => Constraint::Fill(1),
+                        column => {
+                            let width = visible
+                                .iter()
+                                .map(|row| column.width(row))
+                                .fold(column.header().len(), usize::max);
+                            Constraint::Length(u16::try_from(width).unwrap_or(u16::MAX))
+                        }
+                    })
+                    .collect()
+            } else {
+                let count = u32::try_from(columns.len()).unwrap_or(u32::MAX);
+                vec![Constraint::Ratio(1, count); columns.len()]
+            };
+
+            let mut block = Block::bordered()
+                .title(Line::from(format!(" sorted by {} ", self.sort.name())).right_aligned());
+            if self.grouped {
+                block = block.title(Line::from(" grouped by lemma ").right_aligned());
+            }
+            if self.pinned_first {
+                block = block.title(Line::from(" pinned first ").right_aligned());
+            }
+            if let Some(violation) = row.and_then(|row| row.violation.as_ref()) {
+                block = block.title_bottom(
+                    Span::from(format!("Impossible chord: {violation}")).style(Style::new().red()),
+                );
+            }
+            let rows = visible.iter().map(|row| {
+                let cells = columns.iter().map(|column| column.cell(row));
+                if self.marked.contains(row.word) {
+                    TableRow::new(cells).style(Style::new().magenta().bold())
+                } else if self.pinned.contains(row.word) {
+                    TableRow::new(cells).style(Style::new().cyan())
+                } else if self.stop_words.contains(row.word) {
+                    TableRow::new(cells).style(Style::new().dim())
+                } else {
+                    TableRow::new(cells)
+                }
+            });
+            let table = Table::new(rows, widths)
                 .block(block)
                 .header(header)
                 .row_highlight_style(Style::new().reversed());
-            frame.render_stateful_widget(table, layout[1], &mut self.table_state);
+            let mut state = TableState::new().with_selected(selected);
+            frame.render_stateful_widget(table, panes[0], &mut state);
+
+            let history = self.history.as_ref().map(|history| {
+                row.and_then(|row| history.get(row.chord))
+                    .copied()
+                    .unwrap_or_default()
+            });
+            detail::draw(frame, panes[1], row, &self.layout, history);
+
+            let mut status = vec![Span::from(format!(" {} ", self.mode.name()))
+                .reversed()
+                .bold()];
+            if let Some(prompt) = &prompt {
+                status.push(Span::from(format!(" {prompt}{}", self.input)));
+            } else {
+                status.push(Span::from(format!(" {}", self.mode.help())));
+            }
+            if let Some(message) = &self.message {
+                status.push(Span::from(format!("  {message}")).red());
+            }
+            frame.render_widget(Line::from(status), layout[2]);
+
+            match &mut self.overlay {
+                Some(Overlay::Conflict(conflict)) => {
+                    conflict.draw(frame, frame.area(), &self.notation);
+                }
+                Some(Overlay::Columns(picker)) => picker.draw(frame, frame.area(), &self.columns),
+                _ => {}
+            }
+            if let Some(menu) = &mut self.menu {
+                menu.draw(frame, frame.area());
+            }
         })?;
 
-        if self.table_state.selected().is_none() {
-            let x: u16 = self.search.len().try_into().unwrap_or(u16::MAX - 1);
+        if self.overlay.is_none() && self.menu.is_none() && self.mode == Mode::Search {
+            let x: u16 = self
+                .search
+                .cursor_width()
+                .try_into()
+                .unwrap_or(u16::MAX - 1);
             self.terminal.set_cursor_position((x + 1, 1))?;
             self.terminal.show_cursor()?;
+        } else if let Some(prompt) = prompt {
+            let x = self.mode.name().width() + 3 + prompt.width() + self.input.width();
+            let y = self.terminal.get_frame().area().bottom().saturating_sub(1);
+            self.terminal
+                .set_cursor_position((x.try_into().unwrap_or(u16::MAX), y))?;
+            self.terminal.show_cursor()?;
         }
 
         Ok(())
     }
 
+    fn prompt(&self) -> Option<String> {
+        let word = self.selected().map(|(word, _)| word);
+
+        match self.mode {
+            Mode::Edit(Field::Rank) => Some(format!("New rank for {}: ", word?)),
+            Mode::Edit(Field::NewWord) => Some("New word: ".to_owned()),
+            Mode::Edit(Field::NewRank) => {
+                Some(format!("Rank for {} (empty to append): ", self.new_word))
+            }
+            Mode::Edit(Field::Tags) => Some(format!("Tags for {}: ", word?)),
+            Mode::Edit(Field::Note) => Some(format!("Note for {}: ", word?)),
+            Mode::Edit(Field::Delete) => match self.deletion_targets().len() {
+                1 => Some(format!("Delete {}? ", word?)),
+                count => Some(format!("Delete {count} words? ")),
+            },
+            _ => None,
+        }
+    }
+
     fn handle_key(&mut self, key: KeyEvent, chords: &mut Chords) -> bool {
         if key.kind != KeyEventKind::Press {
             return false;
         }
+        self.message = None;
 
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return true;
+        }
+
+        if let Some(overlay) = self.overlay.take() {
+            self.overlay = self.handle_overlay_key(overlay, key, chords);
+
+            return false;
+        }
+
+        if let Some(menu) = &mut self.menu {
+            match menu.handle_key(key) {
+                Some(menu::Action::Choose(item)) => {
+                    self.menu = None;
+                    self.choose(item, chords);
+                }
+                Some(menu::Action::Close) => self.menu = None,
+                None => {}
+            }
+
+            return false;
+        }
+
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            match key.code {
+                KeyCode::Char('h') => {
+                    // ctrl-backspace
+                    self.search.clear();
+                    self.search_in_background();
+                }
+                KeyCode::Char('w') if self.mode == Mode::Search => {
+                    self.search.delete_word();
+                    self.search_in_background();
+                }
+                KeyCode::Char('u') if self.mode == Mode::Search => {
+                    self.search.kill_line();
+                    self.search_in_background();
+                }
+                KeyCode::Char('t') => self.toggle_case(),
+                KeyCode::Char('f') => self.toggle_matching(),
+                KeyCode::Char('a') => self.start_sprint(chords),
+                _ => {}
+            }
+
+            return false;
+        }
+
+        match self.mode {
+            Mode::Search => self.handle_search_key(key),
+            Mode::Normal => return self.handle_normal_key(key, chords),
+            Mode::Edit(Field::Chord) => self.handle_edit_key(key, chords),
+            Mode::Edit(Field::Rank) => self.handle_rank_key(key),
+            Mode::Edit(Field::NewWord) => self.handle_new_word_key(key),
+            Mode::Edit(Field::NewRank) => self.handle_new_rank_key(key),
+            Mode::Edit(Field::Tags) => self.handle_tags_key(key, chords),
+            Mode::Edit(Field::Note) => self.handle_note_key(key, chords),
+            Mode::Edit(Field::Delete) => self.handle_delete_key(key, chords),
+        }
+
+        false
+    }
+
+    fn handle_overlay_key(
+        &mut self,
+        overlay: Overlay,
+        key: KeyEvent,
+        chords: &mut Chords,
+    ) -> Option<Overlay> {
+        match overlay {
+            Overlay::Conflict(conflict) => match Conflict::handle_key(key) {
+                Some(resolution) => {
+                    self.edited = true;
+                    conflict.resolve(&resolution, self.searcher.edit(&mut self.words), chords);
+                    self.update_rows();
+                    None
+                }
+                None => Some(Overlay::Conflict(conflict)),
+            },
+            Overlay::Sprint(mut sprint) => {
+                self.edited = true;
+                if sprint.handle_key(
+                    key,
+                    self.searcher.edit(&mut self.words),
+                    chords,
+                    &self.layout,
+                ) {
+                    self.update_rows();
+                    return None;
+                }
+                Some(Overlay::Sprint(sprint))
+            }
+            Overlay::Calibration(mut calibration) => {
+                let closed = calibration.handle_key(key, &mut self.model);
+                (!closed).then_some(Overlay::Calibration(calibration))
+            }
+            Overlay::Drill(mut drill) => {
+                let closed = drill.handle_key(key, &mut self.practice);
+                (!closed).then_some(Overlay::Drill(drill))
+            }
+            Overlay::Flashcards(mut flashcards) => {
+                let closed = flashcards.handle_key(key, &mut self.practice);
+                (!closed).then_some(Overlay::Flashcards(flashcards))
+            }
+            Overlay::Quiz(mut quiz) => {
+                let closed = quiz.handle_key(key, &mut self.practice);
+                (!closed).then_some(Overlay::Quiz(quiz))
+            }
+            Overlay::Charts(charts) => {
+                (!Charts::handle_key(key)).then_some(Overlay::Charts(charts))
+            }
+            Overlay::Popup(popup) => match key.code {
+                KeyCode::Char('e') => {
+                    self.mode = Mode::Edit(Field::Chord);
+                    None
+                }
+                KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => None,
+                _ => Some(Overlay::Popup(popup)),
+            },
+            Overlay::Tree(mut tree) => {
+                match tree.handle_key(key, &self.words, &self.notation, &self.layout) {
+                    Some(Action::Open(word)) => {
+                        self.select_word(&word);
+                        self.mode = Mode::Normal;
+                        None
+                    }
+                    Some(Action::Close) => None,
+                    None => Some(Overlay::Tree(tree)),
+                }
+            }
+            Overlay::Warnings(mut warnings) => match warnings.handle_key(key) {
+                Some(warnings::Action::Open(word)) => {
+                    self.select_word(&word);
+                    self.mode = Mode::Normal;
+                    None
+                }
+                Some(warnings::Action::Suppress(index)) => {
+                    self.suppress_warning(&mut warnings, index, chords);
+                    Some(Overlay::Warnings(warnings))
+                }
+                Some(warnings::Action::Close) => None,
+                None => Some(Overlay::Warnings(warnings)),
+            },
+            Overlay::Remap(mut remap) => match remap.handle_key(key, chords, &self.layout) {
+                Some(remap::Action::Apply(moves)) => {
+                    self.apply_moves(&moves, chords);
+                    self.message = Some(format!("Remapped {} entries · u undo", moves.len()));
+                    self.undo.push(moves);
+                    None
+                }
+                Some(remap::Action::Close) => None,
+                None => Some(Overlay::Remap(remap)),
+            },
+            Overlay::Columns(mut picker) => {
+                let closed = picker.handle_key(key, &mut self.columns);
+                (!closed).then_some(Overlay::Columns(picker))
+            }
+        }
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) {
         match key.code {
             KeyCode::Char(char) => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    match key.code {
-                        KeyCode::Char('c') => {
-                            // ctrl-c
-                            return true;
-                        }
-                        KeyCode::Char('h') => {
-                            // ctrl-backspace
-                            self.search.clear();
-                            self.update_rows();
-                        }
-                        _ => {}
+                self.search.insert(char);
+                self.search_in_background();
+            }
+            KeyCode::Backspace => {
+                self.search.backspace();
+                self.search_in_background();
+            }
+            KeyCode::Delete => {
+                self.search.delete();
+                self.search_in_background();
+            }
+            KeyCode::Left => self.search.left(),
+            KeyCode::Right => self.search.right(),
+            KeyCode::Home => self.search.home(),
+            KeyCode::End => self.search.end(),
+            KeyCode::Enter => {
+                self.mode = Mode::Normal;
+                if self.table_state.selected().is_none() {
+                    self.select_next_row();
+                }
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            KeyCode::Down | KeyCode::Tab => {
+                self.mode = Mode::Normal;
+                self.select_next_row();
+            }
+            _ => {}
+        }
+    }
+
+    fn has_overlay(&self) -> bool {
+        self.overlay.is_some()
+    }
+
+    fn handle_paste(&mut self, text: &str) {
+        if self.has_overlay() || self.menu.is_some() || matches!(self.mode, Mode::Edit(_)) {
+            return;
+        }
+
+        // pasted line breaks would be invisible in the single line search field
+        let text: String = text
+            .trim()
+            .chars()
+            .map(|char| if char.is_control() { ' ' } else { char })
+            .collect();
+        self.mode = Mode::Search;
+        self.search.insert_str(&text);
+        self.search_in_background();
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent, chords: &mut Chords) {
+        if let Some(menu) = &self.menu {
+            if let MouseEventKind::Down(_) = mouse.kind {
+                match menu.handle_click(mouse.column, mouse.row) {
+                    menu::Action::Choose(item) => {
+                        self.menu = None;
+                        self.choose(item, chords);
                     }
+                    menu::Action::Close => self.menu = None,
+                }
+            }
+
+            return;
+        }
+
+        if self.has_overlay() || matches!(self.mode, Mode::Edit(_)) {
+            return;
+        }
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.select_next_row(),
+            MouseEventKind::ScrollUp => self.select_previous_row(),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let Some(index) = self.row_at(mouse.column, mouse.row) else {
+                    return;
+                };
+
+                let now = Instant::now();
+                let double_click = self.last_click.is_some_and(|(time, clicked)| {
+                    clicked == index && now.duration_since(time) <= DOUBLE_CLICK
+                });
+
+                self.mode = Mode::Normal;
+                self.table_state.select(Some(index));
+                if double_click {
+                    self.last_click = None;
+                    self.mode = Mode::Edit(Field::Chord);
                 } else {
-                    match self.get_current_row() {
-                        Some(row) => {
-                            let chord = &mut row.chord;
-                            chords.remove(chord);
-
-                            if chord.insert(char) {
-                                let chord = chord.clone();
-                                let word = row.word.clone();
-
-                                chords.insert(chord.clone(), word.clone());
-                                self.words.update_chord(word, chord);
-                                self.update_rows();
-                            } else if !chord.is_empty() {
-                                chords.insert(chord.clone(), row.word.clone());
-                            }
+                    self.last_click = Some((now, index));
+                }
+            }
+            MouseEventKind::Down(MouseButton::Right) => {
+                if let Some(index) = self.row_at(mouse.column, mouse.row) {
+                    self.mode = Mode::Normal;
+                    self.table_state.select(Some(index));
+                    self.menu = Some(Menu::new((mouse.column, mouse.row)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn row_at(&self, column: u16, row: u16) -> Option<usize> {
+        let area = self.table_area;
+        let first = area.y + 2;
+        let inside = (area.x + 1..area.right().saturating_sub(1)).contains(&column)
+            && (first..area.bottom().saturating_sub(1)).contains(&row);
+
+        let index = self.offset + usize::from(row.checked_sub(first)?);
+        (inside && index < self.rows.len()).then_some(index)
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent, chords: &mut Chords) -> bool {
+        match key.code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Char('e') => {
+                if self.table_state.selected().is_none() {
+                    self.select_next_row();
+                }
+                if self.table_state.selected().is_some() {
+                    self.mode = Mode::Edit(Field::Chord);
+                }
+            }
+            KeyCode::Enter => self.open_popup(chords),
+            KeyCode::Char('r') => {
+                if let Some((_, entry)) = self.selected() {
+                    self.input = entry.rank.map_or(String::new(), |rank| rank.to_string());
+                    self.mode = Mode::Edit(Field::Rank);
+                }
+            }
+            KeyCode::Char('n') => {
+                self.input.clear();
+                self.mode = Mode::Edit(Field::NewWord);
+            }
+            KeyCode::Char('T') => self.start_tagging(),
+            KeyCode::Char('N') => self.start_note(),
+            KeyCode::Char('o') => {
+                self.sort = self.sort.next();
+                self.update_rows_keeping_selection();
+            }
+            KeyCode::Char('a') => self.start_sprint(chords),
+            KeyCode::Char('l') => {
+                self.overlay = Drill::new(&self.words, &self.practice).map(Overlay::Drill);
+                if self.overlay.is_none() {
+                    self.message = Some(String::from("No chords to practice"));
+                }
+            }
+            KeyCode::Char('L') => {
+                self.overlay =
+                    Flashcards::new(&self.words, &self.practice).map(Overlay::Flashcards);
+                if self.overlay.is_none() {
+                    self.message = Some(String::from("No chords to review"));
+                }
+            }
+            KeyCode::Char('Q') => {
+                self.overlay = Quiz::new(&self.words, &self.practice).map(Overlay::Quiz);
+                if self.overlay.is_none() {
+                    self.message = Some(String::from("No chords to quiz"));
+                }
+            }
+            KeyCode::Char('S') => {
+                let charts = Charts::new(&self.practice, &self.coverage, clock::now());
+                self.overlay = Some(Overlay::Charts(charts));
+            }
+            KeyCode::Char('C') => {
+                self.overlay = Calibration::new(&self.words).map(Overlay::Calibration);
+                if self.overlay.is_none() {
+                    self.message = Some(String::from("No words to calibrate with"));
+                }
+            }
+            KeyCode::Char('t') => self.overlay = Some(Overlay::Tree(Tree::new())),
+            KeyCode::Char('v') => self.overlay = Some(Overlay::Columns(Picker::new())),
+            KeyCode::Char('w') => {
+                let warnings = Warnings::new(self.validate(chords));
+                self.overlay = Some(Overlay::Warnings(warnings));
+            }
+            KeyCode::Char('m') => self.start_remap(chords),
+            KeyCode::Char('u') => self.undo_remap(chords),
+            KeyCode::Char('g') => {
+                self.grouped = !self.grouped;
+                self.update_rows_keeping_selection();
+            }
+            KeyCode::Char('z') if self.grouped => self.toggle_group(),
+            KeyCode::Char('c') => self.toggle_case(),
+            KeyCode::Char('f') => self.toggle_matching(),
+            KeyCode::Char('<') => {
+                self.pane_ratio = self.pane_ratio.saturating_sub(5).max(*PANE_RATIOS.start());
+            }
+            KeyCode::Char('>') => self.pane_ratio = (self.pane_ratio + 5).min(*PANE_RATIOS.end()),
+            KeyCode::Char('k') | KeyCode::Up => self.select_previous_row(),
+            KeyCode::Char('j') | KeyCode::Down | KeyCode::Tab => self.select_next_row(),
+            KeyCode::Backspace => self.clear_chord(chords),
+            KeyCode::Char(' ') => self.toggle_mark(),
+            KeyCode::Char('p') => self.toggle_pin(),
+            KeyCode::Char('y') => self.copy_selected(),
+            KeyCode::Menu => self.open_menu(),
+            KeyCode::Char('P') => {
+                self.pinned_first = !self.pinned_first;
+                self.update_rows_keeping_selection();
+            }
+            KeyCode::Char('x') => {
+                if let Some((word, _)) = self.selected() {
+                    let word = word.clone();
+                    self.stop_words.toggle(&word);
+                    self.stop_words_changed = true;
+                    self.update_rows_keeping_selection();
+                }
+            }
+            KeyCode::Char('d') if !self.deletion_targets().is_empty() => {
+                self.mode = Mode::Edit(Field::Delete);
+            }
+            KeyCode::Esc => {
+                self.marked.clear();
+                self.unselect_row();
+            }
+            _ => {}
+        }
+
+        false
+    }
+
+    fn handle_edit_key(&mut self, key: KeyEvent, chords: &mut Chords) {
+        if let (KeyCode::Char(_) | KeyCode::Backspace, Some((word, entry))) =
+            (key.code, self.selected())
+        {
+            if !matches!(&self.edit_origin, Some((edited, _)) if edited == word) {
+                self.edit_origin = Some((word.clone(), entry.chord.clone()));
+            }
+        }
+
+        match key.code {
+            KeyCode::Char(char) => {
+                let Some((word, entry)) = self.selected() else {
+                    return;
+                };
+
+                let previous = entry.chord.clone();
+                let mut chord = previous.clone();
+                let word = word.clone();
+
+                if chord.insert(char) {
+                    match chords.get(&chord) {
+                        Some(existing) if *existing != word => {
+                            self.overlay = Some(Overlay::Conflict(Conflict {
+                                chord,
+                                word,
+                                existing: existing.clone(),
+                                previous,
+                            }));
                         }
-                        None => {
-                            self.search.push(char);
+                        _ => {
+                            let too_many =
+                                self.layout
+                                    .violations(&chord)
+                                    .into_iter()
+                                    .find(|violation| {
+                                        matches!(violation, Violation::TooManyKeysPressed { .. })
+                                    });
+                            if let Some(violation) = too_many {
+                                self.message = Some(format!("Warning: {violation}"));
+                            }
+
+                            chords.remove(&previous);
+                            chords.insert(chord.clone(), word.clone());
+                            self.edited = true;
+                            self.searcher
+                                .edit(&mut self.words)
+                                .update_chord(word, chord);
                             self.update_rows();
                         }
                     }
                 }
             }
+            KeyCode::Backspace => self.clear_chord(chords),
+            KeyCode::Enter | KeyCode::Esc => {
+                self.finish_chord_edit(chords);
+                self.mode = Mode::Normal;
+            }
+            KeyCode::Up => {
+                self.finish_chord_edit(chords);
+                self.select_previous_row();
+            }
+            KeyCode::Down => {
+                self.finish_chord_edit(chords);
+                self.select_next_row();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_rank_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(char) if char.is_ascii_digit() => self.input.push(char),
             KeyCode::Backspace => {
-                match self.get_current_row() {
-                    Some(row) => {
-                        chords.remove(&row.chord);
-                        row.chord.clear();
+                self.input.pop();
+            }
+            KeyCode::Enter => {
+                let Some(word) = self.selected().map(|(word, _)| word.clone()) else {
+                    self.mode = Mode::Normal;
+                    return;
+                };
 
-                        let word = row.word.clone();
-                        let chord = row.chord.clone();
+                let ranked = self.words.ranked();
+                let max = if self.words.rank(&word).is_some() {
+                    ranked
+                } else {
+                    ranked + 1
+                };
 
-                        self.words.update_chord(word, chord);
+                match self.input.parse::<NonZeroUsize>() {
+                    Ok(rank) if rank.get() <= max => {
+                        if let Some(before) =
+                            self.words.rank(&word).filter(|&before| before != rank)
+                        {
+                            self.journal.push(Change::Rank {
+                                word: word.clone(),
+                                before,
+                                after: rank,
+                            });
+                        }
+                        self.searcher.edit(&mut self.words).set_rank(&word, rank);
+                        self.edited = true;
+                        self.words_changed = true;
+                        self.mode = Mode::Normal;
+                        self.update_rows_keeping_selection();
                     }
-                    None => {
-                        self.search.pop();
+                    _ => self.message = Some(format!("Rank must be between 1 and {max}")),
+                }
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    fn handle_new_word_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(char) if !char.is_whitespace() => self.input.push(char),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Enter if self.input.is_empty() => self.mode = Mode::Normal,
+            KeyCode::Enter => {
+                let word = self.config.normalization.apply(&self.input).into_owned();
+                self.input.clear();
+
+                if self.words.contains(&word) {
+                    self.message = Some(format!("{word} is already in the word list"));
+                    self.select_word(&word);
+                    self.mode = Mode::Normal;
+                } else {
+                    self.new_word = word;
+                    self.mode = Mode::Edit(Field::NewRank);
+                }
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    fn handle_new_rank_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(char) if char.is_ascii_digit() => self.input.push(char),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Enter => {
+                let max = self.words.ranked() + 1;
+                let rank = if self.input.is_empty() {
+                    None
+                } else {
+                    match self.input.parse::<NonZeroUsize>() {
+                        Ok(rank) if rank.get() <= max => Some(rank),
+                        _ => {
+                            self.message = Some(format!("Rank must be between 1 and {max}"));
+                            return;
+                        }
                     }
+                };
+
+                let word = mem::take(&mut self.new_word);
+                self.searcher
+                    .edit(&mut self.words)
+                    .insert(word.clone(), rank);
+                self.words_changed = true;
+                self.edited = true;
+                self.select_word(&word);
+                self.mode = Mode::Edit(Field::Chord);
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    fn handle_delete_key(&mut self, key: KeyEvent, chords: &mut Chords) {
+        if key.code == KeyCode::Char('y') {
+            for word in self.deletion_targets() {
+                if let Some(entry) = self.searcher.edit(&mut self.words).remove(&word) {
+                    chords.remove(&entry.chord);
+                }
+            }
+
+            let selected = self.table_state.selected();
+            self.edited = true;
+            self.marked.clear();
+            self.words_changed = true;
+            self.update_rows();
+            self.table_state
+                .select(selected.map(|index| index.min(self.rows.len().saturating_sub(1))));
+        }
+
+        self.mode = Mode::Normal;
+    }
+
+    fn toggle_mark(&mut self) {
+        if let Some(word) = self.selected().map(|(word, _)| word.clone()) {
+            if !self.marked.remove(&word) {
+                self.marked.insert(word);
+            }
+            self.select_next_row();
+        }
+    }
+
+    fn toggle_pin(&mut self) {
+        if let Some((word, _)) = self.selected() {
+            let word = word.clone();
+            self.pinned.toggle(&word);
+            self.pinned_changed = true;
+            if self.pinned_first {
+                self.update_rows_keeping_selection();
+            }
+        }
+    }
+
+    fn copy_selected(&mut self) {
+        let Some((word, entry)) = self.selected() else {
+            return;
+        };
+        let text = format!("{word}: {}", self.notation.format(&entry.chord));
+
+        // OSC 52 lets the terminal set the system clipboard, also over SSH
+        let sequence = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+        let backend = self.terminal.backend_mut();
+        self.message = Some(
+            match backend
+                .write_all(sequence.as_bytes())
+                .and_then(|()| backend.flush())
+            {
+                Ok(()) => format!("Copied {text}"),
+                Err(error) => format!("Cannot copy: {error}"),
+            },
+        );
+    }
+
+    fn open_menu(&mut self) {
+        let Some(selected) = self.table_state.selected() else {
+            return;
+        };
+        let row = selected.saturating_sub(self.offset) + 2;
+        let row = u16::try_from(row).unwrap_or(u16::MAX);
+
+        self.menu = Some(Menu::new((
+            self.table_area.x + 2,
+            self.table_area.y.saturating_add(row).saturating_add(1),
+        )));
+    }
+
+    fn choose(&mut self, item: Item, chords: &mut Chords) {
+        match item {
+            Item::Edit => self.mode = Mode::Edit(Field::Chord),
+            Item::Delete if !self.deletion_targets().is_empty() => {
+                self.mode = Mode::Edit(Field::Delete);
+            }
+            Item::Delete => {}
+            Item::Copy => self.copy_selected(),
+            Item::Mark => self.toggle_mark(),
+            Item::Pin => self.toggle_pin(),
+            Item::Suggest => self.start_remap(chords),
+            Item::Tag => self.start_tagging(),
+        }
+    }
+
+    fn start_tagging(&mut self) {
+        let Some((_, entry)) = self.selected() else {
+            return;
+        };
+        if entry.chord.is_empty() {
+            self.message = Some(String::from("Only chorded words can be tagged"));
+            return;
+        }
+
+        let tags: Vec<_> = entry.metadata.tags.iter().map(String::as_str).collect();
+        self.input = tags.join(", ");
+        self.mode = Mode::Edit(Field::Tags);
+    }
+
+    fn handle_tags_key(&mut self, key: KeyEvent, chords: &mut Chords) {
+        match key.code {
+            KeyCode::Char(char) => self.input.push(char),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Enter => {
+                let Some((word, entry)) = self.selected() else {
+                    self.mode = Mode::Normal;
+                    return;
+                };
+                let word = word.clone();
+                let mut metadata = entry.metadata.clone();
+
+                let tags = self
+                    .input
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty());
+                metadata.tags.clear();
+                for tag in tags {
+                    let Some(tag) = parse_tag(tag) else {
+                        self.message = Some(format!("Invalid tag {tag}"));
+                        return;
+                    };
+                    metadata.tags.insert(tag);
                 }
-                self.update_rows();
+
+                self.set_metadata(&word, metadata, chords);
             }
-            KeyCode::Esc => self.unselect_row(),
-            KeyCode::Up => self.select_previous_row(),
-            KeyCode::Down => self.select_next_row(),
-            KeyCode::Tab => self.select_next_row(),
+            KeyCode::Esc => self.mode = Mode::Normal,
             _ => {}
         }
+    }
 
-        false
+    fn start_note(&mut self) {
+        let Some((_, entry)) = self.selected() else {
+            return;
+        };
+        if entry.chord.is_empty() {
+            self.message = Some(String::from("Only chorded words can have a note"));
+            return;
+        }
+
+        self.input = entry.metadata.note.clone().unwrap_or_default();
+        self.mode = Mode::Edit(Field::Note);
     }
 
-    fn update_rows(&mut self) {
-        self.rows = self
-            .words
+    fn handle_note_key(&mut self, key: KeyEvent, chords: &mut Chords) {
+        match key.code {
+            KeyCode::Char('|') => self.message = Some(String::from("Notes cannot contain |")),
+            KeyCode::Char(char) => self.input.push(char),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Enter => {
+                let Some((word, entry)) = self.selected() else {
+                    self.mode = Mode::Normal;
+                    return;
+                };
+                let word = word.clone();
+                let mut metadata = entry.metadata.clone();
+                metadata.note = parse_note(&self.input);
+
+                self.set_metadata(&word, metadata, chords);
+            }
+            KeyCode::Esc => self.mode = Mode::Normal,
+            _ => {}
+        }
+    }
+
+    fn set_metadata(&mut self, word: &str, metadata: Metadata, chords: &mut Chords) {
+        chords.set_metadata(word, metadata.clone());
+        self.searcher
+            .edit(&mut self.words)
+            .set_metadata(word, metadata);
+        self.mode = Mode::Normal;
+        self.update_rows_keeping_selection();
+    }
+
+    fn deletion_targets(&self) -> Vec<String> {
+        if self.marked.is_empty() {
+            self.selected()
+                .map(|(word, _)| word.clone())
+                .into_iter()
+                .collect()
+        } else {
+            self.marked.iter().cloned().collect()
+        }
+    }
+
+    fn clear_chord(&mut self, chords: &mut Chords) {
+        if let Some((word, entry)) = self.selected() {
+            chords.remove(&entry.chord);
+
+            let word = word.clone();
+            self.edited = true;
+            self.searcher
+                .edit(&mut self.words)
+                .update_chord(word, Chord::default());
+            self.update_rows();
+        }
+    }
+
+    fn finish_chord_edit(&mut self, chords: &mut Chords) {
+        let Some((word, origin)) = self.edit_origin.take() else {
+            return;
+        };
+
+        let changed = self
+            .selected()
+            .is_some_and(|(selected, entry)| *selected == word && entry.chord != origin);
+        if changed {
+            self.derive_inflections(chords);
+        }
+    }
+
+    fn derive_inflections(&mut self, chords: &mut Chords) {
+        let Some((word, entry)) = self.selected() else {
+            return;
+        };
+        if entry.chord.is_empty() || self.suffix_keys.is_empty() {
+            return;
+        }
+
+        let derived = inflection::derive(
+            word,
+            &entry.chord,
+            &self.suffix_keys,
+            &self.words,
+            chords,
+            &self.layout,
+        );
+        let mut assigned = Vec::new();
+        let mut conflicts = Vec::new();
+        for derived in derived {
+            match derived {
+                Derived::Assigned { word, chord } => {
+                    assigned.push(format!("{word} ({})", self.notation.format(&chord)));
+                    chords.insert(chord.clone(), word.clone());
+                    self.edited = true;
+                    self.searcher
+                        .edit(&mut self.words)
+                        .update_chord(word, chord);
+                }
+                Derived::Conflict { word, existing } => {
+                    conflicts.push(format!("{word} (chord taken by {existing})"));
+                }
+                Derived::Impossible { word, violation } => {
+                    conflicts.push(format!("{word} ({violation})"));
+                }
+            }
+        }
+
+        let mut message = Vec::new();
+        if !assigned.is_empty() {
+            message.push(format!("Derived {}", assigned.join(", ")));
+            self.update_rows_keeping_selection();
+        }
+        if !conflicts.is_empty() {
+            message.push(format!("Skipped {}", conflicts.join(", ")));
+        }
+        if !message.is_empty() {
+            self.message = Some(message.join(" · "));
+        }
+    }
+
+    fn toggle_group(&mut self) {
+        let Some((word, _)) = self.selected() else {
+            return;
+        };
+
+        let lemma = lemmas::lemma_of(word, &self.words);
+        if !self.expanded.remove(&lemma) {
+            self.expanded.insert(lemma.clone());
+        }
+        self.update_rows();
+        self.select_visible_word(&lemma);
+    }
+
+    fn toggle_case(&mut self) {
+        self.case = self.case.next();
+        self.search_in_background();
+    }
+
+    fn toggle_matching(&mut self) {
+        self.matching = self.matching.next();
+        self.search_in_background();
+    }
+
+    fn open_popup(&mut self, chords: &Chords) {
+        if self.table_state.selected().is_none() {
+            self.select_next_row();
+        }
+        let Some((word, entry)) = self.selected() else {
+            return;
+        };
+
+        let history = self
+            .history
+            .as_ref()
+            .map(|history| history.get(&entry.chord).copied().unwrap_or_default());
+        let mut popup = Popup::new(
+            word,
+            entry,
+            &self.notation,
+            &self.layout,
+            self.words.frequency(entry),
+            self.uses.get(&entry.chord).copied(),
+            history,
+        );
+        if !entry.chord.is_empty() {
+            let conflicts = self
+                .validate(chords)
+                .iter()
+                .filter(|warning| warning.chords().contains(&&entry.chord))
+                .map(ToString::to_string)
+                .collect();
+            popup.section("Conflicts", conflicts);
+        }
+        let alternatives = suggest(word, chords, &self.layout, &self.blacklist, SUGGESTIONS)
             .iter()
-            .filter_map(|(word, entry)| {
-                if !word.contains(&self.search) {
-                    return None;
+            .map(|suggestion| {
+                format!(
+                    "{} (difficulty {})",
+                    self.notation.format(&suggestion.chord),
+                    suggestion.score
+                )
+            })
+            .collect();
+        popup.section("Alternatives", alternatives);
+
+        self.overlay = Some(Overlay::Popup(popup));
+    }
+
+    fn start_sprint(&mut self, chords: &Chords) {
+        self.overlay = Sprint::new(
+            &self.words,
+            chords,
+            &self.layout,
+            &self.stop_words,
+            &self.blacklist,
+            self.config.cutoff,
+        )
+        .map(Overlay::Sprint);
+    }
+
+    fn query(&self) -> Query {
+        let search = self.config.normalization.apply(self.search.as_str());
+
+        Query::new(&search, self.case, self.matching).with_cutoff(self.config.cutoff)
+    }
+
+    fn search_in_background(&mut self) {
+        self.searcher.search(&self.words, self.query(), self.sort);
+    }
+
+    fn update_rows(&mut self) {
+        self.searcher.cancel();
+
+        let rows = search(&self.words, &self.query(), self.sort, || false);
+        self.set_rows(rows.unwrap_or_default());
+    }
+
+    fn set_rows(&mut self, mut rows: Vec<Hit>) {
+        // unchorded stop words only show up when searched for, so they can be unmarked
+        if self.search.as_str().is_empty() {
+            rows.retain(|hit| {
+                self.words.get_index(hit.index).is_none_or(|(word, entry)| {
+                    !entry.chord.is_empty() || !self.stop_words.contains(word)
+                })
+            });
+        }
+        if self.sort == Sort::Difficulty {
+            self.sort_by_difficulty(&mut rows);
+        }
+        let mut rows = if self.grouped {
+            lemmas::group(rows, &self.words, &self.expanded)
+        } else {
+            rows
+        };
+        if self.pinned_first {
+            rows = self.pin_rows(rows);
+        }
+        self.rows = rows;
+
+        if let Some(selected) = self.table_state.selected() {
+            let last = self.rows.len().checked_sub(1);
+            self.table_state.select(last.map(|last| selected.min(last)));
+        }
+    }
+
+    fn sort_by_difficulty(&self, rows: &mut [Hit]) {
+        rows.sort_by_cached_key(|hit| {
+            let chord = self
+                .words
+                .get_index(hit.index)
+                .map(|(_, entry)| &entry.chord);
+            match chord {
+                Some(chord) if !chord.is_empty() => {
+                    (false, Reverse(difficulty(chord, &self.layout)))
                 }
+                _ => (true, Reverse(0)),
+            }
+        });
+    }
+
+    fn pin_rows(&self, rows: Vec<Hit>) -> Vec<Hit> {
+        let (mut pinned, unpinned): (Vec<_>, Vec<_>) = rows.into_iter().partition(|hit| {
+            self.words
+                .get_index(hit.index)
+                .is_some_and(|(word, _)| self.pinned.contains(word))
+        });
+
+        let mut missing: Vec<_> = self
+            .pinned
+            .iter()
+            .filter_map(|word| self.words.index_of(word))
+            .filter(|index| !pinned.iter().any(|hit| hit.index == *index))
+            .map(|index| Hit {
+                index,
+                matches: Vec::new(),
+                score: 0.0,
+                group: Group::Single,
+            })
+            .collect();
+        missing.sort_by_key(|hit| hit.index);
+
+        pinned.append(&mut missing);
+        pinned.extend(unpinned);
+        pinned
+    }
+
+    fn update_rows_keeping_selection(&mut self) {
+        let word = self.selected().map(|(word, _)| word.clone());
+        self.update_rows();
+
+        if let Some(word) = word {
+            self.select_visible_word(&word);
+        }
+    }
+
+    fn start_remap(&mut self, chords: &Chords) {
+        let Some((word, entry)) = self.selected() else {
+            return;
+        };
+        let (word, chord) = (word.clone(), entry.chord.clone());
+
+        let suggestions = suggest(&word, chords, &self.layout, &self.blacklist, SUGGESTIONS);
+        self.external = self
+            .config
+            .suggest_command
+            .clone()
+            .filter(|_| !self.blacklist.contains(&word))
+            .map(|command| {
+                // the command may take a while, so its candidates are added once it finishes
+                let (sender, receiver) = channel();
+                let (word, chords, notation) =
+                    (word.clone(), chords.clone(), self.notation.clone());
+                let timeout = self.config.suggest_timeout;
+                thread::spawn(move || {
+                    let candidates = external(&command, timeout, &word, &chords, &notation);
+                    let _ = sender.send((word, candidates));
+                });
+                receiver
+            });
+
+        self.overlay = Some(Overlay::Remap(Remap::new(
+            &word,
+            &chord,
+            &self.suffix_keys,
+            &self.words,
+            chords,
+            &self.layout,
+            suggestions,
+        )));
+    }
 
-                let word = word.to_owned();
-                let rank = entry
-                    .rank
-                    .as_ref()
-                    .map_or(String::new(), |rank| rank.to_string());
-                let chord = entry.chord.clone();
+    fn undo_remap(&mut self, chords: &mut Chords) {
+        let Some(moves) = self.undo.pop() else {
+            self.message = Some(String::from("Nothing to undo"));
+            return;
+        };
+        if moves
+            .iter()
+            .any(|change| chords.get(&change.to) != Some(&change.word))
+        {
+            self.message = Some(String::from("Cannot undo, the entries changed since"));
+            return;
+        }
 
-                Some(Row { rank, word, chord })
+        let reverted: Vec<_> = moves
+            .into_iter()
+            .map(|change| Move {
+                from: change.to,
+                to: change.from,
+                ..change
             })
             .collect();
+        self.apply_moves(&reverted, chords);
+        self.message = Some(format!("Undid remap of {} entries", reverted.len()));
+    }
+
+    fn apply_moves(&mut self, moves: &[Move], chords: &mut Chords) {
+        self.edited = true;
+        for change in moves {
+            chords.remove(&change.from);
+        }
+        for change in moves {
+            if !change.to.is_empty() {
+                chords.insert(change.to.clone(), change.word.clone());
+            }
+            self.searcher
+                .edit(&mut self.words)
+                .update_chord(change.word.clone(), change.to.clone());
+        }
+
+        self.update_rows_keeping_selection();
+    }
+
+    fn validate(&self, chords: &Chords) -> Vec<validate::Warning> {
+        validate::validate(chords, &self.words, &self.layout, &self.config)
+    }
+
+    fn suppress_warning(&mut self, view: &mut Warnings, index: usize, chords: &mut Chords) {
+        let Some(warning) = view.get(index) else {
+            return;
+        };
+        let Some(flag) = warning.suppression() else {
+            return;
+        };
+
+        let words: Vec<_> = warning.words().into_iter().map(str::to_owned).collect();
+        for word in &words {
+            let mut metadata = chords.metadata(word);
+            if let Some(suppressed) = metadata.suppressed.flag(flag) {
+                *suppressed = true;
+            }
+            chords.set_metadata(word, metadata.clone());
+            self.searcher
+                .edit(&mut self.words)
+                .set_metadata(word, metadata);
+        }
+
+        view.update(self.validate(chords));
+    }
+
+    fn select_word(&mut self, word: &str) {
+        self.update_rows();
+
+        if !self.select_visible_word(word) {
+            self.search.clear();
+            self.update_rows();
+            self.select_visible_word(word);
+        }
+    }
+
+    fn select_visible_word(&mut self, word: &str) -> bool {
+        let index = self.words.index_of(word);
+        let row = self.rows.iter().position(|hit| Some(hit.index) == index);
+        self.table_state.select(row);
+
+        row.is_some()
     }
 
     fn unselect_row(&mut self) {
@@ -216,28 +1824,153 @@ impl Tui {
     }
 
     fn select_next_row(&mut self) {
+        let last = self.rows.len().checked_sub(1);
         let row = self
             .table_state
             .selected()
-            .map_or(Some(0), |row| Some(row + 1));
+            .map_or(Some(0), |row| Some(row + 1))
+            .zip(last)
+            .map(|(row, last)| row.min(last));
         self.table_state.select(row);
     }
 
-    fn get_current_row(&mut self) -> Option<&mut Row> {
-        self.table_state
+    fn selected(&self) -> Option<(&String, &Entry)> {
+        let hit = self
+            .table_state
             .selected()
-            .and_then(|index| self.rows.get_mut(index))
+            .and_then(|index| self.rows.get(index))?;
+
+        self.words.get_index(hit.index)
     }
 }
 
-struct Row {
+struct Row<'a> {
     rank: String,
-    word: String,
-    chord: Chord,
+    word: &'a str,
+    matches: &'a [usize],
+    chord: &'a Chord,
+    notation: String,
+    violation: Option<Violation>,
+    savings: String,
+    frequency: String,
+    tags: String,
+    uses: String,
+    count: String,
+    difficulty: String,
+    group: Group,
+}
+
+impl<'a> Row<'a> {
+    fn new(
+        hit: &'a Hit,
+        words: &'a Words,
+        notation: &Notation,
+        layout: &KeyboardLayout,
+        uses: &BTreeMap<Chord, usize>,
+    ) -> Option<Self> {
+        let (word, entry) = words.get_index(hit.index)?;
+        let chord = &entry.chord;
+
+        let (savings, difficulty) = if chord.is_empty() {
+            (String::new(), String::new())
+        } else {
+            let keys = chord.keys().count();
+            (
+                word.chars().count().saturating_sub(keys).to_string(),
+                difficulty(chord, layout).to_string(),
+            )
+        };
+
+        let mut rank = entry.rank.map_or(String::new(), |rank| rank.to_string());
+        if let Group::Head {
+            rank: Some(combined),
+            ..
+        } = hit.group
+        {
+            rank = format!("{rank} (≈{combined})");
+        }
+
+        Some(Self {
+            rank,
+            word,
+            matches: &hit.matches,
+            chord,
+            notation: notation.format(chord),
+            violation: layout.violations(chord).into_iter().next(),
+            savings,
+            frequency: format!("{:.4}", words.frequency(entry)),
+            tags: entry
+                .metadata
+                .tags
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", "),
+            uses: uses.get(chord).map_or(String::new(), ToString::to_string),
+            count: entry.count.map_or(String::new(), |count| count.to_string()),
+            difficulty,
+            group: hit.group,
+        })
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0, |group, (index, byte)| {
+            group | u32::from(*byte) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(char::from(
+                    ALPHABET[(group >> (18 - 6 * index)) as usize & 63],
+                ));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+fn highlight<'a>(text: &'a str, matches: &[usize]) -> Line<'a> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut highlighted = false;
+
+    for (index, _) in text.char_indices() {
+        let matched = matches.contains(&index);
+
+        if matched != highlighted {
+            spans.push(highlight_span(&text[start..index], highlighted));
+            start = index;
+            highlighted = matched;
+        }
+    }
+    spans.push(highlight_span(&text[start..], highlighted));
+
+    Line::from(spans)
 }
 
-impl<'a> From<&'a Row> for TableRow<'a> {
-    fn from(row: &'a Row) -> Self {
-        TableRow::new([row.rank.as_str(), row.word.as_str(), row.chord.as_str()])
+fn highlight_span(text: &str, highlighted: bool) -> Span<'_> {
+    if highlighted {
+        Span::from(text).style(Style::new().bold().yellow())
+    } else {
+        Span::from(text)
     }
 }
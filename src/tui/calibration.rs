@@ -0,0 +1,187 @@
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::Stylize,
+    text::{Line, Span, Text},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use crate::{
+    chords::{Chord, Notation},
+    simulate::Model,
+    words::Words,
+};
+
+const TYPED_TRIALS: usize = 8;
+const CHORDED_TRIALS: usize = 8;
+const MIN_TYPED_LENGTH: usize = 4;
+
+struct Trial {
+    word: String,
+    chord: Option<Chord>,
+}
+
+pub struct Calibration {
+    trials: Vec<Trial>,
+    index: usize,
+    input: String,
+    shown: Instant,
+    keys: Vec<Instant>,
+    corrected: bool,
+    char_times: Vec<f64>,
+    chord_times: Vec<f64>,
+}
+
+impl Calibration {
+    pub fn new(words: &Words) -> Option<Self> {
+        let typed = words
+            .iter()
+            .filter(|(word, _)| word.chars().count() >= MIN_TYPED_LENGTH)
+            .take(TYPED_TRIALS)
+            .map(|(word, _)| Trial {
+                word: word.clone(),
+                chord: None,
+            });
+        let chorded = words
+            .iter()
+            .filter(|(_, entry)| !entry.chord.is_empty())
+            .take(CHORDED_TRIALS)
+            .map(|(word, entry)| Trial {
+                word: word.clone(),
+                chord: Some(entry.chord.clone()),
+            });
+        let trials: Vec<_> = typed.chain(chorded).collect();
+
+        (!trials.is_empty()).then(|| Self {
+            trials,
+            index: 0,
+            input: String::new(),
+            shown: Instant::now(),
+            keys: Vec::new(),
+            corrected: false,
+            char_times: Vec::new(),
+            chord_times: Vec::new(),
+        })
+    }
+
+    pub fn fitted(&self, model: Model) -> Model {
+        Model {
+            chord_time: median(&self.chord_times).unwrap_or(model.chord_time),
+            char_time: median(&self.char_times).unwrap_or(model.char_time),
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect, notation: &Notation, model: Model) {
+        let areas = Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).split(area);
+
+        let (text, help) = match self.trials.get(self.index) {
+            Some(trial) => {
+                let prompt = match &trial.chord {
+                    Some(chord) => Line::from(vec![
+                        Span::from("Chord "),
+                        Span::from(trial.word.as_str()).bold(),
+                        Span::from(format!(" ({})", notation.format(chord))),
+                    ]),
+                    None => Line::from(vec![
+                        Span::from("Type "),
+                        Span::from(trial.word.as_str()).bold(),
+                    ]),
+                };
+                let text = Text::from(vec![
+                    Line::from(format!("Trial {} of {}", self.index + 1, self.trials.len())),
+                    Line::default(),
+                    prompt,
+                    Line::from(format!("> {}", self.input)),
+                ]);
+
+                (text, "Type the word without pausing · Esc cancel")
+            }
+            None => {
+                let fitted = self.fitted(model);
+                let text = Text::from(vec![
+                    Line::from(format!(
+                        "Per character: {:.0} ms (was {:.0} ms)",
+                        fitted.char_time, model.char_time
+                    )),
+                    Line::from(format!(
+                        "Per chord: {:.0} ms (was {:.0} ms)",
+                        fitted.chord_time, model.chord_time
+                    )),
+                ]);
+
+                (text, "Enter save · Esc discard")
+            }
+        };
+
+        let block = Block::bordered().title(Span::from("Calibration").bold());
+        frame.render_widget(Paragraph::new(text).block(block), areas[0]);
+        frame.render_widget(
+            Paragraph::new(Line::from(help)).block(Block::bordered()),
+            areas[1],
+        );
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, model: &mut Model) -> bool {
+        let Some(trial) = self.trials.get(self.index) else {
+            match key.code {
+                KeyCode::Enter => *model = self.fitted(*model),
+                KeyCode::Esc => {}
+                _ => return false,
+            }
+            return true;
+        };
+
+        match key.code {
+            KeyCode::Esc => return true,
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.corrected = true;
+            }
+            // chording devices may emit a space before or after the word
+            KeyCode::Char(char) if char.is_whitespace() => {}
+            KeyCode::Char(char) => {
+                self.input.push(char);
+                self.keys.push(Instant::now());
+            }
+            _ => {}
+        }
+
+        if self.input == trial.word {
+            self.record();
+        }
+
+        false
+    }
+
+    fn record(&mut self) {
+        let is_chord = self.trials[self.index].chord.is_some();
+
+        // both are timed from the prompt, so either includes the reaction to it once per word
+        if !self.corrected {
+            if let Some(last) = self.keys.last() {
+                let time = last.duration_since(self.shown).as_secs_f64() * 1000.0;
+                if is_chord {
+                    self.chord_times.push(time);
+                } else {
+                    self.char_times.push(time / self.keys.len() as f64);
+                }
+            }
+        }
+
+        self.index += 1;
+        self.input.clear();
+        self.keys.clear();
+        self.corrected = false;
+        self.shown = Instant::now();
+    }
+}
+
+fn median(values: &[f64]) -> Option<f64> {
+    let mut values = values.to_vec();
+    values.sort_by(f64::total_cmp);
+
+    values.get(values.len() / 2).copied()
+}
@@ -0,0 +1,127 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{Axis, Block, Chart, Dataset, GraphType, Paragraph},
+    Frame,
+};
+
+use crate::{clock::DAY_MS, practice::History};
+
+const MIN_DAYS: f64 = 7.0;
+
+struct Series {
+    title: &'static str,
+    points: Vec<(f64, f64)>,
+    percent: bool,
+}
+
+pub struct Charts {
+    series: [Series; 3],
+}
+
+impl Charts {
+    pub fn new(practice: &History, coverage: &[(u64, f64)], now: u64) -> Self {
+        let today = now / DAY_MS;
+        let x = |day: u64| day as f64 - today as f64;
+
+        Self {
+            series: [
+                Series {
+                    title: "Practice accuracy",
+                    points: practice
+                        .daily_accuracy()
+                        .into_iter()
+                        .map(|(day, share)| (x(day), share * 100.0))
+                        .collect(),
+                    percent: true,
+                },
+                Series {
+                    title: "Chords learned",
+                    points: practice
+                        .learned()
+                        .into_iter()
+                        .map(|(day, learned)| (x(day), learned as f64))
+                        .collect(),
+                    percent: false,
+                },
+                Series {
+                    title: "Words entered by chord",
+                    points: coverage
+                        .iter()
+                        .map(|(day, share)| (x(*day), share * 100.0))
+                        .collect(),
+                    percent: true,
+                },
+            ],
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        let areas = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Length(3),
+        ])
+        .split(area);
+
+        for (series, area) in self.series.iter().zip(areas.iter()) {
+            draw_series(frame, *area, series);
+        }
+
+        frame.render_widget(
+            Paragraph::new(Line::from("Esc/q close")).block(Block::bordered()),
+            areas[3],
+        );
+    }
+
+    pub fn handle_key(key: KeyEvent) -> bool {
+        matches!(key.code, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter)
+    }
+}
+
+fn draw_series(frame: &mut Frame, area: Rect, series: &Series) {
+    let block = Block::bordered().title(Span::from(series.title).bold());
+    if series.points.is_empty() {
+        frame.render_widget(Paragraph::new("No history yet").italic().block(block), area);
+        return;
+    }
+
+    let first = series
+        .points
+        .iter()
+        .map(|(x, _)| *x)
+        .fold(-MIN_DAYS, f64::min);
+    let top = if series.percent {
+        100.0
+    } else {
+        series.points.iter().map(|(_, y)| *y).fold(1.0, f64::max)
+    };
+    let (top_label, middle_label) = if series.percent {
+        (String::from("100%"), String::from("50%"))
+    } else {
+        (format!("{top:.0}"), format!("{:.0}", top / 2.0))
+    };
+
+    let dataset = Dataset::default()
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::new().fg(Color::Cyan))
+        .data(&series.points);
+    let chart = Chart::new(vec![dataset])
+        .block(block)
+        .x_axis(
+            Axis::default()
+                .bounds([first, 0.0])
+                .labels([format!("{first:.0}d"), String::from("today")]),
+        )
+        .y_axis(Axis::default().bounds([0.0, top]).labels([
+            String::from("0"),
+            middle_label,
+            top_label,
+        ]));
+    frame.render_widget(chart, area);
+}
@@ -0,0 +1,222 @@
+use std::str::FromStr;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Cell, Clear, List, ListState},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::search::Group;
+
+use super::{centered, highlight, Row};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    Rank,
+    Word,
+    Chord,
+    Savings,
+    Tags,
+    Uses,
+    Count,
+    Difficulty,
+}
+
+impl Column {
+    const ALL: [Column; 8] = [
+        Column::Rank,
+        Column::Word,
+        Column::Chord,
+        Column::Savings,
+        Column::Tags,
+        Column::Uses,
+        Column::Count,
+        Column::Difficulty,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Rank => "rank",
+            Column::Word => "word",
+            Column::Chord => "chord",
+            Column::Savings => "savings",
+            Column::Tags => "tags",
+            Column::Uses => "uses",
+            Column::Count => "count",
+            Column::Difficulty => "difficulty",
+        }
+    }
+
+    pub fn header(self) -> &'static str {
+        match self {
+            Column::Rank => "Rank",
+            Column::Word => "Word",
+            Column::Chord => "Chord",
+            Column::Savings => "Savings",
+            Column::Tags => "Tags",
+            Column::Uses => "Uses",
+            Column::Count => "Count",
+            Column::Difficulty => "Difficulty",
+        }
+    }
+
+    pub(super) fn cell<'a>(self, row: &'a Row<'_>) -> Cell<'a> {
+        match self {
+            Column::Rank => Cell::from(row.rank.as_str()),
+            Column::Word => {
+                let mut line = highlight(row.word, row.matches);
+                match row.group {
+                    Group::Single => {}
+                    Group::Head { forms, .. } => line.push_span(format!(" +{forms}").dim()),
+                    Group::Member => line.spans.insert(0, "  ".into()),
+                }
+                Cell::from(line)
+            }
+            Column::Chord if row.violation.is_some() => Cell::from(row.notation.as_str()).red(),
+            Column::Chord => Cell::from(row.notation.as_str()),
+            Column::Savings => Cell::from(row.savings.as_str()),
+            Column::Tags => Cell::from(row.tags.as_str()).dim(),
+            Column::Uses => Cell::from(row.uses.as_str()),
+            Column::Count => Cell::from(row.count.as_str()),
+            Column::Difficulty => Cell::from(row.difficulty.as_str()),
+        }
+    }
+
+    pub(super) fn width(self, row: &Row<'_>) -> usize {
+        match self {
+            Column::Rank => row.rank.width(),
+            Column::Word => row.word.width(),
+            Column::Chord => row.notation.width(),
+            Column::Savings => row.savings.width(),
+            Column::Tags => row.tags.width(),
+            Column::Uses => row.uses.width(),
+            Column::Count => row.count.width(),
+            Column::Difficulty => row.difficulty.width(),
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Column::ALL
+            .into_iter()
+            .find(|column| column.name() == string)
+            .ok_or(())
+    }
+}
+
+pub struct Picker {
+    state: ListState,
+}
+
+impl Picker {
+    pub fn new() -> Self {
+        Self {
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect, shown: &[Column]) {
+        let height = u16::try_from(Column::ALL.len()).unwrap_or(u16::MAX) + 3;
+        let area = centered(area, 34, height);
+
+        let items = Self::order(shown).into_iter().map(|column| {
+            let mark = if shown.contains(&column) { "x" } else { " " };
+            Line::from(format!("[{mark}] {}", column.header()))
+        });
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .title(Span::from("Columns").bold())
+                    .title_bottom(Line::from(" Space toggle · J/K move ").dim()),
+            )
+            .highlight_style(Style::new().reversed());
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.state);
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, shown: &mut Vec<Column>) -> bool {
+        let order = Self::order(shown);
+        let last = order.len() - 1;
+        let selected = self.state.selected().unwrap_or_default().min(last);
+        let column = order[selected];
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('v' | 'q') => return true,
+            KeyCode::Char('j') | KeyCode::Down => self.state.select(Some((selected + 1).min(last))),
+            KeyCode::Char('k') | KeyCode::Up => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Char(' ') => {
+                match shown.iter().position(|shown| *shown == column) {
+                    // the table needs at least one column
+                    Some(_) if shown.len() == 1 => {}
+                    Some(index) => {
+                        shown.remove(index);
+                    }
+                    None => shown.push(column),
+                }
+                let position = Self::order(shown).iter().position(|other| *other == column);
+                self.state.select(position);
+            }
+            KeyCode::Char('J') if selected < last && shown.contains(&order[selected + 1]) => {
+                Self::swap(shown, column, order[selected + 1]);
+                self.state.select(Some(selected + 1));
+            }
+            KeyCode::Char('K') if selected > 0 && shown.contains(&order[selected - 1]) => {
+                Self::swap(shown, column, order[selected - 1]);
+                self.state.select(Some(selected - 1));
+            }
+            _ => {}
+        }
+
+        false
+    }
+
+    fn order(shown: &[Column]) -> Vec<Column> {
+        let hidden = Column::ALL
+            .into_iter()
+            .filter(|column| !shown.contains(column));
+
+        shown.iter().copied().chain(hidden).collect()
+    }
+
+    fn swap(shown: &mut [Column], first: Column, second: Column) {
+        let first = shown.iter().position(|column| *column == first);
+        let second = shown.iter().position(|column| *column == second);
+        if let (Some(first), Some(second)) = (first, second) {
+            shown.swap(first, second);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_and_move_columns() {
+        let key = |code| KeyEvent::from(code);
+        let mut picker = Picker::new();
+        let mut shown = vec![Column::Rank, Column::Word, Column::Chord];
+
+        picker.handle_key(key(KeyCode::Char(' ')), &mut shown);
+        assert_eq!(shown, [Column::Word, Column::Chord]);
+
+        picker.handle_key(key(KeyCode::Char('j')), &mut shown);
+        picker.handle_key(key(KeyCode::Char('j')), &mut shown);
+        picker.handle_key(key(KeyCode::Char(' ')), &mut shown);
+        assert_eq!(shown, [Column::Word, Column::Chord, Column::Tags]);
+
+        picker.handle_key(key(KeyCode::Char('K')), &mut shown);
+        assert_eq!(shown, [Column::Word, Column::Tags, Column::Chord]);
+
+        assert!(picker.handle_key(key(KeyCode::Esc), &mut shown));
+        assert_eq!("tags".parse(), Ok(Column::Tags));
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    chords::{Chord, Notation},
+    clock::{self, DAY_MS},
+    layout::Layout as KeyboardLayout,
+    suggest::difficulty,
+    usage::HISTORY_BUCKETS,
+    words::Entry,
+};
+
+use super::Row;
+
+const SPARKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub fn draw(
+    frame: &mut Frame,
+    area: Rect,
+    row: Option<&Row>,
+    layout: &KeyboardLayout,
+    history: Option<[usize; HISTORY_BUCKETS]>,
+) {
+    let block = Block::bordered().title(Span::from("Details").bold());
+
+    let Some(row) = row else {
+        let text = Text::from("No entry selected").italic();
+        frame.render_widget(Paragraph::new(text).block(block), area);
+        return;
+    };
+
+    let mut lines = vec![
+        field("Word", row.word),
+        field("Rank", &row.rank),
+        field("Frequency", &row.frequency),
+        field("Chord", &row.notation),
+    ];
+    if !row.difficulty.is_empty() {
+        lines.push(field("Difficulty", &row.difficulty));
+    }
+    let sparkline = history
+        .filter(|_| !row.chord.is_empty())
+        .map(|history| sparkline(&history));
+    if let Some(sparkline) = &sparkline {
+        lines.push(field("Usage", sparkline));
+    }
+    lines.push(Line::default());
+    lines.extend(keyboard(layout, row.chord));
+
+    if !row.chord.is_empty() {
+        lines.push(Line::default());
+        lines.extend(
+            layout
+                .violations(row.chord)
+                .into_iter()
+                .map(|violation| Line::from(violation.to_string()).red()),
+        );
+    }
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+pub struct Popup {
+    word: String,
+    lines: Vec<Line<'static>>,
+}
+
+impl Popup {
+    pub fn new(
+        word: &str,
+        entry: &Entry,
+        notation: &Notation,
+        layout: &KeyboardLayout,
+        frequency: f64,
+        uses: Option<usize>,
+        history: Option<[usize; HISTORY_BUCKETS]>,
+    ) -> Self {
+        let chord = &entry.chord;
+        let owned = |name: &str, value: String| {
+            Line::from(vec![
+                Span::from(format!("{name}: ")).bold(),
+                Span::from(value),
+            ])
+        };
+
+        let mut lines = vec![
+            owned(
+                "Rank",
+                entry
+                    .rank
+                    .map_or(String::from("unranked"), |rank| rank.to_string()),
+            ),
+            owned("Frequency", format!("{frequency:.4}")),
+            owned("Chord", notation.format(chord)),
+        ];
+        if let Some(count) = entry.count {
+            lines.insert(1, owned("Count", count.to_string()));
+        }
+        if !chord.is_empty() {
+            let savings = word.chars().count().saturating_sub(chord.keys().count());
+            lines.push(owned("Savings", format!("{savings} keystrokes")));
+            lines.push(owned("Difficulty", difficulty(chord, layout).to_string()));
+            lines.push(owned("Uses", uses.unwrap_or_default().to_string()));
+            if let Some(history) = history {
+                lines.push(owned("Usage", sparkline(&history)));
+            }
+        }
+        let now = clock::now();
+        let age = |timestamp: u64| format!("{} days ago", now.saturating_sub(timestamp) / DAY_MS);
+        if let Some(created) = entry.metadata.created {
+            lines.push(owned("Added", age(created)));
+        }
+        if let Some(modified) = entry.metadata.modified {
+            lines.push(owned("Changed", age(modified)));
+        }
+        if !entry.metadata.tags.is_empty() {
+            let tags: Vec<_> = entry.metadata.tags.iter().map(String::as_str).collect();
+            lines.push(owned("Tags", tags.join(", ")));
+        }
+        if let Some(note) = &entry.metadata.note {
+            lines.push(owned("Note", note.clone()));
+        }
+
+        lines.push(Line::default());
+        lines.extend(keyboard(layout, chord));
+        if !chord.is_empty() {
+            lines.extend(
+                layout
+                    .violations(chord)
+                    .into_iter()
+                    .map(|violation| Line::from(violation.to_string()).red()),
+            );
+        }
+
+        Self {
+            word: word.to_owned(),
+            lines,
+        }
+    }
+
+    pub fn section(&mut self, title: &str, items: Vec<String>) {
+        if items.is_empty() {
+            return;
+        }
+
+        self.lines.push(Line::default());
+        self.lines.push(Line::from(title.to_owned()).bold());
+        self.lines.extend(
+            items
+                .into_iter()
+                .map(|item| Line::from(format!("  {item}"))),
+        );
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        let areas = Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).split(area);
+
+        let block = Block::bordered().title(Span::from(self.word.as_str()).bold());
+        frame.render_widget(
+            Paragraph::new(self.lines.clone())
+                .wrap(Wrap { trim: false })
+                .block(block),
+            areas[0],
+        );
+        frame.render_widget(
+            Paragraph::new(Line::from("e edit · Enter/Esc close")).block(Block::bordered()),
+            areas[1],
+        );
+    }
+}
+
+fn field<'a>(name: &'a str, value: &'a str) -> Line<'a> {
+    Line::from(vec![
+        Span::from(format!("{name}: ")).bold(),
+        Span::from(value),
+    ])
+}
+
+fn sparkline(history: &[usize]) -> String {
+    let max = history.iter().copied().max().unwrap_or_default().max(1);
+
+    history
+        .iter()
+        .map(|&uses| SPARKS[(uses * (SPARKS.len() - 1)).div_ceil(max)])
+        .collect()
+}
+
+pub fn keyboard(layout: &KeyboardLayout, chord: &Chord) -> Vec<Line<'static>> {
+    let mut rows: BTreeMap<u8, BTreeMap<u8, &str>> = BTreeMap::new();
+    for (name, key) in layout.keys() {
+        rows.entry(key.row).or_default().insert(key.column, name);
+    }
+
+    let columns = rows
+        .values()
+        .filter_map(|row| row.keys().max())
+        .max()
+        .map_or(0, |column| column + 1);
+
+    rows.into_values()
+        .map(|row| {
+            let spans: Vec<_> = (0..columns)
+                .map(|column| match row.get(&column) {
+                    Some(name) if chord.keys().any(|key| key == *name) => {
+                        Span::from(format!(" {name} ")).style(Style::new().reversed().bold())
+                    }
+                    Some(name) => Span::from(format!(" {name} ")).dim(),
+                    None => Span::from("   "),
+                })
+                .collect();
+
+            Line::from(spans)
+        })
+        .collect()
+}
@@ -0,0 +1,172 @@
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::Stylize,
+    text::{Line, Span, Text},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use crate::{
+    chords::{Chord, Notation},
+    clock,
+    practice::{Grade, History, Record},
+    words::Words,
+};
+
+const ROUNDS: usize = 10;
+const WEAKEST: usize = 5;
+
+pub struct Drill {
+    session: u64,
+    entries: Vec<(String, Chord)>,
+    index: usize,
+    input: String,
+    shown: Instant,
+    last: Option<(bool, String, Chord)>,
+    correct: usize,
+}
+
+impl Drill {
+    pub fn new(words: &Words, history: &History) -> Option<Self> {
+        let now = clock::now();
+        let chorded = words
+            .iter()
+            .filter(|(_, entry)| !entry.chord.is_empty())
+            .map(|(word, entry)| (word, &entry.chord));
+        let entries = history.due(chorded, now, ROUNDS);
+
+        (!entries.is_empty()).then(|| Self {
+            session: now,
+            entries,
+            index: 0,
+            input: String::new(),
+            shown: Instant::now(),
+            last: None,
+            correct: 0,
+        })
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect, notation: &Notation, history: &History) {
+        let areas = Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).split(area);
+
+        let feedback = match &self.last {
+            Some((true, word, _)) => Line::from(format!("✓ {word}")).green(),
+            Some((false, word, chord)) => {
+                Line::from(format!("✗ {word} is {}", notation.format(chord))).red()
+            }
+            None => Line::default(),
+        };
+
+        let (lines, help) = match self.entries.get(self.index) {
+            Some((word, _)) => {
+                let lines = vec![
+                    Line::from(format!(
+                        "Round {} of {}",
+                        self.index + 1,
+                        self.entries.len()
+                    )),
+                    Line::default(),
+                    Line::from(vec![Span::from("Chord "), Span::from(word.as_str()).bold()]),
+                    Line::from(format!("> {}", self.input)),
+                    Line::default(),
+                    feedback,
+                ];
+
+                (lines, "Chord the word · Space/Enter submit · Esc stop")
+            }
+            None => {
+                let now = clock::now();
+                let mut lines = vec![
+                    feedback,
+                    Line::default(),
+                    Line::from(format!(
+                        "Correct: {} of {}",
+                        self.correct,
+                        self.entries.len()
+                    )),
+                    Line::from(match history.streak(now) {
+                        1 => String::from("Streak: 1 day"),
+                        streak => format!("Streak: {streak} days"),
+                    }),
+                ];
+                if let Some(retention) = history.average_retention(now) {
+                    lines.push(Line::from(format!("Retention: {:.0}%", retention * 100.0)));
+                }
+
+                let weakest = history.weakest(WEAKEST);
+                if !weakest.is_empty() {
+                    lines.push(Line::default());
+                    lines.push(Line::from("Weakest chords:"));
+                }
+                lines.extend(weakest.iter().map(|weakness| {
+                    Line::from(format!(
+                        "  {} {} ({:.0}% of {})",
+                        notation.format(&weakness.chord),
+                        weakness.word,
+                        weakness.accuracy * 100.0,
+                        weakness.attempts
+                    ))
+                }));
+
+                (lines, "Enter/Esc close")
+            }
+        };
+
+        let block = Block::bordered().title(Span::from("Practice").bold());
+        frame.render_widget(Paragraph::new(Text::from(lines)).block(block), areas[0]);
+        frame.render_widget(
+            Paragraph::new(Line::from(help)).block(Block::bordered()),
+            areas[1],
+        );
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, history: &mut History) -> bool {
+        let Some((word, _)) = self.entries.get(self.index) else {
+            return matches!(key.code, KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q'));
+        };
+
+        match key.code {
+            KeyCode::Esc => return true,
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Enter if !self.input.is_empty() => self.answer(history),
+            KeyCode::Char(char) if char.is_whitespace() && !self.input.is_empty() => {
+                self.answer(history);
+            }
+            KeyCode::Char(char) if char.is_whitespace() => {}
+            KeyCode::Char(char) => {
+                self.input.push(char);
+                if self.input == *word {
+                    self.answer(history);
+                }
+            }
+            _ => {}
+        }
+
+        false
+    }
+
+    fn answer(&mut self, history: &mut History) {
+        let (word, chord) = self.entries[self.index].clone();
+        let correct = self.input == word;
+
+        history.push(Record {
+            timestamp: clock::now(),
+            session: self.session,
+            grade: if correct { Grade::Good } else { Grade::Again },
+            time: u64::try_from(self.shown.elapsed().as_millis()).unwrap_or(u64::MAX),
+            chord: chord.clone(),
+            word: word.clone(),
+        });
+        self.correct += usize::from(correct);
+        self.last = Some((correct, word, chord));
+
+        self.index += 1;
+        self.input.clear();
+        self.shown = Instant::now();
+    }
+}
@@ -0,0 +1,155 @@
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Default)]
+pub struct EditBuffer {
+    text: String,
+    cursor: usize,
+}
+
+impl EditBuffer {
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor_width(&self) -> usize {
+        self.text[..self.cursor].width()
+    }
+
+    pub fn insert(&mut self, char: char) {
+        self.text.insert(self.cursor, char);
+        self.cursor += char.len_utf8();
+    }
+
+    pub fn insert_str(&mut self, string: &str) {
+        self.text.insert_str(self.cursor, string);
+        self.cursor += string.len();
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(char) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= char.len_utf8();
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn left(&mut self) {
+        if let Some(char) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= char.len_utf8();
+        }
+    }
+
+    pub fn right(&mut self) {
+        if let Some(char) = self.text[self.cursor..].chars().next() {
+            self.cursor += char.len_utf8();
+        }
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    pub fn delete_word(&mut self) {
+        let before = self.text[..self.cursor].trim_end();
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, char)| char.is_whitespace())
+            .map_or(0, |(index, char)| index + char.len_utf8());
+
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn kill_line(&mut self) {
+        self.text.replace_range(..self.cursor, "");
+        self.cursor = 0;
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+}
+
+impl From<String> for EditBuffer {
+    fn from(text: String) -> Self {
+        let cursor = text.len();
+
+        Self { text, cursor }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_mid_string() {
+        let mut buffer = EditBuffer::from(String::from("chrd"));
+        buffer.left();
+        buffer.left();
+        buffer.insert('o');
+        assert_eq!(buffer.as_str(), "chord");
+        assert_eq!(buffer.cursor_width(), 3);
+
+        buffer.insert_str("al ");
+        buffer.end();
+        buffer.insert('s');
+        assert_eq!(buffer.as_str(), "choal rds");
+    }
+
+    #[test]
+    fn delete_word_across_whitespace() {
+        let mut buffer = EditBuffer::from(String::from("one two  three"));
+        buffer.left();
+        buffer.left();
+        buffer.left();
+        buffer.left();
+        buffer.left();
+        buffer.delete_word();
+        assert_eq!(buffer.as_str(), "one three");
+
+        buffer.delete_word();
+        assert_eq!(buffer.as_str(), "three");
+        assert_eq!(buffer.cursor_width(), 0);
+
+        let mut trailing = EditBuffer::from(String::from("one two   "));
+        trailing.delete_word();
+        assert_eq!(trailing.as_str(), "one ");
+    }
+
+    #[test]
+    fn edit_multi_byte_characters() {
+        let mut buffer = EditBuffer::from(String::from("naïve 日本"));
+        buffer.backspace();
+        assert_eq!(buffer.as_str(), "naïve 日");
+        assert_eq!(buffer.cursor_width(), 8);
+
+        buffer.home();
+        buffer.right();
+        buffer.right();
+        buffer.delete();
+        assert_eq!(buffer.as_str(), "nave 日");
+
+        buffer.insert('ï');
+        buffer.right();
+        buffer.right();
+        buffer.right();
+        buffer.delete_word();
+        assert_eq!(buffer.as_str(), "日");
+
+        buffer.end();
+        buffer.kill_line();
+        assert_eq!(buffer.as_str(), "");
+    }
+}
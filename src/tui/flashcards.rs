@@ -0,0 +1,139 @@
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::Stylize,
+    text::{Line, Span, Text},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use crate::{
+    chords::{Chord, Notation},
+    clock,
+    practice::{Grade, History, Record},
+    words::Words,
+};
+
+const CARDS: usize = 20;
+
+pub struct Flashcards {
+    session: u64,
+    cards: Vec<(String, Chord)>,
+    index: usize,
+    revealed: bool,
+    shown: Instant,
+    grades: [usize; Grade::ALL.len()],
+}
+
+impl Flashcards {
+    pub fn new(words: &Words, history: &History) -> Option<Self> {
+        let now = clock::now();
+        let chorded = words
+            .iter()
+            .filter(|(_, entry)| !entry.chord.is_empty())
+            .map(|(word, entry)| (word, &entry.chord));
+        let cards = history.due(chorded, now, CARDS);
+
+        (!cards.is_empty()).then(|| Self {
+            session: now,
+            cards,
+            index: 0,
+            revealed: false,
+            shown: Instant::now(),
+            grades: [0; Grade::ALL.len()],
+        })
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect, notation: &Notation) {
+        let areas = Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).split(area);
+        let block = Block::bordered().title(Span::from("Flashcards").bold());
+        let inner = block.inner(areas[0]);
+        frame.render_widget(block, areas[0]);
+
+        let (lines, help) = match self.cards.get(self.index) {
+            Some((word, chord)) => {
+                let spaced: String = word
+                    .chars()
+                    .map(|char| char.to_uppercase().to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let mut lines = vec![
+                    Line::from(format!("{} of {}", self.index + 1, self.cards.len())).dark_gray(),
+                    Line::default(),
+                    Line::from(spaced).bold(),
+                    Line::default(),
+                ];
+
+                if self.revealed {
+                    lines.push(Line::from(notation.format(chord)).bold().green());
+                    (lines, "1 again · 2 hard · 3 good · 4 easy · Esc stop")
+                } else {
+                    lines.push(Line::from("?").dark_gray());
+                    (lines, "Space/Enter reveal · Esc stop")
+                }
+            }
+            None => {
+                let mut lines = vec![
+                    Line::from(format!("Reviewed {} cards", self.cards.len())),
+                    Line::default(),
+                ];
+                lines.extend(
+                    Grade::ALL
+                        .iter()
+                        .zip(self.grades)
+                        .map(|(grade, count)| Line::from(format!("{}: {count}", grade.name()))),
+                );
+
+                (lines, "Enter/Esc close")
+            }
+        };
+
+        let height = u16::try_from(lines.len()).unwrap_or(u16::MAX);
+        let [_, middle, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(height),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+        frame.render_widget(
+            Paragraph::new(Text::from(lines)).alignment(Alignment::Center),
+            middle,
+        );
+        frame.render_widget(
+            Paragraph::new(Line::from(help)).block(Block::bordered()),
+            areas[1],
+        );
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, history: &mut History) -> bool {
+        let Some((word, chord)) = self.cards.get(self.index) else {
+            return matches!(key.code, KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q'));
+        };
+
+        match key.code {
+            KeyCode::Esc => return true,
+            KeyCode::Enter | KeyCode::Char(' ') => self.revealed = true,
+            KeyCode::Char(digit @ '1'..='4') if self.revealed => {
+                let index = digit as usize - '1' as usize;
+                history.push(Record {
+                    timestamp: clock::now(),
+                    session: self.session,
+                    grade: Grade::ALL[index],
+                    time: u64::try_from(self.shown.elapsed().as_millis()).unwrap_or(u64::MAX),
+                    chord: chord.clone(),
+                    word: word.clone(),
+                });
+                self.grades[index] += 1;
+
+                self.index += 1;
+                self.revealed = false;
+                self.shown = Instant::now();
+            }
+            _ => {}
+        }
+
+        false
+    }
+}
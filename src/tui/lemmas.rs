@@ -0,0 +1,107 @@
+use std::collections::BTreeSet;
+
+use indexmap::IndexMap;
+
+use crate::{
+    inflection::lemma,
+    search::{Group, Hit},
+    words::Words,
+};
+
+pub fn lemma_of(word: &str, words: &Words) -> String {
+    lemma(word, |word| words.contains(word))
+}
+
+pub fn group(hits: Vec<Hit>, words: &Words, expanded: &BTreeSet<String>) -> Vec<Hit> {
+    let word = |hit: &Hit| words.get_index(hit.index).map(|(word, _)| word);
+
+    let mut groups: IndexMap<String, Vec<Hit>> = IndexMap::new();
+    for hit in hits {
+        let lemma = word(&hit).map_or_else(String::new, |word| lemma_of(word, words));
+        groups.entry(lemma).or_default().push(hit);
+    }
+
+    let mut rows = Vec::new();
+    for (lemma, mut members) in groups {
+        if members.len() == 1 {
+            rows.append(&mut members);
+            continue;
+        }
+
+        let head = members
+            .iter()
+            .position(|hit| word(hit) == Some(&lemma))
+            .unwrap_or_default();
+        let mut head = members.remove(head);
+
+        let frequency: f64 = members
+            .iter()
+            .chain([&head])
+            .filter_map(|hit| words.get_index(hit.index))
+            .map(|(_, entry)| words.frequency(entry))
+            .sum();
+        head.group = Group::Head {
+            forms: members.len(),
+            rank: (frequency > 0.0).then(|| ((1.0 / frequency).round() as usize).max(1)),
+        };
+        rows.push(head);
+
+        if expanded.contains(&lemma) {
+            rows.extend(members.into_iter().map(|hit| Hit {
+                group: Group::Member,
+                ..hit
+            }));
+        }
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_word_forms() {
+        let mut words = Words::default();
+        words.import("the\nwalks\nof\nwalk\nwalked\n");
+        let hits = || {
+            (0..5).map(|index| Hit {
+                index,
+                matches: Vec::new(),
+                score: 0.0,
+                group: Group::Single,
+            })
+        };
+        let rows = |expanded: &BTreeSet<String>| -> Vec<_> {
+            group(hits().collect(), &words, expanded)
+                .into_iter()
+                .map(|hit| (words.get_index(hit.index).unwrap().0.clone(), hit.group))
+                .collect()
+        };
+
+        let collapsed = rows(&BTreeSet::new());
+        let head = Group::Head {
+            forms: 2,
+            rank: Some(1),
+        };
+        assert_eq!(
+            collapsed,
+            [
+                ("the".to_owned(), Group::Single),
+                ("walk".to_owned(), head),
+                ("of".to_owned(), Group::Single),
+            ]
+        );
+
+        let expanded = rows(&["walk".to_owned()].into_iter().collect());
+        let members: Vec<_> = expanded[2..4]
+            .iter()
+            .map(|(word, _)| word.as_str())
+            .collect();
+        assert_eq!(members, ["walks", "walked"]);
+        assert!(expanded[2..4]
+            .iter()
+            .all(|(_, group)| *group == Group::Member));
+    }
+}
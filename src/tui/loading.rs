@@ -0,0 +1,40 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ratatui::{
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use super::{centered, SPINNER};
+
+#[derive(Default)]
+pub struct Progress {
+    pub chords: AtomicUsize,
+    pub words: AtomicUsize,
+    pub events: AtomicUsize,
+}
+
+pub fn draw(frame: &mut Frame, area: Rect, progress: &Progress, ticks: usize) {
+    let spinner = SPINNER[ticks % SPINNER.len()];
+    let block = Block::bordered().title(Span::from(format!(" {spinner} Loading ")).bold());
+
+    let lines = vec![
+        count("Chords", &progress.chords),
+        count("Words", &progress.words),
+        count("Usage events", &progress.events),
+        Line::default(),
+        Line::from("Ctrl+C to abort").dim(),
+    ];
+
+    frame.render_widget(Paragraph::new(lines).block(block), centered(area, 40, 7));
+}
+
+fn count<'a>(name: &'a str, lines: &AtomicUsize) -> Line<'a> {
+    Line::from(vec![
+        Span::from(format!("{name}: ")).bold(),
+        Span::from(lines.load(Ordering::Relaxed).to_string()),
+    ])
+}
@@ -0,0 +1,126 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    text::Span,
+    widgets::{Block, Clear, List, ListState},
+    Frame,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Item {
+    Edit,
+    Delete,
+    Copy,
+    Mark,
+    Pin,
+    Tag,
+    Suggest,
+}
+
+impl Item {
+    const ALL: [Item; 7] = [
+        Item::Edit,
+        Item::Delete,
+        Item::Copy,
+        Item::Mark,
+        Item::Pin,
+        Item::Tag,
+        Item::Suggest,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Item::Edit => "Edit chord     e",
+            Item::Delete => "Delete         d",
+            Item::Copy => "Copy           y",
+            Item::Mark => "Mark       Space",
+            Item::Pin => "Pin            p",
+            Item::Tag => "Tag            T",
+            Item::Suggest => "Suggest chord  m",
+        }
+    }
+
+    fn shortcut(self) -> KeyCode {
+        match self {
+            Item::Edit => KeyCode::Char('e'),
+            Item::Delete => KeyCode::Char('d'),
+            Item::Copy => KeyCode::Char('y'),
+            Item::Mark => KeyCode::Char(' '),
+            Item::Pin => KeyCode::Char('p'),
+            Item::Tag => KeyCode::Char('T'),
+            Item::Suggest => KeyCode::Char('m'),
+        }
+    }
+}
+
+pub enum Action {
+    Close,
+    Choose(Item),
+}
+
+pub struct Menu {
+    position: (u16, u16),
+    area: Rect,
+    state: ListState,
+}
+
+impl Menu {
+    const WIDTH: u16 = 20;
+
+    pub fn new(position: (u16, u16)) -> Self {
+        Self {
+            position,
+            area: Rect::default(),
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let height = Item::ALL.len() as u16 + 2;
+        let x = self
+            .position
+            .0
+            .min(area.right().saturating_sub(Self::WIDTH));
+        let y = self.position.1.min(area.bottom().saturating_sub(height));
+        self.area = Rect::new(x, y, Self::WIDTH, height).intersection(area);
+
+        let list = List::new(Item::ALL.map(Item::label))
+            .block(Block::bordered().title(Span::from("Entry").bold()))
+            .highlight_style(Style::new().reversed());
+
+        frame.render_widget(Clear, self.area);
+        frame.render_stateful_widget(list, self.area, &mut self.state);
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let last = Item::ALL.len() - 1;
+        let selected = self.state.selected().unwrap_or_default();
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Menu => Some(Action::Close),
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.state.select(Some((selected + 1).min(last)));
+                None
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.state.select(Some(selected.saturating_sub(1)));
+                None
+            }
+            KeyCode::Enter => Some(Action::Choose(Item::ALL[selected])),
+            code => Item::ALL
+                .into_iter()
+                .find(|item| item.shortcut() == code)
+                .map(Action::Choose),
+        }
+    }
+
+    pub fn handle_click(&self, column: u16, row: u16) -> Action {
+        let inside = (self.area.x + 1..self.area.right().saturating_sub(1)).contains(&column);
+
+        row.checked_sub(self.area.y + 1)
+            .and_then(|index| Item::ALL.get(usize::from(index)))
+            .filter(|_| inside)
+            .map_or(Action::Close, |item| Action::Choose(*item))
+    }
+}
@@ -0,0 +1,156 @@
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::Stylize,
+    text::{Line, Span, Text},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use super::detail::keyboard;
+use crate::{
+    chords::{Chord, Notation},
+    clock,
+    layout::Layout as KeyboardLayout,
+    practice::{Grade, History, Record},
+    words::Words,
+};
+
+const ROUNDS: usize = 10;
+
+pub struct Quiz {
+    session: u64,
+    entries: Vec<(String, Chord)>,
+    index: usize,
+    input: String,
+    shown: Instant,
+    last: Option<(bool, String, Chord)>,
+    correct: usize,
+}
+
+impl Quiz {
+    pub fn new(words: &Words, history: &History) -> Option<Self> {
+        let now = clock::now();
+        let chorded = words
+            .iter()
+            .filter(|(_, entry)| !entry.chord.is_empty())
+            .map(|(word, entry)| (word, &entry.chord));
+        let entries = history.due(chorded, now, ROUNDS);
+
+        (!entries.is_empty()).then(|| Self {
+            session: now,
+            entries,
+            index: 0,
+            input: String::new(),
+            shown: Instant::now(),
+            last: None,
+            correct: 0,
+        })
+    }
+
+    pub fn draw(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        notation: &Notation,
+        layout: &KeyboardLayout,
+    ) {
+        let areas = Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).split(area);
+
+        let feedback = match &self.last {
+            Some((true, word, _)) => Line::from(format!("✓ {word}")).green(),
+            Some((false, word, chord)) => {
+                Line::from(format!("✗ {} is {word}", notation.format(chord))).red()
+            }
+            None => Line::default(),
+        };
+
+        let (lines, help) = match self.entries.get(self.index) {
+            Some((_, chord)) => {
+                let mut lines = vec![
+                    Line::from(format!(
+                        "Round {} of {}",
+                        self.index + 1,
+                        self.entries.len()
+                    )),
+                    Line::default(),
+                    Line::from(vec![
+                        Span::from("Which word is "),
+                        Span::from(notation.format(chord)).bold(),
+                        Span::from("?"),
+                    ]),
+                    Line::default(),
+                ];
+                lines.extend(keyboard(layout, chord));
+                lines.extend([
+                    Line::default(),
+                    Line::from(format!("> {}", self.input)),
+                    Line::default(),
+                    feedback,
+                ]);
+
+                (lines, "Type the word · Enter submit · Esc stop")
+            }
+            None => {
+                let lines = vec![
+                    feedback,
+                    Line::default(),
+                    Line::from(format!(
+                        "Correct: {} of {}",
+                        self.correct,
+                        self.entries.len()
+                    )),
+                ];
+
+                (lines, "Enter/Esc close")
+            }
+        };
+
+        let block = Block::bordered().title(Span::from("Chord quiz").bold());
+        frame.render_widget(Paragraph::new(Text::from(lines)).block(block), areas[0]);
+        frame.render_widget(
+            Paragraph::new(Line::from(help)).block(Block::bordered()),
+            areas[1],
+        );
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, history: &mut History) -> bool {
+        if self.index >= self.entries.len() {
+            return matches!(key.code, KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q'));
+        }
+
+        match key.code {
+            KeyCode::Esc => return true,
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Enter if !self.input.trim().is_empty() => self.answer(history),
+            KeyCode::Char(char) => self.input.push(char),
+            _ => {}
+        }
+
+        false
+    }
+
+    fn answer(&mut self, history: &mut History) {
+        let (word, chord) = self.entries[self.index].clone();
+        let correct = self.input.trim().to_lowercase() == word.to_lowercase();
+
+        history.push(Record {
+            timestamp: clock::now(),
+            session: self.session,
+            grade: if correct { Grade::Good } else { Grade::Again },
+            time: u64::try_from(self.shown.elapsed().as_millis()).unwrap_or(u64::MAX),
+            chord: chord.clone(),
+            word: word.clone(),
+        });
+        self.correct += usize::from(correct);
+        self.last = Some((correct, word, chord));
+
+        self.index += 1;
+        self.input.clear();
+        self.shown = Instant::now();
+    }
+}
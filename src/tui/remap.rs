@@ -0,0 +1,351 @@
+use std::mem;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, List, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    chords::{Chord, Chords, Notation},
+    inflection::lemma,
+    layout::Layout as KeyboardLayout,
+    suggest::{merge, Suggestion},
+    words::Words,
+};
+
+pub const SUGGESTIONS: usize = 5;
+
+#[derive(Clone)]
+pub struct Move {
+    pub word: String,
+    pub from: Chord,
+    pub to: Chord,
+}
+
+pub enum Action {
+    Close,
+    Apply(Vec<Move>),
+}
+
+#[derive(Default)]
+struct Plan {
+    moves: Vec<Move>,
+    problems: Vec<String>,
+}
+
+pub struct Remap {
+    word: String,
+    chord: Chord,
+    derived: Vec<(String, Chord, char)>,
+    suggestions: Vec<Suggestion>,
+    state: ListState,
+    edit: Option<Chord>,
+    plan: Plan,
+    message: Option<&'static str>,
+}
+
+impl Remap {
+    pub fn new(
+        word: &str,
+        chord: &Chord,
+        suffix_keys: &[(String, char)],
+        words: &Words,
+        chords: &Chords,
+        layout: &KeyboardLayout,
+        suggestions: Vec<Suggestion>,
+    ) -> Self {
+        let derived = suffix_keys
+            .iter()
+            .filter_map(|(_, key)| {
+                let mut derived = chord.clone();
+                if chord.is_empty() || !derived.insert(*key) {
+                    return None;
+                }
+                let form = chords.get(&derived)?;
+
+                (lemma(form, |word| words.contains(word)) == word)
+                    .then(|| (form.clone(), derived, *key))
+            })
+            .collect();
+
+        let mut remap = Self {
+            word: word.to_owned(),
+            chord: chord.clone(),
+            derived,
+            suggestions,
+            state: ListState::default().with_selected(Some(0)),
+            edit: None,
+            plan: Plan::default(),
+            message: None,
+        };
+        remap.update_plan(chords, layout);
+
+        remap
+    }
+
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    pub fn add_candidates(
+        &mut self,
+        candidates: Vec<Chord>,
+        chords: &Chords,
+        layout: &KeyboardLayout,
+    ) {
+        let suggestions = mem::take(&mut self.suggestions);
+        self.suggestions = merge(suggestions, candidates, chords, layout, SUGGESTIONS);
+        self.update_plan(chords, layout);
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect, notation: &Notation) {
+        let areas = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(SUGGESTIONS as u16 + 2),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+        let current = if self.chord.is_empty() {
+            String::from(" has no chord yet")
+        } else {
+            format!(" currently {}", notation.format(&self.chord))
+        };
+        let current = Line::from(vec![
+            Span::from(self.word.as_str()).bold(),
+            Span::from(current),
+        ]);
+        let block = Block::bordered().title(Span::from("Remap chord").bold());
+        frame.render_widget(Paragraph::new(current).block(block), areas[0]);
+
+        match &self.edit {
+            Some(chord) => {
+                let block = Block::bordered().title(Span::from("New chord").bold());
+                let chord = Line::from(notation.format(chord));
+                frame.render_widget(Paragraph::new(chord).block(block), areas[1]);
+            }
+            None => {
+                let lines = self
+                    .suggestions
+                    .iter()
+                    .zip(1..)
+                    .map(|(suggestion, number)| {
+                        Line::from(format!(
+                            "{number}. {chord} (difficulty {score})",
+                            chord = notation.format(&suggestion.chord),
+                            score = suggestion.score,
+                        ))
+                    });
+                let block = Block::bordered().title(Span::from("Suggestions").bold());
+                let list = List::new(lines)
+                    .block(block)
+                    .highlight_style(Style::new().reversed());
+                frame.render_stateful_widget(list, areas[1], &mut self.state);
+            }
+        }
+
+        let mut lines: Vec<_> = self
+            .plan
+            .moves
+            .iter()
+            .map(|change| {
+                let from = if change.from.is_empty() {
+                    String::from("(none)")
+                } else {
+                    notation.format(&change.from)
+                };
+                Line::from(format!(
+                    "{word}: {from} → {to}",
+                    word = change.word,
+                    to = notation.format(&change.to),
+                ))
+            })
+            .collect();
+        lines.extend(
+            self.plan
+                .problems
+                .iter()
+                .map(|problem| Line::from(problem.as_str()).red()),
+        );
+        let block = Block::bordered().title(Span::from("Changes").bold());
+        frame.render_widget(Paragraph::new(Text::from(lines)).block(block), areas[2]);
+
+        let help = match (self.message, &self.edit) {
+            (Some(message), _) => Line::from(format!(" {message}")).red(),
+            (None, Some(_)) => Line::from(" Type keys · Enter apply · Backspace clear · Esc back"),
+            (None, None) => Line::from(" Enter apply · 1-5/j/k pick · e type chord · Esc cancel"),
+        };
+        frame.render_widget(Paragraph::new(help), areas[3]);
+    }
+
+    pub fn handle_key(
+        &mut self,
+        key: KeyEvent,
+        chords: &Chords,
+        layout: &KeyboardLayout,
+    ) -> Option<Action> {
+        self.message = None;
+
+        if let Some(chord) = &mut self.edit {
+            match key.code {
+                KeyCode::Char(char) => {
+                    chord.insert(char);
+                }
+                KeyCode::Backspace => chord.clear(),
+                KeyCode::Enter => return self.apply(),
+                KeyCode::Esc => self.edit = None,
+                _ => {}
+            }
+            self.update_plan(chords, layout);
+
+            return None;
+        }
+
+        let last = self.suggestions.len().saturating_sub(1);
+        let selected = self.state.selected().unwrap_or_default();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Some(Action::Close),
+            KeyCode::Enter => return self.apply(),
+            KeyCode::Char('j') | KeyCode::Down => self.state.select(Some((selected + 1).min(last))),
+            KeyCode::Char('k') | KeyCode::Up => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Char(char @ '1'..='5') => {
+                self.state
+                    .select(Some(usize::from(char as u8 - b'1').min(last)));
+            }
+            KeyCode::Char('e') => self.edit = Some(Chord::default()),
+            _ => {}
+        }
+        self.update_plan(chords, layout);
+
+        None
+    }
+
+    fn apply(&mut self) -> Option<Action> {
+        if self.plan.moves.is_empty() {
+            self.message = Some("Pick a new chord first");
+            None
+        } else if !self.plan.problems.is_empty() {
+            self.message = Some("Resolve the problems before applying");
+            None
+        } else {
+            Some(Action::Apply(self.plan.moves.clone()))
+        }
+    }
+
+    fn candidate(&self) -> Option<&Chord> {
+        match &self.edit {
+            Some(chord) => Some(chord).filter(|chord| !chord.is_empty()),
+            None => self
+                .suggestions
+                .get(self.state.selected()?)
+                .map(|suggestion| &suggestion.chord),
+        }
+    }
+
+    fn update_plan(&mut self, chords: &Chords, layout: &KeyboardLayout) {
+        let Some(chord) = self.candidate() else {
+            self.plan = Plan::default();
+            return;
+        };
+
+        let mut plan = Plan::default();
+        if *chord == self.chord {
+            plan.problems
+                .push(String::from("The new chord is the current chord"));
+        }
+        plan.moves.push(Move {
+            word: self.word.clone(),
+            from: self.chord.clone(),
+            to: chord.clone(),
+        });
+        for (form, derived, key) in &self.derived {
+            let mut to = chord.clone();
+            if to.insert(*key) {
+                plan.moves.push(Move {
+                    word: form.clone(),
+                    from: derived.clone(),
+                    to,
+                });
+            } else {
+                plan.problems.push(format!(
+                    "{form}: suffix key {key} is already part of the new chord"
+                ));
+            }
+        }
+
+        for change in &plan.moves {
+            let vacated = plan.moves.iter().any(|other| other.from == change.to);
+            if let Some(existing) = chords.get(&change.to).filter(|_| !vacated) {
+                plan.problems.push(format!(
+                    "{word}: {chord} is already assigned to {existing}",
+                    word = change.word,
+                    chord = change.to.as_str(),
+                ));
+            }
+            plan.problems.extend(
+                layout
+                    .violations(&change.to)
+                    .into_iter()
+                    .map(|violation| format!("{}: {violation}", change.word)),
+            );
+        }
+
+        self.plan = plan;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_moves_with_derived_forms() {
+        let mut words = Words::default();
+        words.import("walk\nwalks\nx\n");
+        let chords = Chords::from_pairs(&[("K+W", "walk"), ("K+S+W", "walks"), ("G+H", "x")]);
+        let layout = KeyboardLayout::qwerty();
+        let chord = "K+W".parse().unwrap();
+        let suffix_keys = [("s".to_owned(), 'S')];
+        let mut remap = Remap::new(
+            "walk",
+            &chord,
+            &suffix_keys,
+            &words,
+            &chords,
+            &layout,
+            Vec::new(),
+        );
+        let type_keys = |remap: &mut Remap, keys: &str| {
+            let mut action = None;
+            for code in keys.chars().map(KeyCode::Char).chain([KeyCode::Enter]) {
+                action = remap.handle_key(KeyEvent::from(code), &chords, &layout);
+            }
+            action
+        };
+
+        assert!(type_keys(&mut remap, "egh").is_none());
+        assert!(remap.plan.problems[0].ends_with("G+H is already assigned to x"));
+
+        remap.handle_key(KeyEvent::from(KeyCode::Backspace), &chords, &layout);
+        let Some(Action::Apply(moves)) = type_keys(&mut remap, "fj") else {
+            panic!("expected the remap to apply");
+        };
+        let moves: Vec<_> = moves
+            .iter()
+            .map(|change| {
+                format!(
+                    "{}: {} -> {}",
+                    change.word,
+                    change.from.as_str(),
+                    change.to.as_str()
+                )
+            })
+            .collect();
+        assert_eq!(moves, ["walk: K+W -> F+J", "walks: K+S+W -> F+J+S"]);
+    }
+}
@@ -0,0 +1,93 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span, Text},
+    widgets::{Block, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    chords::{Chord, Chords, Notation},
+    words::Words,
+};
+
+use super::centered;
+
+pub enum Resolution {
+    Overwrite,
+    Swap,
+    Cancel,
+}
+
+pub struct Conflict {
+    pub chord: Chord,
+    pub word: String,
+    pub existing: String,
+    pub previous: Chord,
+}
+
+impl Conflict {
+    pub fn draw(&self, frame: &mut Frame, area: Rect, notation: &Notation) {
+        let chord = notation.format(&self.chord);
+        let swap = if self.previous.is_empty() {
+            format!(
+                "s: swap, {existing} loses its chord",
+                existing = self.existing
+            )
+        } else {
+            format!(
+                "s: swap, {existing} gets {previous}",
+                existing = self.existing,
+                previous = notation.format(&self.previous)
+            )
+        };
+
+        let text = Text::from(vec![
+            Line::from(format!(
+                "{chord} is already assigned to {existing}.",
+                existing = self.existing
+            )),
+            Line::default(),
+            Line::from(format!(
+                "o: overwrite, assign {chord} to {word}",
+                word = self.word
+            )),
+            Line::from(swap),
+            Line::from("c: cancel"),
+        ]);
+
+        let area = centered(area, 60, 7);
+        let block = Block::bordered().title(Span::from("Chord conflict").bold().red());
+        let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+    }
+
+    pub fn handle_key(key: KeyEvent) -> Option<Resolution> {
+        match key.code {
+            KeyCode::Char('o') => Some(Resolution::Overwrite),
+            KeyCode::Char('s') => Some(Resolution::Swap),
+            KeyCode::Char('c') | KeyCode::Esc => Some(Resolution::Cancel),
+            _ => None,
+        }
+    }
+
+    pub fn resolve(self, resolution: &Resolution, words: &mut Words, chords: &mut Chords) {
+        let existing_chord = match resolution {
+            Resolution::Overwrite => Chord::default(),
+            Resolution::Swap => self.previous.clone(),
+            Resolution::Cancel => return,
+        };
+
+        chords.remove(&self.previous);
+        chords.insert(self.chord.clone(), self.word.clone());
+        if !existing_chord.is_empty() {
+            chords.insert(existing_chord.clone(), self.existing.clone());
+        }
+
+        words.update_chord(self.existing, existing_chord);
+        words.update_chord(self.word, self.chord);
+    }
+}
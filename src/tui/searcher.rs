@@ -0,0 +1,124 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Weak,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    search::{search, Hit, Query, Sort},
+    words::Words,
+};
+
+const DEBOUNCE: Duration = Duration::from_millis(30);
+
+struct Request {
+    generation: u64,
+    // only held strongly while searching, see `Searcher::edit`
+    words: Weak<Words>,
+    query: Query,
+    sort: Sort,
+}
+
+struct Results {
+    generation: u64,
+    hits: Vec<Hit>,
+}
+
+pub struct Searcher {
+    requests: Sender<Request>,
+    results: Receiver<Results>,
+    latest: Arc<AtomicU64>,
+    pending: bool,
+}
+
+impl Searcher {
+    pub fn spawn() -> Self {
+        let (requests, request_receiver) = channel();
+        let (result_sender, results) = channel();
+        let latest = Arc::new(AtomicU64::new(0));
+
+        let worker_latest = latest.clone();
+        thread::spawn(move || run(&request_receiver, &result_sender, &worker_latest));
+
+        Self {
+            requests,
+            results,
+            latest,
+            pending: false,
+        }
+    }
+
+    pub fn search(&mut self, words: &Arc<Words>, query: Query, sort: Sort) {
+        let generation = self.cancel();
+        self.pending = true;
+
+        let _ = self.requests.send(Request {
+            generation,
+            words: Arc::downgrade(words),
+            query,
+            sort,
+        });
+    }
+
+    /// Waits for the worker to drop the words, so they are changed in place.
+    pub fn edit<'a>(&mut self, words: &'a mut Arc<Words>) -> &'a mut Words {
+        self.cancel();
+        while Arc::strong_count(words) > 1 {
+            thread::yield_now();
+        }
+
+        Arc::make_mut(words)
+    }
+
+    pub fn cancel(&mut self) -> u64 {
+        self.pending = false;
+        self.latest.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    pub fn try_recv(&mut self) -> Option<Vec<Hit>> {
+        let latest = self.latest.load(Ordering::Relaxed);
+
+        let hits = self
+            .results
+            .try_iter()
+            .filter(|results| results.generation == latest)
+            .last()
+            .map(|results| results.hits)?;
+        self.pending = false;
+
+        Some(hits)
+    }
+}
+
+fn run(requests: &Receiver<Request>, results: &Sender<Results>, latest: &AtomicU64) {
+    while let Ok(mut request) = requests.recv() {
+        while let Ok(newer) = requests.recv_timeout(DEBOUNCE) {
+            request = newer;
+        }
+
+        let cancelled = || latest.load(Ordering::Relaxed) != request.generation;
+        let Some(words) = request.words.upgrade().filter(|_| !cancelled()) else {
+            continue;
+        };
+        let Some(hits) = search(&words, &request.query, request.sort, cancelled) else {
+            continue;
+        };
+        drop(words);
+
+        let results_sent = results.send(Results {
+            generation: request.generation,
+            hits,
+        });
+        if results_sent.is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::{
+    io::{ErrorKind, Result},
+    path::Path,
+};
+
+use crate::{
+    search::{Case, Matching},
+    storage::{read_to_string, write},
+};
+
+use super::Sort;
+
+#[derive(Default)]
+pub struct Session {
+    pub(super) search: String,
+    pub(super) case: Case,
+    pub(super) matching: Matching,
+    pub(super) sort: Sort,
+    pub(super) pinned_first: bool,
+    pub(super) selected: Option<String>,
+}
+
+impl Session {
+    pub fn read_from_file_if_exists(path: impl AsRef<Path>) -> Result<Self> {
+        let lines = match read_to_string(path) {
+            Ok(lines) => lines,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+
+        let mut session = Self::default();
+
+        // the session is only a convenience, so stale or broken entries are skipped
+        for line in lines.lines() {
+            let Some((name, value)) = line.split_once(" = ") else {
+                continue;
+            };
+
+            match name {
+                "search" => session.search = value.to_owned(),
+                "case" => session.case = value.parse().unwrap_or_default(),
+                "matching" => session.matching = value.parse().unwrap_or_default(),
+                "sort" => session.sort = value.parse().unwrap_or_default(),
+                "pinned_first" => session.pinned_first = value.parse().unwrap_or_default(),
+                "selected" => session.selected = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+
+        Ok(session)
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut lines = format!(
+            "search = {}\ncase = {}\nmatching = {}\nsort = {}\npinned_first = {}\n",
+            self.search,
+            self.case,
+            self.matching,
+            self.sort.name(),
+            self.pinned_first,
+        );
+        if let Some(selected) = &self.selected {
+            lines.push_str(&format!("selected = {selected}\n"));
+        }
+
+        write(path, lines)
+    }
+}
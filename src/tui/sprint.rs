@@ -0,0 +1,263 @@
+use std::num::NonZeroUsize;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::Stylize,
+    text::{Line, Span, Text},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use super::resolver::{Conflict, Resolution};
+use crate::{
+    chords::{Chord, Chords, Notation},
+    layout::Layout as KeyboardLayout,
+    suggest::{suggest, Suggestion},
+    word_set::WordSet,
+    words::{Cutoff, Words},
+};
+
+const SUGGESTIONS: usize = 5;
+
+pub struct Sprint {
+    index: usize,
+    word: String,
+    rank: Option<NonZeroUsize>,
+    suggestions: Vec<Suggestion>,
+    edit: Option<Chord>,
+    conflict: Option<Conflict>,
+    message: Option<String>,
+    stop_words: WordSet,
+    blacklist: WordSet,
+    cutoff: Option<Cutoff>,
+}
+
+impl Sprint {
+    pub fn new(
+        words: &Words,
+        chords: &Chords,
+        layout: &KeyboardLayout,
+        stop_words: &WordSet,
+        blacklist: &WordSet,
+        cutoff: Option<Cutoff>,
+    ) -> Option<Self> {
+        let mut sprint = Self {
+            index: 0,
+            word: String::new(),
+            rank: None,
+            suggestions: Vec::new(),
+            edit: None,
+            conflict: None,
+            message: None,
+            stop_words: stop_words.clone(),
+            blacklist: blacklist.clone(),
+            cutoff,
+        };
+
+        sprint.advance(0, words, chords, layout).then_some(sprint)
+    }
+
+    pub fn draw(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        notation: &Notation,
+        layout: &KeyboardLayout,
+    ) {
+        let areas = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+        ])
+        .split(area);
+
+        let rank = self
+            .rank
+            .map_or(String::new(), |rank| format!(" (rank {rank})"));
+        let word = Line::from(vec![
+            Span::from(self.word.as_str()).bold(),
+            Span::from(rank),
+        ]);
+        let block = Block::bordered().title(Span::from("Assignment sprint").bold());
+        frame.render_widget(Paragraph::new(word).block(block), areas[0]);
+
+        let text = match &self.edit {
+            Some(chord) => {
+                let mut lines = vec![Line::from(format!("Chord: {}", notation.format(chord)))];
+                lines.extend(
+                    layout
+                        .violations(chord)
+                        .into_iter()
+                        .map(|violation| Line::from(violation.to_string()).red()),
+                );
+                Text::from(lines)
+            }
+            None => Text::from(
+                self.suggestions
+                    .iter()
+                    .zip(1..)
+                    .map(|(suggestion, number)| {
+                        Line::from(format!(
+                            "{number}. {chord} (difficulty {score})",
+                            chord = notation.format(&suggestion.chord),
+                            score = suggestion.score,
+                        ))
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        };
+        let title = if self.edit.is_some() {
+            "Edit chord"
+        } else {
+            "Suggestions"
+        };
+        let block = Block::bordered().title(Span::from(title).bold());
+        frame.render_widget(Paragraph::new(text).block(block), areas[1]);
+
+        let help = match (&self.message, &self.edit) {
+            (Some(message), _) => Line::from(message.as_str()).red(),
+            (None, Some(_)) => Line::from("Type keys · Enter assign · Backspace clear · Esc back"),
+            (None, None) => {
+                Line::from("Enter accept · 1-5 pick · e edit · s skip · Esc leave sprint")
+            }
+        };
+        frame.render_widget(Paragraph::new(help).block(Block::bordered()), areas[2]);
+
+        if let Some(conflict) = &self.conflict {
+            conflict.draw(frame, area, notation);
+        }
+    }
+
+    pub fn handle_key(
+        &mut self,
+        key: KeyEvent,
+        words: &mut Words,
+        chords: &mut Chords,
+        layout: &KeyboardLayout,
+    ) -> bool {
+        self.message = None;
+
+        if let Some(conflict) = self.conflict.take() {
+            return match Conflict::handle_key(key) {
+                Some(Resolution::Cancel) => false,
+                Some(resolution) => {
+                    conflict.resolve(&resolution, words, chords);
+                    !self.advance(self.index + 1, words, chords, layout)
+                }
+                None => {
+                    self.conflict = Some(conflict);
+                    false
+                }
+            };
+        }
+
+        if let Some(chord) = &mut self.edit {
+            match key.code {
+                KeyCode::Char(char) => {
+                    chord.insert(char);
+                }
+                KeyCode::Backspace => chord.clear(),
+                KeyCode::Enter if !chord.is_empty() => {
+                    let chord = chord.clone();
+
+                    if let Some(existing) = chords.get(&chord) {
+                        self.conflict = Some(Conflict {
+                            chord,
+                            word: self.word.clone(),
+                            existing: existing.clone(),
+                            previous: Chord::default(),
+                        });
+                    } else {
+                        return self.assign(chord, words, chords, layout);
+                    }
+                }
+                KeyCode::Esc => self.edit = None,
+                _ => {}
+            }
+
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Enter => self.assign_suggestion(0, words, chords, layout),
+            KeyCode::Char(char @ '1'..='5') => {
+                let index = usize::from(char as u8 - b'1');
+                self.assign_suggestion(index, words, chords, layout)
+            }
+            KeyCode::Char('e') => {
+                self.edit = Some(Chord::default());
+                false
+            }
+            KeyCode::Char('s') | KeyCode::Tab | KeyCode::Right => {
+                !self.advance(self.index + 1, words, chords, layout)
+            }
+            KeyCode::Esc => true,
+            _ => false,
+        }
+    }
+
+    fn assign_suggestion(
+        &mut self,
+        index: usize,
+        words: &mut Words,
+        chords: &mut Chords,
+        layout: &KeyboardLayout,
+    ) -> bool {
+        match self.suggestions.get(index) {
+            Some(suggestion) => {
+                let chord = suggestion.chord.clone();
+                self.assign(chord, words, chords, layout)
+            }
+            None => {
+                self.message = Some(String::from("No such suggestion"));
+                false
+            }
+        }
+    }
+
+    fn assign(
+        &mut self,
+        chord: Chord,
+        words: &mut Words,
+        chords: &mut Chords,
+        layout: &KeyboardLayout,
+    ) -> bool {
+        chords.insert(chord.clone(), self.word.clone());
+        words.update_chord(self.word.clone(), chord);
+
+        !self.advance(self.index + 1, words, chords, layout)
+    }
+
+    fn advance(
+        &mut self,
+        from: usize,
+        words: &Words,
+        chords: &Chords,
+        layout: &KeyboardLayout,
+    ) -> bool {
+        let next = words
+            .iter()
+            .enumerate()
+            .skip(from)
+            .find(|(_, (word, entry))| {
+                entry.chord.is_empty()
+                    && word.chars().count() > 2
+                    && !self.stop_words.contains(word)
+                    && !self.blacklist.contains(word)
+                    && !self.cutoff.is_some_and(|cutoff| cutoff.excludes(entry))
+            });
+
+        let Some((index, (word, entry))) = next else {
+            return false;
+        };
+
+        self.index = index;
+        self.word.clone_from(word);
+        self.rank = entry.rank;
+        self.suggestions = suggest(word, chords, layout, &self.blacklist, SUGGESTIONS);
+        self.edit = None;
+
+        true
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{chords::Notation, layout::Layout as KeyboardLayout, words::Words};
+
+const IMPULSE_GROUP: &str = "Impulse chords";
+
+pub enum Action {
+    Close,
+    Open(String),
+}
+
+enum Item {
+    Group { key: String, count: usize },
+    Entry { chord: String, word: String },
+}
+
+#[derive(Default)]
+pub struct Tree {
+    expanded: BTreeSet<String>,
+    state: ListState,
+}
+
+impl Tree {
+    pub fn new() -> Self {
+        Self {
+            state: ListState::default().with_selected(Some(0)),
+            ..Self::default()
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        words: &Words,
+        notation: &Notation,
+        layout: &KeyboardLayout,
+    ) {
+        let areas = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(area);
+
+        let items = self.items(words, notation, layout);
+        let lines = items.iter().map(|item| match item {
+            Item::Group { key, count } => {
+                let marker = if self.expanded.contains(key) {
+                    '▾'
+                } else {
+                    '▸'
+                };
+                Line::from(vec![
+                    Span::from(format!("{marker} {key}")).bold(),
+                    Span::from(format!(" ({count})")).dim(),
+                ])
+            }
+            Item::Entry { chord, word } => Line::from(format!("    {chord:<12} {word}")),
+        });
+
+        let block = Block::bordered().title(Span::from("Chords by first key").bold());
+        let list = List::new(lines)
+            .block(block)
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(list, areas[0], &mut self.state);
+
+        let help = "Enter/l expand or open · h collapse · j/k move · e expand all · E collapse all · Esc/t back";
+        frame.render_widget(Paragraph::new(Line::from(format!(" {help}"))), areas[1]);
+    }
+
+    pub fn handle_key(
+        &mut self,
+        key: KeyEvent,
+        words: &Words,
+        notation: &Notation,
+        layout: &KeyboardLayout,
+    ) -> Option<Action> {
+        if matches!(key.code, KeyCode::Esc | KeyCode::Char('t' | 'q')) {
+            return Some(Action::Close);
+        }
+
+        let items = self.items(words, notation, layout);
+        let last = items.len().checked_sub(1)?;
+        let selected = self.state.selected().unwrap_or_default().min(last);
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.state.select(Some((selected + 1).min(last))),
+            KeyCode::Char('k') | KeyCode::Up => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Enter | KeyCode::Char('l' | ' ') | KeyCode::Right => match items.get(selected)
+            {
+                Some(Item::Group { key, .. }) => self.toggle(key),
+                Some(Item::Entry { word, .. }) => return Some(Action::Open(word.clone())),
+                None => {}
+            },
+            KeyCode::Char('h') | KeyCode::Left => {
+                let group = items[..=selected].iter().enumerate().rev().find_map(
+                    |(index, item)| match item {
+                        Item::Group { key, .. } => Some((index, key)),
+                        Item::Entry { .. } => None,
+                    },
+                );
+                if let Some((index, key)) = group {
+                    self.expanded.remove(key);
+                    self.state.select(Some(index));
+                }
+            }
+            KeyCode::Char('e') => {
+                self.expanded = items
+                    .iter()
+                    .filter_map(|item| match item {
+                        Item::Group { key, .. } => Some(key.clone()),
+                        Item::Entry { .. } => None,
+                    })
+                    .collect();
+            }
+            KeyCode::Char('E') => {
+                let group = items[..selected]
+                    .iter()
+                    .filter(|item| matches!(item, Item::Group { .. }))
+                    .count();
+                let current = matches!(items.get(selected), Some(Item::Group { .. }));
+                self.expanded.clear();
+                self.state.select(Some(if current {
+                    group
+                } else {
+                    group.saturating_sub(1)
+                }));
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn toggle(&mut self, key: &str) {
+        if !self.expanded.remove(key) {
+            self.expanded.insert(key.to_owned());
+        }
+    }
+
+    fn items(&self, words: &Words, notation: &Notation, layout: &KeyboardLayout) -> Vec<Item> {
+        let mut groups: BTreeMap<(bool, String), Vec<(String, String)>> = BTreeMap::new();
+        for (word, entry) in words.iter().filter(|(_, entry)| !entry.chord.is_empty()) {
+            let chord = notation.format(&entry.chord);
+            let group = if layout.is_impulse(&entry.chord) {
+                (true, IMPULSE_GROUP.to_owned())
+            } else {
+                (
+                    false,
+                    notation
+                        .format_canonical(&entry.chord)
+                        .split('+')
+                        .next()
+                        .unwrap_or_default()
+                        .to_owned(),
+                )
+            };
+
+            groups.entry(group).or_default().push((chord, word.clone()));
+        }
+
+        let mut items = Vec::new();
+        for ((_, key), mut entries) in groups {
+            let expanded = self.expanded.contains(&key);
+            items.push(Item::Group {
+                count: entries.len(),
+                key,
+            });
+
+            if expanded {
+                entries.sort();
+                items.extend(
+                    entries
+                        .into_iter()
+                        .map(|(chord, word)| Item::Entry { chord, word }),
+                );
+            }
+        }
+
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(tree: &Tree, words: &Words, layout: &KeyboardLayout) -> Vec<String> {
+        tree.items(words, &Notation::default(), layout)
+            .into_iter()
+            .map(|item| match item {
+                Item::Group { key, count } => format!("{key} ({count})"),
+                Item::Entry { chord, word } => format!("{chord} {word}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn expand_and_open_groups() {
+        let mut words = Words::default();
+        words.import("the\nand\nthat\none\nof\n");
+        for (word, chord) in [
+            ("the", "H+T"),
+            ("and", "A+N"),
+            ("that", "H+T+A"),
+            ("one", "1+2"),
+        ] {
+            words.update_chord(word.to_owned(), chord.parse().unwrap());
+        }
+        let layout = KeyboardLayout::qwerty();
+        let notation = Notation::default();
+        let key = |code| KeyEvent::from(code);
+        let mut tree = Tree::new();
+
+        assert_eq!(
+            labels(&tree, &words, &layout),
+            ["A (2)", "H (1)", "Impulse chords (1)"]
+        );
+
+        tree.handle_key(key(KeyCode::Enter), &words, &notation, &layout);
+        assert_eq!(
+            labels(&tree, &words, &layout),
+            [
+                "A (2)",
+                "A+H+T that",
+                "A+N and",
+                "H (1)",
+                "Impulse chords (1)"
+            ]
+        );
+
+        tree.handle_key(key(KeyCode::Char('j')), &words, &notation, &layout);
+        tree.handle_key(key(KeyCode::Char('j')), &words, &notation, &layout);
+        let action = tree.handle_key(key(KeyCode::Enter), &words, &notation, &layout);
+        assert!(matches!(action, Some(Action::Open(word)) if word == "and"));
+
+        tree.handle_key(key(KeyCode::Char('h')), &words, &notation, &layout);
+        assert_eq!(tree.state.selected(), Some(0));
+        assert_eq!(labels(&tree, &words, &layout).len(), 3);
+
+        tree.handle_key(key(KeyCode::Char('e')), &words, &notation, &layout);
+        assert_eq!(labels(&tree, &words, &layout).len(), 7);
+        let action = tree.handle_key(key(KeyCode::Esc), &words, &notation, &layout);
+        assert!(matches!(action, Some(Action::Close)));
+    }
+}
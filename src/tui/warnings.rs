@@ -0,0 +1,101 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListState, Paragraph},
+    Frame,
+};
+
+use crate::validate::Warning;
+
+pub enum Action {
+    Close,
+    Open(String),
+    Suppress(usize),
+}
+
+pub struct Warnings {
+    warnings: Vec<Warning>,
+    state: ListState,
+    note: Option<&'static str>,
+}
+
+impl Warnings {
+    pub fn new(warnings: Vec<Warning>) -> Self {
+        Self {
+            warnings,
+            state: ListState::default().with_selected(Some(0)),
+            note: None,
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Warning> {
+        self.warnings.get(index)
+    }
+
+    pub fn update(&mut self, warnings: Vec<Warning>) {
+        let last = warnings.len().saturating_sub(1);
+        self.state
+            .select(Some(self.state.selected().unwrap_or_default().min(last)));
+        self.warnings = warnings;
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let areas = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(area);
+
+        let title = format!("Warnings ({})", self.warnings.len());
+        let block = Block::bordered().title(Span::from(title).bold());
+        if self.warnings.is_empty() {
+            let paragraph =
+                Paragraph::new(Line::from(" No warnings, the library looks clean").dim());
+            frame.render_widget(paragraph.block(block), areas[0]);
+        } else {
+            let lines = self.warnings.iter().map(|warning| {
+                let line = Line::from(warning.to_string());
+                match warning {
+                    Warning::Impossible { .. } => line.red(),
+                    Warning::Orphan { .. } => line.dim(),
+                    _ => line,
+                }
+            });
+            let list = List::new(lines)
+                .block(block)
+                .highlight_style(Style::new().reversed());
+            frame.render_stateful_widget(list, areas[0], &mut self.state);
+        }
+
+        let help = self
+            .note
+            .unwrap_or("Enter/l jump to entry · s suppress · j/k move · Esc/w back");
+        frame.render_widget(Paragraph::new(Line::from(format!(" {help}"))), areas[1]);
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        self.note = None;
+        if matches!(key.code, KeyCode::Esc | KeyCode::Char('w' | 'q')) {
+            return Some(Action::Close);
+        }
+
+        let last = self.warnings.len().checked_sub(1)?;
+        let selected = self.state.selected().unwrap_or_default().min(last);
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.state.select(Some((selected + 1).min(last))),
+            KeyCode::Char('k') | KeyCode::Up => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Char('g') | KeyCode::Home => self.state.select(Some(0)),
+            KeyCode::Char('G') | KeyCode::End => self.state.select(Some(last)),
+            KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => {
+                let word = self.warnings[selected].words().first()?.to_string();
+                return Some(Action::Open(word));
+            }
+            KeyCode::Char('s') => match self.warnings[selected].suppression() {
+                Some(_) => return Some(Action::Suppress(selected)),
+                None => self.note = Some("Only duplicate and near-miss warnings can be suppressed"),
+            },
+            _ => {}
+        }
+
+        None
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::BTreeMap,
+    io::{Error, ErrorKind, Result},
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    chords::{Chord, Chords},
+    clock::DAY_MS,
+    storage::read_to_string,
+};
+
+const MISFIRE_WINDOW_MS: u64 = 2000;
+const PAUSE_MS: u64 = 2000;
+const HISTORY_BUCKET_MS: u64 = 7 * DAY_MS;
+pub const HISTORY_BUCKETS: usize = 24;
+
+pub enum Event {
+    Chord(Chord),
+    Char(char),
+    Backspace,
+}
+
+#[derive(Default)]
+pub struct Log(Vec<(u64, Event)>);
+
+impl Log {
+    pub fn read_from_file_if_exists(
+        path: impl AsRef<Path>,
+        progress: &AtomicUsize,
+    ) -> Result<Self> {
+        match Self::read_from_file_with_progress(path, progress) {
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            result => result,
+        }
+    }
+
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::read_from_file_with_progress(path, &AtomicUsize::new(0))
+    }
+
+    fn read_from_file_with_progress(
+        path: impl AsRef<Path>,
+        progress: &AtomicUsize,
+    ) -> Result<Self> {
+        let lines = read_to_string(path)?;
+
+        let events = lines
+            .split('\n')
+            .inspect(|_| {
+                progress.fetch_add(1, Ordering::Relaxed);
+            })
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                parse_event(line).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Invalid log line {}: {line}", number + 1),
+                    )
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self(events))
+    }
+
+    pub fn uses(&self) -> BTreeMap<Chord, usize> {
+        let mut uses = BTreeMap::new();
+
+        for (_, event) in &self.0 {
+            if let Event::Chord(chord) = event {
+                *uses.entry(chord.clone()).or_default() += 1;
+            }
+        }
+
+        uses
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn chord_time(&self) -> Option<f64> {
+        let mut gaps: Vec<_> = self
+            .0
+            .windows(2)
+            .filter(|pair| matches!(pair[1].1, Event::Chord(_)))
+            .map(|pair| pair[1].0.saturating_sub(pair[0].0))
+            .filter(|gap| *gap <= PAUSE_MS)
+            .collect();
+        gaps.sort_unstable();
+
+        gaps.get(gaps.len() / 2).map(|gap| *gap as f64)
+    }
+
+    pub fn daily_coverage(&self) -> Vec<(u64, f64)> {
+        let mut days: BTreeMap<u64, (usize, usize)> = BTreeMap::new();
+        let mut typing = false;
+        for (timestamp, event) in &self.0 {
+            let (chorded, typed) = days.entry(timestamp / DAY_MS).or_default();
+            match event {
+                Event::Chord(_) => {
+                    *chorded += 1;
+                    typing = false;
+                }
+                Event::Char(char) if char.is_whitespace() => typing = false,
+                Event::Char(_) if !typing => {
+                    *typed += 1;
+                    typing = true;
+                }
+                Event::Char(_) | Event::Backspace => {}
+            }
+        }
+
+        days.into_iter()
+            .filter(|(_, (chorded, typed))| chorded + typed > 0)
+            .map(|(day, (chorded, typed))| (day, chorded as f64 / (chorded + typed) as f64))
+            .collect()
+    }
+
+    pub fn history(&self, now: u64) -> BTreeMap<Chord, [usize; HISTORY_BUCKETS]> {
+        let mut history = BTreeMap::new();
+
+        for (timestamp, event) in &self.0 {
+            let Event::Chord(chord) = event else {
+                continue;
+            };
+            let Some(age) = now.checked_sub(*timestamp) else {
+                continue;
+            };
+            let Some(bucket) = HISTORY_BUCKETS.checked_sub((age / HISTORY_BUCKET_MS) as usize + 1)
+            else {
+                continue;
+            };
+
+            history.entry(chord.clone()).or_insert([0; HISTORY_BUCKETS])[bucket] += 1;
+        }
+
+        history
+    }
+}
+
+fn parse_event(line: &str) -> Option<(u64, Event)> {
+    let (timestamp, event) = line.split_once(' ')?;
+    let timestamp = timestamp.parse().ok()?;
+
+    let event = match event.split_once(' ') {
+        Some(("chord", chord)) => Event::Chord(chord.parse().ok()?),
+        Some(("char", char)) => {
+            let mut chars = char.chars();
+            let char = chars.next()?;
+            chars.next().is_none().then_some(Event::Char(char))?
+        }
+        None if event == "backspace" => Event::Backspace,
+        _ => return None,
+    };
+
+    Some((timestamp, event))
+}
+
+pub struct Misfire {
+    pub chord: Chord,
+    pub word: String,
+    pub fired: usize,
+    pub misfired: usize,
+    pub retyped: BTreeMap<String, usize>,
+}
+
+pub fn misfires(log: &Log, chords: &Chords) -> Vec<Misfire> {
+    let mut misfires: BTreeMap<Chord, Misfire> = BTreeMap::new();
+
+    for (index, (timestamp, event)) in log.0.iter().enumerate() {
+        let Event::Chord(chord) = event else {
+            continue;
+        };
+        let Some(word) = chords.get(chord) else {
+            continue;
+        };
+
+        let misfire = misfires.entry(chord.clone()).or_insert_with(|| Misfire {
+            chord: chord.clone(),
+            word: word.clone(),
+            fired: 0,
+            misfired: 0,
+            retyped: BTreeMap::new(),
+        });
+        misfire.fired += 1;
+
+        let mut following = log.0[index + 1..]
+            .iter()
+            .take_while(|(time, _)| time.saturating_sub(*timestamp) <= MISFIRE_WINDOW_MS)
+            .map(|(_, event)| event)
+            .peekable();
+
+        if !matches!(following.peek(), Some(Event::Backspace)) {
+            continue;
+        }
+        misfire.misfired += 1;
+
+        let mut retyped = String::new();
+        for event in following.skip_while(|event| matches!(event, Event::Backspace)) {
+            match event {
+                Event::Char(char) if !char.is_whitespace() => retyped.push(*char),
+                Event::Chord(chord) if retyped.is_empty() => {
+                    retyped = chords.get(chord).cloned().unwrap_or_default();
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        if !retyped.is_empty() {
+            *misfire.retyped.entry(retyped).or_default() += 1;
+        }
+    }
+
+    let mut misfires: Vec<_> = misfires
+        .into_values()
+        .filter(|misfire| misfire.misfired > 0)
+        .collect();
+    misfires.sort_by(|a, b| b.misfired.cmp(&a.misfired).then(a.chord.cmp(&b.chord)));
+
+    misfires
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_chord_time() {
+        let log = Log(vec![
+            (0, Event::Char('a')),
+            (400, Event::Chord("A+B".parse().unwrap())),
+            (600, Event::Chord("A+B".parse().unwrap())),
+            (10_000, Event::Chord("A+B".parse().unwrap())),
+            (10_300, Event::Char('b')),
+            (10_600, Event::Chord("A+B".parse().unwrap())),
+        ]);
+
+        assert_eq!(log.chord_time(), Some(300.0));
+        assert_eq!(Log::default().chord_time(), None);
+    }
+
+    #[test]
+    fn detect_misfires() {
+        let chords = Chords::from_pairs(&[("A+B", "about"), ("A+V", "above")]);
+        let events = [
+            "0 chord A+B",
+            "100 char  ",
+            "5000 chord A+B",
+            "5100 backspace",
+            "5200 backspace",
+            "5300 chord A+V",
+            "9000 chord A+B",
+            "9100 backspace",
+            "9200 char a",
+            "9300 char b",
+            "9400 char  ",
+        ];
+        let log = Log(events.iter().filter_map(|line| parse_event(line)).collect());
+
+        let misfires = misfires(&log, &chords);
+
+        assert_eq!(misfires.len(), 1);
+        assert_eq!(misfires[0].word, "about");
+        assert_eq!(misfires[0].fired, 3);
+        assert_eq!(misfires[0].misfired, 2);
+        assert_eq!(misfires[0].retyped.get("above"), Some(&1));
+        assert_eq!(misfires[0].retyped.get("ab"), Some(&1));
+    }
+
+    #[test]
+    fn weekly_history() {
+        let now = 30 * HISTORY_BUCKET_MS;
+        let events = [
+            "0 chord A+B",
+            &format!("{} chord A+B", now - HISTORY_BUCKET_MS - 1),
+            &format!("{} chord A+B", now - 1),
+            &format!("{} chord A+B", now),
+            &format!("{} chord A+V", now + 1),
+        ];
+        let log = Log(events.iter().filter_map(|line| parse_event(line)).collect());
+
+        let history = log.history(now);
+        let recent = history[&"A+B".parse().unwrap()];
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(recent[HISTORY_BUCKETS - 1], 2);
+        assert_eq!(recent[HISTORY_BUCKETS - 2], 1);
+        assert_eq!(recent.iter().sum::<usize>(), 3);
+    }
+}
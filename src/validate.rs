@@ -0,0 +1,410 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+use crate::{
+    chords::{Chord, Chords, Notation},
+    config::Config,
+    inflection::lemma,
+    json::Json,
+    layout::{Layout, Violation},
+    words::Words,
+};
+
+pub enum Warning {
+    Impossible {
+        chord: Chord,
+        word: String,
+        violation: Violation,
+    },
+    DuplicateWord {
+        word: String,
+        chords: Vec<Chord>,
+    },
+    NearMiss {
+        first: (Chord, String),
+        second: (Chord, String),
+        distance: usize,
+        weight: f64,
+    },
+    InconsistentFamily {
+        base: (Chord, String),
+        members: Vec<(Chord, String)>,
+    },
+    Shadowing {
+        chord: Chord,
+        word: String,
+        typed: String,
+    },
+    Orphan {
+        chord: Chord,
+        word: String,
+    },
+    UnknownKey {
+        chord: Chord,
+        word: String,
+        error: String,
+    },
+}
+
+impl Warning {
+    pub fn words(&self) -> Vec<&str> {
+        match self {
+            Warning::Impossible { word, .. }
+            | Warning::DuplicateWord { word, .. }
+            | Warning::Orphan { word, .. }
+            | Warning::UnknownKey { word, .. } => vec![word],
+            Warning::Shadowing { word, typed, .. } => vec![word, typed],
+            Warning::NearMiss { first, second, .. } => vec![&first.1, &second.1],
+            Warning::InconsistentFamily { base, members } => [base]
+                .into_iter()
+                .chain(members)
+                .map(|(_, word)| word.as_str())
+                .collect(),
+        }
+    }
+
+    pub fn chords(&self) -> Vec<&Chord> {
+        match self {
+            Warning::Impossible { chord, .. }
+            | Warning::Shadowing { chord, .. }
+            | Warning::Orphan { chord, .. }
+            | Warning::UnknownKey { chord, .. } => vec![chord],
+            Warning::DuplicateWord { chords, .. } => chords.iter().collect(),
+            Warning::NearMiss { first, second, .. } => vec![&first.0, &second.0],
+            Warning::InconsistentFamily { base, members } => [base]
+                .into_iter()
+                .chain(members)
+                .map(|(chord, _)| chord)
+                .collect(),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Warning::Impossible { .. } => "impossible",
+            Warning::DuplicateWord { .. } => "duplicate",
+            Warning::NearMiss { .. } => "near-miss",
+            Warning::InconsistentFamily { .. } => "inconsistent-family",
+            Warning::Shadowing { .. } => "shadowing",
+            Warning::Orphan { .. } => "orphan",
+            Warning::UnknownKey { .. } => "unknown-key",
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("kind", self.kind().into()),
+            ("words", Json::array(self.words())),
+            (
+                "chords",
+                Json::array(self.chords().into_iter().map(Chord::as_str)),
+            ),
+            ("message", self.to_string().into()),
+        ])
+    }
+
+    pub fn suppression(&self) -> Option<&'static str> {
+        match self {
+            Warning::DuplicateWord { .. } => Some("duplicate"),
+            Warning::NearMiss { .. } => Some("near-miss"),
+            Warning::Impossible { .. }
+            | Warning::InconsistentFamily { .. }
+            | Warning::Shadowing { .. }
+            | Warning::Orphan { .. }
+            | Warning::UnknownKey { .. } => None,
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Warning::Impossible {
+                chord,
+                word,
+                violation,
+            } => write!(
+                f,
+                "{chord} ({word}): impossible chord, {violation}",
+                chord = chord.as_str()
+            ),
+            Warning::DuplicateWord { word, chords } => {
+                let chords: Vec<_> = chords.iter().map(Chord::as_str).collect();
+                write!(f, "{word}: duplicate word, assigned to {}", chords.join(", "))
+            }
+            Warning::NearMiss {
+                first: (first, first_word),
+                second: (second, second_word),
+                distance,
+                weight,
+            } => write!(
+                f,
+                "{first} ({first_word}) and {second} ({second_word}): near miss, chords differ by {distance} {keys}, frequency weight {weight:.4}",
+                first = first.as_str(),
+                second = second.as_str(),
+                keys = if *distance == 1 { "key" } else { "keys" },
+            ),
+            Warning::InconsistentFamily {
+                base: (base, base_word),
+                members,
+            } => {
+                let members: Vec<_> = members
+                    .iter()
+                    .map(|(chord, word)| format!("{} ({word})", chord.as_str()))
+                    .collect();
+                write!(
+                    f,
+                    "{base} ({base_word}): inconsistent family, {members} not derived from it",
+                    base = base.as_str(),
+                    members = members.join(", "),
+                )
+            }
+            Warning::Shadowing { chord, word, typed } => write!(
+                f,
+                "{chord} ({word}): shadows typing {typed}, which presses the same keys",
+                chord = chord.as_str()
+            ),
+            Warning::Orphan { chord, word } => write!(
+                f,
+                "{chord} ({word}): orphan, the word is not in the ranked word list",
+                chord = chord.as_str()
+            ),
+            Warning::UnknownKey { word, error, .. } => write!(f, "{word}: {error}"),
+        }
+    }
+}
+
+pub fn validate(chords: &Chords, words: &Words, layout: &Layout, config: &Config) -> Vec<Warning> {
+    let notation = Notation::from_config(config);
+    let mut warnings: Vec<_> = chords
+        .iter()
+        .filter_map(|(chord, word)| {
+            let error = notation.check(&chord).err()?.to_string();
+            Some(Warning::UnknownKey { chord, word, error })
+        })
+        .collect();
+    warnings.extend(chords.iter().flat_map(|(chord, word)| {
+        layout
+            .violations(&chord)
+            .into_iter()
+            .map(move |violation| Warning::Impossible {
+                chord: chord.clone(),
+                word: word.clone(),
+                violation,
+            })
+    }));
+    warnings.extend(duplicate_words(chords));
+    warnings.extend(near_misses(chords, words, config.near_miss_distance));
+    warnings.extend(inconsistent_families(chords, words));
+    warnings.extend(shadowing(chords, words));
+    warnings.extend(orphans(chords, words));
+
+    warnings
+}
+
+fn duplicate_words(chords: &Chords) -> Vec<Warning> {
+    let mut chords_per_word: BTreeMap<String, Vec<Chord>> = BTreeMap::new();
+    for (chord, word) in chords.iter() {
+        chords_per_word.entry(word).or_default().push(chord);
+    }
+
+    chords_per_word
+        .into_iter()
+        .filter(|(word, chords_of_word)| {
+            chords_of_word.len() > 1 && !chords.metadata(word).suppressed.duplicate
+        })
+        .map(|(word, chords)| Warning::DuplicateWord { word, chords })
+        .collect()
+}
+
+fn near_misses(chords: &Chords, words: &Words, max_distance: usize) -> Vec<Warning> {
+    let entries: Vec<_> = chords
+        .iter()
+        .filter(|(_, word)| !chords.metadata(word).suppressed.near_miss)
+        .collect();
+    let frequency = |word: &str| {
+        let index = words.index_of(word);
+        index
+            .and_then(|index| words.get_index(index))
+            .map_or(0.0, |(_, entry)| words.frequency(entry))
+    };
+
+    let mut near_misses = Vec::new();
+    for (index, (first, first_word)) in entries.iter().enumerate() {
+        for (second, second_word) in &entries[index + 1..] {
+            let distance = first.distance(second);
+
+            if distance > 0 && distance <= max_distance {
+                let weight = frequency(first_word) + frequency(second_word);
+                near_misses.push(Warning::NearMiss {
+                    first: (first.clone(), first_word.clone()),
+                    second: (second.clone(), second_word.clone()),
+                    distance,
+                    weight,
+                });
+            }
+        }
+    }
+
+    near_misses.sort_by(|a, b| weight(b).total_cmp(&weight(a)));
+
+    near_misses
+}
+
+fn weight(warning: &Warning) -> f64 {
+    match warning {
+        Warning::NearMiss { weight, .. } => *weight,
+        _ => 0.0,
+    }
+}
+
+fn inconsistent_families(chords: &Chords, words: &Words) -> Vec<Warning> {
+    let mut families: BTreeMap<String, Vec<(Chord, String)>> = BTreeMap::new();
+    for (chord, word) in chords.iter() {
+        let lemma = lemma(&word, |word| words.contains(word));
+        families.entry(lemma).or_default().push((chord, word));
+    }
+
+    families
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .filter_map(|(lemma, mut members)| {
+            let base = members
+                .iter()
+                .position(|(_, word)| *word == lemma)
+                .unwrap_or_else(|| {
+                    (0..members.len())
+                        .min_by_key(|&index| members[index].1.len())
+                        .unwrap_or_default()
+                });
+            let base = members.remove(base);
+
+            let members: Vec<_> = members
+                .into_iter()
+                .filter(|(chord, word)| *word != base.1 && !is_derived(chord, &base.0))
+                .collect();
+
+            (!members.is_empty()).then_some(Warning::InconsistentFamily { base, members })
+        })
+        .collect()
+}
+
+fn shadowing(chords: &Chords, words: &Words) -> Vec<Warning> {
+    let mut typed_words: HashMap<String, &String> = HashMap::new();
+    for (word, _) in words.iter().take(words.ranked()) {
+        let mut keys: Vec<_> = word.chars().map(|char| char.to_ascii_uppercase()).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        if keys.len() > 1
+            && keys.len() == word.chars().count()
+            && keys.iter().all(char::is_ascii_uppercase)
+        {
+            typed_words
+                .entry(keys.into_iter().collect())
+                .or_insert(word);
+        }
+    }
+
+    chords
+        .iter()
+        .filter(|(chord, _)| chord.keys().all(|key| key.len() == 1))
+        .filter_map(|(chord, word)| {
+            let typed = *typed_words.get(&chord.keys().collect::<String>())?;
+            (*typed != word).then(|| Warning::Shadowing {
+                chord,
+                word,
+                typed: typed.clone(),
+            })
+        })
+        .collect()
+}
+
+fn orphans(chords: &Chords, words: &Words) -> Vec<Warning> {
+    chords
+        .iter()
+        .filter(|(_, word)| words.rank(word).is_none())
+        .map(|(chord, word)| Warning::Orphan { chord, word })
+        .collect()
+}
+
+fn is_derived(chord: &Chord, base: &Chord) -> bool {
+    chord.distance(base) == chord.keys().count().abs_diff(base.keys().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weigh_near_misses() {
+        let chords = Chords::from_pairs(&[("A+B", "rare"), ("A+C", "the"), ("B+C", "of")]);
+        let mut words = Words::default();
+        words.import("the\nof\nrare\n");
+
+        let warnings = near_misses(&chords, &words, 2);
+
+        let weights: Vec<_> = warnings.iter().map(weight).collect();
+        assert_eq!(weights.len(), 3);
+        assert!(weights.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(warnings[0].words(), ["the", "of"]);
+        assert!(warnings[0]
+            .to_string()
+            .ends_with("differ by 2 keys, frequency weight 1.5000"));
+    }
+
+    #[test]
+    fn leave_duplicates_to_their_own_warning() {
+        let chords = Chords::from_pairs(&[("K+W", "walk"), ("L+W", "walk"), ("K+S+W", "walks")]);
+        let mut words = Words::default();
+        words.import("walk\nwalks\n");
+
+        assert!(inconsistent_families(&chords, &words).is_empty());
+        assert_eq!(duplicate_words(&chords)[0].words(), ["walk"]);
+
+        let chords = Chords::from_pairs(&[("K+W", "walk"), ("L+S", "walks")]);
+        assert_eq!(
+            inconsistent_families(&chords, &words)[0].words(),
+            ["walk", "walks"]
+        );
+    }
+
+    #[test]
+    fn shadowing_and_orphans() {
+        let chords = Chords::from_pairs(&[("N+O", "not"), ("O+T", "to"), ("K+Y", "key")]);
+        let mut words = Words::default();
+        words.import("on\nnot\nto\n");
+
+        let shadowing = shadowing(&chords, &words);
+        assert_eq!(shadowing.len(), 1);
+        assert_eq!(
+            shadowing[0].to_string(),
+            "N+O (not): shadows typing on, which presses the same keys"
+        );
+
+        let orphans = orphans(&chords, &words);
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].words(), ["key"]);
+    }
+
+    #[test]
+    fn report_unknown_keys() {
+        let chords = Chords::from_pairs(&[("TH+E", "the"), ("A+N", "and")]);
+        let mut words = Words::default();
+        words.import("the\nand\n");
+        let config = Config::default();
+
+        let warnings = validate(&chords, &words, &Layout::from_config(&config), &config);
+
+        let unknown: Vec<_> = warnings
+            .iter()
+            .filter(|warning| warning.kind() == "unknown-key")
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            unknown,
+            ["the: unknown key TH in E+TH, declare it as key.TH in the config"]
+        );
+    }
+}
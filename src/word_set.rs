@@ -0,0 +1,55 @@
+use std::{
+    collections::BTreeSet,
+    io::{ErrorKind, Result},
+    path::Path,
+};
+
+use crate::storage::{read_to_string, write};
+
+#[derive(Clone, Default)]
+pub struct WordSet(BTreeSet<String>);
+
+impl WordSet {
+    pub fn read_from_file_if_exists(path: impl AsRef<Path>) -> Result<Self> {
+        let words = match read_to_string(path) {
+            Ok(words) => words,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+
+        Ok(Self(
+            words
+                .lines()
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(ToOwned::to_owned)
+                .collect(),
+        ))
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let words: String = self.0.iter().map(|word| format!("{word}\n")).collect();
+
+        write(path, words)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.0.iter()
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.0.contains(word)
+    }
+
+    pub fn toggle(&mut self, word: &str) {
+        if !self.0.remove(word) {
+            self.0.insert(word.to_owned());
+        }
+    }
+}
+
+impl FromIterator<String> for WordSet {
+    fn from_iter<T: IntoIterator<Item = String>>(words: T) -> Self {
+        Self(words.into_iter().collect())
+    }
+}
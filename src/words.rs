@@ -1,47 +1,769 @@
-use std::{fs::read_to_string, io::Result, num::NonZeroUsize, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io::{ErrorKind, Result},
+    mem,
+    num::NonZeroUsize,
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+};
 
 use indexmap::{map::Iter, IndexMap};
 
-use crate::chords::{Chord, Chords};
+use crate::{
+    chords::{Chord, Chords, Metadata},
+    clock,
+    normalize::Normalization,
+    storage::{read_to_string, write},
+};
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Entry {
     pub rank: Option<NonZeroUsize>,
+    pub count: Option<u64>,
     pub chord: Chord,
+    pub metadata: Metadata,
 }
 
-pub struct Words(IndexMap<String, Entry>);
+#[derive(Clone, Copy)]
+pub enum Cutoff {
+    Rank(NonZeroUsize),
+    Count(u64),
+}
+
+impl Cutoff {
+    pub fn excludes(self, entry: &Entry) -> bool {
+        if !entry.chord.is_empty() {
+            return false;
+        }
+
+        match self {
+            Cutoff::Rank(max) => entry.rank.is_some_and(|rank| rank > max),
+            Cutoff::Count(min) => entry.count.is_some_and(|count| count < min),
+        }
+    }
+}
+
+impl FromStr for Cutoff {
+    type Err = ();
+
+    fn from_str(string: &str) -> std::result::Result<Self, Self::Err> {
+        match string.split_whitespace().collect::<Vec<_>>()[..] {
+            ["rank", rank] => Ok(Cutoff::Rank(rank.parse().map_err(|_| ())?)),
+            ["count", count] => Ok(Cutoff::Count(count.parse().map_err(|_| ())?)),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Grams {
+    chars: HashMap<char, Vec<usize>>,
+    trigrams: HashMap<[char; 3], Vec<usize>>,
+}
+
+#[derive(Clone, Default)]
+pub struct Words {
+    entries: IndexMap<String, Entry>,
+    index: BTreeSet<(String, String)>,
+    grams: OnceLock<Grams>,
+    top_count: u64,
+}
 
 impl Words {
     pub fn read_from_file_and_chords(path: impl AsRef<Path>, chords: &Chords) -> Result<Self> {
+        Self::read_from_file_and_chords_with_progress(path, chords, &AtomicUsize::new(0))
+    }
+
+    pub fn read_from_file_and_chords_with_progress(
+        path: impl AsRef<Path>,
+        chords: &Chords,
+        progress: &AtomicUsize,
+    ) -> Result<Self> {
         let words = read_to_string(path)?;
 
         let mut entries: IndexMap<_, _> = words
-            .split('\n')
+            .lines()
+            .inspect(|_| {
+                progress.fetch_add(1, Ordering::Relaxed);
+            })
+            .filter(|line| !line.trim().is_empty())
             .enumerate()
-            .map(|(i, word)| {
+            .map(|(i, line)| {
+                let (word, count) = parse_line(line);
                 (
                     word.to_owned(),
                     Entry {
                         rank: Some(NonZeroUsize::new(i + 1).unwrap()),
+                        count,
                         chord: Chord::default(),
+                        metadata: Metadata::default(),
                     },
                 )
             })
             .collect();
 
         for (chord, word) in chords.iter() {
-            entries.entry(word).or_default().chord = chord;
+            let metadata = chords.metadata(&word);
+            let entry = entries.entry(word).or_default();
+            entry.chord = chord;
+            entry.metadata = metadata;
+        }
+
+        Ok(Self::new(entries))
+    }
+
+    fn new(entries: IndexMap<String, Entry>) -> Self {
+        let index = entries.keys().map(|word| index_key(word)).collect();
+        let top_count = entries
+            .values()
+            .filter_map(|entry| entry.count)
+            .max()
+            .unwrap_or_default();
+
+        Self {
+            entries,
+            index,
+            grams: OnceLock::new(),
+            top_count,
+        }
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let words: String = self
+            .entries
+            .iter()
+            .take(self.ranked())
+            .map(|(word, entry)| match entry.count {
+                Some(count) => format!("{word}\t{count}\n"),
+                None => format!("{word}\n"),
+            })
+            .collect();
+
+        write(path, words)
+    }
+
+    pub fn iter(&self) -> Iter<'_, String, Entry> {
+        self.entries.iter()
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<(&String, &Entry)> {
+        self.entries.get_index(index)
+    }
+
+    pub fn index_of(&self, word: &str) -> Option<usize> {
+        self.entries.get_index_of(word)
+    }
+
+    pub fn with_prefix(&self, prefix: &str) -> impl Iterator<Item = (usize, &String, &Entry)> {
+        let prefix = prefix.to_lowercase();
+
+        let mut found: Vec<_> = self
+            .index
+            .range((prefix.clone(), String::new())..)
+            .take_while(|(folded, _)| folded.starts_with(&prefix))
+            .filter_map(|(_, word)| self.entries.get_full(word))
+            .collect();
+        found.sort_unstable_by_key(|(index, _, _)| *index);
+
+        found.into_iter()
+    }
+
+    pub fn with_chars(&self, chars: &str) -> impl Iterator<Item = (usize, &String, &Entry)> {
+        let grams = self.grams();
+        let lists = fold(chars)
+            .into_iter()
+            .map(|char| grams.chars.get(&char).map_or(&[][..], Vec::as_slice))
+            .collect();
+
+        self.candidates(lists)
+    }
+
+    pub fn with_substring(&self, text: &str) -> impl Iterator<Item = (usize, &String, &Entry)> {
+        let grams = self.grams();
+        let folded = fold(text);
+
+        // trigrams only narrow down queries long enough to contain one
+        let lists = if folded.len() < 3 {
+            folded
+                .into_iter()
+                .map(|char| grams.chars.get(&char).map_or(&[][..], Vec::as_slice))
+                .collect()
+        } else {
+            folded
+                .windows(3)
+                .map(|trigram| {
+                    let trigram = [trigram[0], trigram[1], trigram[2]];
+                    grams.trigrams.get(&trigram).map_or(&[][..], Vec::as_slice)
+                })
+                .collect()
+        };
+
+        self.candidates(lists)
+    }
+
+    fn grams(&self) -> &Grams {
+        self.grams.get_or_init(|| {
+            let mut grams = Grams::default();
+            for (position, word) in self.entries.keys().enumerate() {
+                let folded = fold(word);
+                let chars: BTreeSet<_> = folded.iter().copied().collect();
+                for char in chars {
+                    grams.chars.entry(char).or_default().push(position);
+                }
+                let trigrams: BTreeSet<_> = folded
+                    .windows(3)
+                    .map(|trigram| [trigram[0], trigram[1], trigram[2]])
+                    .collect();
+                for trigram in trigrams {
+                    grams.trigrams.entry(trigram).or_default().push(position);
+                }
+            }
+            grams
+        })
+    }
+
+    // intersects the position lists, starting from the shortest
+    fn candidates<'a>(
+        &'a self,
+        mut lists: Vec<&'a [usize]>,
+    ) -> impl Iterator<Item = (usize, &'a String, &'a Entry)> {
+        lists.sort_unstable_by_key(|positions| positions.len());
+
+        let positions: Box<dyn Iterator<Item = usize>> = if lists.is_empty() {
+            Box::new(0..self.entries.len())
+        } else {
+            let others = lists.split_off(1);
+            Box::new(lists[0].iter().copied().filter(move |position| {
+                others
+                    .iter()
+                    .all(|positions| positions.binary_search(position).is_ok())
+            }))
+        };
+        positions.filter_map(|position| {
+            let (word, entry) = self.entries.get_index(position)?;
+            Some((position, word, entry))
+        })
+    }
+
+    pub fn rank(&self, word: &str) -> Option<NonZeroUsize> {
+        self.entries.get(word).and_then(|entry| entry.rank)
+    }
+
+    pub fn frequency(&self, entry: &Entry) -> f64 {
+        match (entry.count, entry.rank) {
+            (Some(count), _) if self.top_count > 0 => count as f64 / self.top_count as f64,
+            (_, Some(rank)) => 1.0 / rank.get() as f64,
+            _ => 0.0,
+        }
+    }
+
+    pub fn ranked(&self) -> usize {
+        count_ranked(&self.entries)
+    }
+
+    fn renumber(&mut self, ranked: usize) {
+        for (index, entry) in self.entries.values_mut().take(ranked).enumerate() {
+            entry.rank = NonZeroUsize::new(index + 1);
+        }
+    }
+
+    pub fn set_rank(&mut self, word: &str, rank: NonZeroUsize) {
+        let Some(index) = self.entries.get_index_of(word) else {
+            return;
+        };
+
+        let ranked = self.ranked();
+        let last = if index < ranked { ranked - 1 } else { ranked };
+        self.entries.move_index(index, (rank.get() - 1).min(last));
+        self.grams = OnceLock::new();
+
+        self.renumber(last + 1);
+    }
+
+    pub fn import(&mut self, list: &str) -> usize {
+        let mut entries = mem::take(&mut self.entries);
+        let ranked = count_ranked(&entries);
+        let mut unranked = entries.split_off(ranked);
+
+        let list = list.strip_prefix('\u{feff}').unwrap_or(list);
+        for (word, count) in list.lines().map(parse_line) {
+            if word.is_empty() {
+                continue;
+            }
+            if let Some(count) = count {
+                self.top_count = self.top_count.max(count);
+            }
+
+            if let Some(entry) = entries.get_mut(word) {
+                entry.count = count.or(entry.count);
+            } else {
+                let mut entry = unranked.shift_remove(word).unwrap_or_default();
+                entry.count = count.or(entry.count);
+                self.index.insert(index_key(word));
+                entries.insert(word.to_owned(), entry);
+            }
+        }
+
+        let imported = entries.len() - ranked;
+        let ranked = entries.len();
+        entries.extend(unranked);
+        self.entries = entries;
+        self.grams = OnceLock::new();
+        self.renumber(ranked);
+
+        imported
+    }
+
+    pub fn replace(&mut self, list: &str) -> usize {
+        for entry in self.entries.values_mut() {
+            entry.rank = None;
+        }
+        let listed = self.import(list);
+
+        let mut entries = mem::take(&mut self.entries);
+        entries.retain(|_, entry| entry.rank.is_some() || !entry.chord.is_empty());
+        *self = Self::new(entries);
+
+        listed
+    }
+
+    /// Blends the frequency under Zipf's law with the share of the personal counts.
+    pub fn reweight(&mut self, counts: &HashMap<String, usize>, weight: f64, min_count: usize) {
+        let ranked = self.ranked();
+        let harmonic: f64 = (1..=ranked).map(|rank| 1.0 / rank as f64).sum();
+        let total = counts.values().sum::<usize>().max(1) as f64;
+
+        let mut entries = mem::take(&mut self.entries);
+        // sorted, so that equally counted new words keep a stable order
+        let mut counted: Vec<_> = counts
+            .iter()
+            .filter(|(word, count)| **count >= min_count || entries.contains_key(*word))
+            .map(|(word, _)| word)
+            .collect();
+        counted.sort_unstable();
+        for word in counted {
+            entries.entry(word.clone()).or_default();
+        }
+
+        let (mut listed, unlisted): (Vec<_>, Vec<_>) = entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, (word, entry))| {
+                let baseline = if index < ranked {
+                    1.0 / ((index + 1) as f64 * harmonic)
+                } else {
+                    0.0
+                };
+                let personal = counts
+                    .get(&word)
+                    .filter(|count| index < ranked || **count >= min_count)
+                    .map_or(0.0, |count| *count as f64 / total);
+                let score = weight * personal + (1.0 - weight) * baseline;
+                (index < ranked || personal > 0.0, score, word, entry)
+            })
+            .partition(|(listed, ..)| *listed);
+        listed.sort_by(|(_, first, ..), (_, second, ..)| second.total_cmp(first));
+
+        let ranked = listed.len();
+        let entries = listed
+            .into_iter()
+            .chain(unlisted)
+            .map(|(listed, _, word, entry)| {
+                let rank = listed.then_some(NonZeroUsize::MIN);
+                (word, Entry { rank, ..entry })
+            })
+            .collect();
+        *self = Self::new(entries);
+        self.renumber(ranked);
+    }
+
+    pub fn fold_case(&mut self) -> Vec<(String, String)> {
+        let mut groups: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+        for word in self.entries.keys() {
+            groups.entry(word.to_lowercase()).or_default().push(word);
+        }
+
+        let mut kept = BTreeMap::new();
+        for (folded, group) in groups.into_iter().filter(|(_, group)| group.len() > 1) {
+            let mut chorded = group
+                .iter()
+                .filter(|word| !self.entries[word.as_str()].chord.is_empty());
+            let spelling = match (chorded.next(), chorded.next()) {
+                // both chords are in use, so neither word can go
+                (Some(_), Some(_)) => continue,
+                (Some(word), None) => *word,
+                (None, _) => group
+                    .iter()
+                    .find(|word| **word == &folded)
+                    .unwrap_or(&group[0]),
+            };
+            kept.insert(folded, spelling.clone());
+        }
+
+        self.merge_spellings(|word| kept.get(&word.to_lowercase()).cloned())
+    }
+
+    pub fn normalize(&mut self, normalization: Normalization) -> Vec<(String, String)> {
+        self.merge_spellings(|word| Some(normalization.apply(word).into_owned()))
+    }
+
+    fn merge_spellings(
+        &mut self,
+        spelling: impl Fn(&str) -> Option<String>,
+    ) -> Vec<(String, String)> {
+        let mut merged = Vec::new();
+        let mut entries: IndexMap<String, Entry> = IndexMap::new();
+        for (word, entry) in mem::take(&mut self.entries) {
+            let spelling = spelling(&word).unwrap_or_else(|| word.clone());
+            if spelling != word {
+                merged.push((word, spelling.clone()));
+            }
+
+            let Some(slot) = entries.get_mut(&spelling) else {
+                entries.insert(spelling, entry);
+                continue;
+            };
+            slot.count = match (slot.count, entry.count) {
+                (Some(count), Some(other)) => Some(count + other),
+                (count, other) => count.or(other),
+            };
+            if slot.chord.is_empty() {
+                slot.chord = entry.chord;
+                slot.metadata = entry.metadata;
+            }
         }
 
-        Ok(Self(entries))
+        *self = Self::new(entries);
+        self.renumber(self.ranked());
+
+        merged
     }
 
-    pub fn iter(&self) -> Iter<String, Entry> {
-        self.0.iter()
+    pub fn contains(&self, word: &str) -> bool {
+        self.entries.contains_key(word)
+    }
+
+    pub fn insert(&mut self, word: String, rank: Option<NonZeroUsize>) {
+        let rank = rank.unwrap_or(NonZeroUsize::MAX);
+
+        self.index.insert(index_key(&word));
+        self.entries.entry(word.clone()).or_default();
+        self.grams = OnceLock::new();
+        self.set_rank(&word, rank);
+    }
+
+    pub fn remove(&mut self, word: &str) -> Option<Entry> {
+        let entry = self.entries.shift_remove(word)?;
+        self.index.remove(&index_key(word));
+        self.grams = OnceLock::new();
+        self.renumber(self.ranked());
+
+        Some(entry)
+    }
+
+    pub fn set_metadata(&mut self, word: &str, metadata: Metadata) {
+        if let Some(entry) = self.entries.get_mut(word) {
+            entry.metadata = metadata;
+        }
     }
 
     pub fn update_chord(&mut self, word: String, chord: Chord) {
-        self.0.entry(word).or_default().chord = chord;
+        if !self.entries.contains_key(&word) {
+            self.index.insert(index_key(&word));
+            self.grams = OnceLock::new();
+        }
+        let entry = self.entries.entry(word).or_default();
+
+        if !chord.is_empty() && chord != entry.chord {
+            let now = clock::now();
+            if entry.chord.is_empty() {
+                entry.metadata.created = Some(now);
+            }
+            entry.metadata.modified = Some(now);
+        }
+        entry.chord = chord;
+    }
+}
+
+pub fn read_counts_if_exists(path: impl AsRef<Path>) -> Result<HashMap<String, usize>> {
+    let lines = match read_to_string(path) {
+        Ok(lines) => lines,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(error) => return Err(error),
+    };
+
+    Ok(lines
+        .lines()
+        .filter_map(|line| match parse_line(line) {
+            (word, Some(count)) if !word.is_empty() => Some((word.to_owned(), count as usize)),
+            _ => None,
+        })
+        .collect())
+}
+
+pub fn write_counts(path: impl AsRef<Path>, counts: &HashMap<String, usize>) -> Result<()> {
+    let mut counts: Vec<_> = counts.iter().collect();
+    counts.sort_by(|(first, first_count), (second, second_count)| {
+        second_count.cmp(first_count).then(first.cmp(second))
+    });
+
+    write(
+        path,
+        counts
+            .into_iter()
+            .map(|(word, count)| format!("{word}\t{count}\n"))
+            .collect::<String>(),
+    )
+}
+
+fn parse_line(line: &str) -> (&str, Option<u64>) {
+    match line.split_once('\t') {
+        Some((word, count)) => match count.trim().parse() {
+            Ok(count) => (word.trim(), Some(count)),
+            Err(_) => (line.trim(), None),
+        },
+        None => (line.trim(), None),
+    }
+}
+
+fn fold(text: &str) -> Vec<char> {
+    text.chars().flat_map(char::to_lowercase).collect()
+}
+
+fn index_key(word: &str) -> (String, String) {
+    (word.to_lowercase(), word.to_owned())
+}
+
+fn count_ranked(entries: &IndexMap<String, Entry>) -> usize {
+    entries
+        .values()
+        .take_while(|entry| entry.rank.is_some())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(ranked: &[&str], unranked: &[&str]) -> Words {
+        let ranked = ranked.iter().enumerate().map(|(index, word)| {
+            let rank = NonZeroUsize::new(index + 1);
+            (
+                word.to_string(),
+                Entry {
+                    rank,
+                    ..Entry::default()
+                },
+            )
+        });
+        let unranked = unranked
+            .iter()
+            .map(|word| (word.to_string(), Entry::default()));
+
+        Words::new(ranked.chain(unranked).collect())
+    }
+
+    #[test]
+    fn set_rank() {
+        let mut words = words(&["the", "of", "and", "to"], &["chord"]);
+        let rank = |words: &Words, word| words.rank(word).map(NonZeroUsize::get);
+
+        words.set_rank("to", NonZeroUsize::new(2).unwrap());
+        assert_eq!(
+            words.entries.keys().collect::<Vec<_>>(),
+            ["the", "to", "of", "and", "chord"]
+        );
+        assert_eq!(rank(&words, "and"), Some(4));
+
+        words.set_rank("chord", NonZeroUsize::new(100).unwrap());
+        assert_eq!(rank(&words, "chord"), Some(5));
+        assert_eq!(words.ranked(), 5);
+
+        words.set_rank("the", NonZeroUsize::new(5).unwrap());
+        assert_eq!(rank(&words, "the"), Some(5));
+        assert_eq!(rank(&words, "to"), Some(1));
+
+        words.remove("of");
+        assert_eq!(rank(&words, "and"), Some(2));
+        assert_eq!(words.ranked(), 4);
+    }
+
+    #[test]
+    fn import_plain_list() {
+        let mut words = words(&["the", "of"], &["chord"]);
+
+        let imported = words.import("\u{feff}of\r\nand\n\n chord \nand\nto\n");
+
+        assert_eq!(imported, 3);
+        assert_eq!(
+            words.entries.keys().collect::<Vec<_>>(),
+            ["the", "of", "and", "chord", "to"]
+        );
+        assert_eq!(words.rank("to").map(NonZeroUsize::get), Some(5));
+    }
+
+    #[test]
+    fn replace_list() {
+        let mut words = words(&["the", "of", "and"], &["chord"]);
+        words.update_chord("of".to_owned(), "F+O".parse().unwrap());
+        words.update_chord("chord".to_owned(), "C+D".parse().unwrap());
+
+        let listed = words.replace("and\nchord\nto\n");
+
+        assert_eq!(listed, 3);
+        assert_eq!(
+            words.entries.keys().collect::<Vec<_>>(),
+            ["and", "chord", "to", "of"]
+        );
+        assert_eq!(words.rank("chord").map(NonZeroUsize::get), Some(2));
+        assert_eq!(words.rank("of"), None);
+        assert_eq!(words.entries["of"].chord.as_str(), "F+O");
+        assert_eq!(words.entries["chord"].chord.as_str(), "C+D");
+    }
+
+    #[test]
+    fn blend_personal_counts() {
+        let mut words = words(&["the", "of", "and", "to"], &["chord"]);
+        let counts = [("chord", 30), ("to", 60), ("new", 10), ("teh", 1)]
+            .into_iter()
+            .map(|(word, count)| (word.to_owned(), count))
+            .collect();
+
+        words.reweight(&counts, 0.7, 2);
+
+        assert_eq!(
+            words.entries.keys().collect::<Vec<_>>(),
+            ["to", "chord", "the", "of", "new", "and"]
+        );
+        assert_eq!(words.ranked(), 6);
+    }
+
+    #[test]
+    fn fold_case_duplicates() {
+        let mut words = words(&["The", "of", "the", "London", "OF"], &["london"]);
+        words.update_chord("London".to_owned(), "L+N".parse().unwrap());
+
+        let folded = words.fold_case();
+
+        assert_eq!(
+            folded,
+            [
+                ("The".to_owned(), "the".to_owned()),
+                ("OF".to_owned(), "of".to_owned()),
+                ("london".to_owned(), "London".to_owned()),
+            ]
+        );
+        assert_eq!(
+            words.entries.keys().collect::<Vec<_>>(),
+            ["the", "of", "London"]
+        );
+        assert_eq!(words.rank("London").map(NonZeroUsize::get), Some(3));
+        assert_eq!(words.with_prefix("lon").count(), 1);
+    }
+
+    #[test]
+    fn count_frequencies() {
+        let mut words = words(&["the", "of"], &[]);
+
+        words.import("of\t500\nand\t250\nto\tmany\n");
+        words.import("The\t100\n");
+        words.fold_case();
+
+        let frequency = |word| {
+            let index = words.index_of(word).unwrap();
+            words.frequency(words.get_index(index).unwrap().1)
+        };
+        assert_eq!(words.rank("to\tmany").map(NonZeroUsize::get), Some(4));
+        assert_eq!(frequency("the"), 0.2);
+        assert_eq!(frequency("of"), 1.0);
+        assert_eq!(frequency("and"), 0.5);
+    }
+
+    #[test]
+    fn cutoff_keeps_chorded() {
+        let mut words = words(&["the", "of", "and"], &["zyx"]);
+        words.update_chord("and".to_owned(), "AD".parse().unwrap());
+        let cutoff: Cutoff = "rank 1".parse().unwrap();
+
+        let kept: Vec<_> = words
+            .iter()
+            .filter(|(_, entry)| !cutoff.excludes(entry))
+            .map(|(word, _)| word)
+            .collect();
+
+        assert_eq!(kept, ["the", "and", "zyx"]);
+        assert!("count".parse::<Cutoff>().is_err());
+    }
+
+    #[test]
+    fn prefix_scan() {
+        let mut words = words(&["the", "of", "There", "than", "then"], &["Th"]);
+        words.remove("than");
+
+        let found: Vec<_> = words.with_prefix("TH").map(|(_, word, _)| word).collect();
+
+        assert_eq!(found, ["the", "There", "then", "Th"]);
+    }
+
+    #[test]
+    fn char_scan() {
+        let mut words = words(&["the", "of", "Zoo", "than"], &["haze"]);
+        let found = |words: &Words, chars| {
+            words
+                .with_chars(chars)
+                .map(|(_, word, _)| word.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(found(&words, "zh"), ["haze"]);
+        assert_eq!(found(&words, "Oz"), ["Zoo"]);
+        assert_eq!(found(&words, "q"), Vec::<String>::new());
+
+        words.remove("Zoo");
+        words.insert("zero".to_owned(), None);
+        assert_eq!(found(&words, "z"), ["zero", "haze"]);
+    }
+
+    #[test]
+    fn substring_scan() {
+        // pseudo-random words over few letters share most of their characters
+        let mut state = 7_u32;
+        let mut list: Vec<String> = (0..5000)
+            .map(|_| {
+                (0..8)
+                    .map(|_| {
+                        state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                        char::from(b"aehnrst"[(state >> 16) as usize % 7])
+                    })
+                    .collect()
+            })
+            .collect();
+        list.push("Northern".to_owned());
+        let words = words(&list.iter().map(String::as_str).collect::<Vec<_>>(), &[]);
+
+        let by_chars = words.with_chars("thern").count();
+        let found: Vec<_> = words
+            .with_substring("THERN")
+            .map(|(_, word, _)| word.clone())
+            .filter(|word| word.to_lowercase().contains("thern"))
+            .collect();
+        let by_trigrams = words.with_substring("thern").count();
+
+        assert!(found.contains(&"Northern".to_owned()));
+        assert_eq!(
+            found.len(),
+            list.iter()
+                .filter(|word| word.to_lowercase().contains("thern"))
+                .count()
+        );
+        assert!(by_trigrams * 20 < by_chars, "{by_trigrams} vs {by_chars}");
+        assert_eq!(
+            words.with_substring("th").count(),
+            words.with_chars("th").count()
+        );
     }
 }